@@ -1,14 +1,14 @@
 use std::sync::Arc;
 
 use crate::{
-    queries::get_user_by_name_query::GetUserByNameQuery,
-    services::user_service::{User, UserService},
+    messages::user_created_event::UserCreatedEvent,
+    queries::get_user_by_name_query::{GetUserByNameQuery, USERS_CACHE_TAG},
 };
 use async_trait::async_trait;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::ports::bus::service_bus_port::ServiceBusPort;
 use kti_cqrs_provider_rs::{
     kti_cqrs_rs::{errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort},
+    ports::cache_invalidation_port::CacheInvalidationPort,
     provider::cqrs_provider::CqrsProvider,
 };
 
@@ -26,28 +26,29 @@ impl CreateSafeUserCommand {
     }
 }
 
+impl CacheInvalidationPort for CreateSafeUserCommand {
+    fn get_invalidated_tags(&self) -> Vec<String> {
+        vec![USERS_CACHE_TAG.to_string()]
+    }
+}
+
 #[async_trait]
 impl CommandHandlerPort for CreateSafeUserCommand {
     type Context = Arc<dyn ContextPort>;
     type Output = ();
 
     async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
-        let service = UserService::get_adapter(&context).await?;
-
         let bus = CqrsProvider::get_adapter(&context).await?;
 
         let check_user_query = GetUserByNameQuery::new(&self.name);
 
-        let user = bus.query(Box::new(check_user_query)).await?;
+        let user = bus.send_query(Box::new(check_user_query)).await?;
 
         if user.is_some() {
             return Err("User already exists".into());
         }
 
-        service
-            .create_user(User::new(&self.name, &self.email))
-            .await?;
-
-        Ok(())
+        bus.publish_and_wait(UserCreatedEvent::new(&self.name, &self.email))
+            .await
     }
 }
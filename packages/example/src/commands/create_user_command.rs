@@ -2,11 +2,16 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::{
-    errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort,
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::{errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort},
+    ports::cache_invalidation_port::CacheInvalidationPort,
+    provider::cqrs_provider::CqrsProvider,
 };
 
-use crate::services::user_service::{User, UserService};
+use crate::{
+    messages::user_created_event::UserCreatedEvent,
+    queries::get_user_by_name_query::USERS_CACHE_TAG,
+};
 
 pub struct CreateUserCommand {
     name: String,
@@ -22,18 +27,21 @@ impl CreateUserCommand {
     }
 }
 
+impl CacheInvalidationPort for CreateUserCommand {
+    fn get_invalidated_tags(&self) -> Vec<String> {
+        vec![USERS_CACHE_TAG.to_string()]
+    }
+}
+
 #[async_trait]
 impl CommandHandlerPort for CreateUserCommand {
     type Context = Arc<dyn ContextPort>;
     type Output = ();
 
     async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
-        let service = UserService::get_adapter(&context).await?;
-
-        service
-            .create_user(User::new(&self.name, &self.email))
-            .await?;
+        let bus = CqrsProvider::get_adapter(&context).await?;
 
-        Ok(())
+        bus.publish_and_wait(UserCreatedEvent::new(&self.name, &self.email))
+            .await
     }
 }
@@ -3,11 +3,16 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::{
-    errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort,
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::{errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort},
+    ports::cache_invalidation_port::CacheInvalidationPort,
+    provider::cqrs_provider::CqrsProvider,
 };
 
-use crate::services::user_service::UserService;
+use crate::{
+    messages::user_email_changed_event::UserEmailChangedEvent,
+    queries::get_user_by_name_query::USERS_CACHE_TAG, services::user_service::UserService,
+};
 
 pub struct UpdateUserCommand {
     name: String,
@@ -23,6 +28,12 @@ impl UpdateUserCommand {
     }
 }
 
+impl CacheInvalidationPort for UpdateUserCommand {
+    fn get_invalidated_tags(&self) -> Vec<String> {
+        vec![USERS_CACHE_TAG.to_string()]
+    }
+}
+
 #[async_trait]
 impl CommandHandlerPort for UpdateUserCommand {
     type Context = Arc<dyn ContextPort>;
@@ -31,8 +42,13 @@ impl CommandHandlerPort for UpdateUserCommand {
     async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
         let service = UserService::get_adapter(&context).await?;
 
-        service.update_user_email(&self.name, &self.email).await?;
+        if service.get_user_by_name(&self.name).await?.is_none() {
+            return Err("Cant find user by name.".into());
+        }
+
+        let bus = CqrsProvider::get_adapter(&context).await?;
 
-        Ok(())
+        bus.publish_and_wait(UserEmailChangedEvent::new(&self.name, &self.email))
+            .await
     }
 }
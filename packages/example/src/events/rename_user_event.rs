@@ -2,11 +2,15 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::{
-    errors::error::Error, ports::handler::event_handler_port::EventHandlerPort,
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::{errors::error::Error, ports::handler::event_handler_port::EventHandlerPort},
+    provider::{cqrs_provider::CqrsProvider, query_cache_provider::QueryCacheProvider},
 };
 
-use crate::services::user_service::UserService;
+use crate::{
+    messages::user_name_changed_event::UserNameChangedEvent,
+    queries::get_user_by_name_query::USERS_CACHE_TAG, services::user_service::UserService,
+};
 
 pub struct RenameUserEvent {
     current_name: String,
@@ -29,9 +33,25 @@ impl EventHandlerPort for RenameUserEvent {
     async fn execute(&self, context: Self::Context) -> Result<(), Error> {
         let service = UserService::get_adapter(&context).await?;
 
-        service
-            .update_user_name(&self.current_name, &self.new_name)
-            .await?;
+        if service
+            .get_user_by_name(&self.current_name)
+            .await?
+            .is_none()
+        {
+            return Err("Cant find user by name.".into());
+        }
+
+        let bus = CqrsProvider::get_adapter(&context).await?;
+
+        bus.publish_and_wait(UserNameChangedEvent::new(
+            &self.current_name,
+            &self.new_name,
+        ))
+        .await?;
+
+        QueryCacheProvider::resolve(&context)
+            .await?
+            .invalidate(&[USERS_CACHE_TAG.to_string()]);
 
         Ok(())
     }
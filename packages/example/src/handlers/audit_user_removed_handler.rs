@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error, ports::event_subscriber_port::EventSubscriberPort,
+};
+
+use crate::{
+    messages::user_removed_event::UserRemovedEvent, services::audit_service::AuditService,
+};
+
+pub struct AuditUserRemovedHandler;
+
+#[async_trait]
+impl EventSubscriberPort<UserRemovedEvent> for AuditUserRemovedHandler {
+    async fn handle(
+        &self,
+        event: &UserRemovedEvent,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<(), Error> {
+        let service = AuditService::get_adapter(&context).await?;
+
+        service
+            .record(&format!("User removed: {}", event.get_name()))
+            .await
+    }
+}
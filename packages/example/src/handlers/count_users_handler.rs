@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error, ports::query_message_handler_port::QueryMessageHandlerPort,
+};
+
+use crate::{messages::count_users_query::CountUsersQuery, services::user_service::UserService};
+
+pub struct CountUsersHandler;
+
+#[async_trait]
+impl QueryMessageHandlerPort<CountUsersQuery> for CountUsersHandler {
+    async fn handle(
+        &self,
+        _: &CountUsersQuery,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<usize, Error> {
+        let service = UserService::get_adapter(&context).await?;
+
+        service.count_users().await
+    }
+}
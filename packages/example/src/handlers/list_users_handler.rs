@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error, models::page::Page,
+    ports::query_message_handler_port::QueryMessageHandlerPort,
+};
+
+use crate::{
+    messages::list_users_query::ListUsersQuery,
+    services::user_service::{User, UserService},
+};
+
+pub struct ListUsersHandler;
+
+#[async_trait]
+impl QueryMessageHandlerPort<ListUsersQuery> for ListUsersHandler {
+    async fn handle(
+        &self,
+        query: &ListUsersQuery,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<Page<User>, Error> {
+        let service = UserService::get_adapter(&context).await?;
+
+        service.list_users(query.get_page()).await
+    }
+}
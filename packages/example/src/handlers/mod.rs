@@ -0,0 +1,5 @@
+pub mod audit_user_removed_handler;
+pub mod count_users_handler;
+pub mod list_users_handler;
+pub mod remove_user_handler;
+pub mod users_by_name_loader;
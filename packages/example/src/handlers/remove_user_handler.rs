@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::command_message_handler_port::CommandMessageHandlerPort,
+    provider::cqrs_provider::CqrsProvider,
+};
+
+use crate::{
+    messages::{remove_user_command::RemoveUserCommand, user_removed_event::UserRemovedEvent},
+    services::user_service::UserService,
+};
+
+pub struct RemoveUserHandler;
+
+#[async_trait]
+impl CommandMessageHandlerPort<RemoveUserCommand> for RemoveUserHandler {
+    async fn handle(
+        &self,
+        command: &RemoveUserCommand,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<(), Error> {
+        let service = UserService::get_adapter(&context).await?;
+
+        if service
+            .get_user_by_name(command.get_name())
+            .await?
+            .is_none()
+        {
+            return Err("Cant find user by name.".into());
+        }
+
+        let bus = CqrsProvider::get_adapter(&context).await?;
+
+        bus.publish_and_wait(UserRemovedEvent::new(command.get_name()))
+            .await
+    }
+}
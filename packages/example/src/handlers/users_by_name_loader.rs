@@ -0,0 +1,60 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error, ports::batch_loader_port::BatchLoaderPort,
+};
+
+use crate::services::user_service::{User, UserService};
+
+#[derive(Default)]
+pub struct UsersByNameLoader {
+    batches: AtomicUsize,
+    keys: AtomicUsize,
+}
+
+impl UsersByNameLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_batches(&self) -> usize {
+        self.batches.load(Ordering::SeqCst)
+    }
+
+    pub fn get_keys(&self) -> usize {
+        self.keys.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl BatchLoaderPort for UsersByNameLoader {
+    type Key = String;
+    type Value = User;
+
+    async fn load(
+        &self,
+        keys: Vec<String>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<HashMap<String, User>, Error> {
+        self.batches.fetch_add(1, Ordering::SeqCst);
+
+        self.keys.fetch_add(keys.len(), Ordering::SeqCst);
+
+        let service = UserService::get_adapter(&context).await?;
+
+        let users = service.get_users_by_names(&keys).await?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| (user.get_name().to_string(), user))
+            .collect())
+    }
+}
@@ -1,5 +1,8 @@
 pub mod commands;
 pub mod events;
+pub mod handlers;
+pub mod messages;
+pub mod projections;
 pub mod queries;
 pub mod services;
 
@@ -11,11 +14,22 @@ use commands::create_user_command::CreateUserCommand;
 use commands::update_user_command::UpdateUserCommand;
 use events::rename_user_event::RenameUserEvent;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::ports::bus::service_bus_port::ServiceBusPort;
 use kti_cqrs_provider_rs::{
-    kti_cqrs_rs::errors::error::Error, provider::cqrs_provider::CqrsProvider,
+    kti_cqrs_rs::errors::error::Error,
+    models::{page::Page, page_request::PageRequest},
+    ports::{
+        cache_invalidation_port::CacheInvalidationPort, stream_query_handler_port::QueryStream,
+    },
+    provider::cqrs_provider::CqrsProvider,
+};
+use messages::{
+    count_users_query::CountUsersQuery, list_users_query::ListUsersQuery,
+    remove_user_command::RemoveUserCommand,
+};
+use queries::{
+    export_users_query::ExportUsersQuery, get_user_by_name_query::GetUserByNameQuery,
+    get_users_by_names_query::GetUsersByNamesQuery,
 };
-use queries::get_user_by_name_query::GetUserByNameQuery;
 use services::user_service::User;
 
 pub struct UserController {
@@ -39,7 +53,36 @@ impl UserController {
 
         let query = GetUserByNameQuery::new(name);
 
-        bus.query(Box::new(query)).await
+        bus.send_query(Box::new(query)).await
+    }
+
+    pub async fn get_cached_user_by_name(&self, name: &str) -> Result<Option<User>, Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        let query = GetUserByNameQuery::new(name);
+
+        let policy = query.get_cache_policy();
+
+        bus.cached_query(Box::new(query), policy).await
+    }
+
+    pub async fn get_users_by_names(&self, names: &[&str]) -> Result<Vec<Option<User>>, Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        bus.send_query(Box::new(GetUsersByNamesQuery::new(names)))
+            .await
+    }
+
+    pub async fn list_users(&self, request: PageRequest) -> Result<Page<User>, Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        bus.dispatch_query(ListUsersQuery::new(request)).await
+    }
+
+    pub async fn export_users(&self) -> Result<QueryStream<User>, Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        bus.stream_query(Box::new(ExportUsersQuery)).await
     }
 
     pub async fn create_user(&self, name: &str, email: &str) -> Result<(), Error> {
@@ -47,7 +90,22 @@ impl UserController {
 
         let command = CreateUserCommand::new(name, email);
 
-        bus.command(Box::new(command)).await?;
+        bus.command_with_invalidation(Box::new(command)).await?;
+
+        Ok(())
+    }
+
+    pub async fn create_user_once(&self, key: &str, name: &str, email: &str) -> Result<(), Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        let command = CreateUserCommand::new(name, email);
+
+        let tags = command.get_invalidated_tags();
+
+        bus.command_with_idempotency_key(Box::new(command), key)
+            .await?;
+
+        bus.get_query_cache().await?.invalidate(&tags);
 
         Ok(())
     }
@@ -57,7 +115,7 @@ impl UserController {
 
         let command = CreateSafeUserCommand::new(name, email);
 
-        bus.command(Box::new(command)).await?;
+        bus.command_with_invalidation(Box::new(command)).await?;
 
         Ok(())
     }
@@ -67,7 +125,7 @@ impl UserController {
 
         let command = UpdateUserCommand::new(name, email);
 
-        bus.command(Box::new(command)).await?;
+        bus.command_with_invalidation(Box::new(command)).await?;
 
         Ok(())
     }
@@ -77,224 +135,4886 @@ impl UserController {
 
         let event = RenameUserEvent::new(current_name, new_name);
 
-        bus.event(Box::new(event)).await?;
+        bus.send_event(Box::new(event)).await?;
 
         Ok(())
     }
+
+    pub async fn update_user_name_and_wait(
+        &self,
+        current_name: &str,
+        new_name: &str,
+    ) -> Result<(), Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        let event = RenameUserEvent::new(current_name, new_name);
+
+        bus.event_and_wait(Box::new(event)).await
+    }
+
+    pub async fn remove_user(&self, name: &str) -> Result<(), Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        bus.dispatch_command(RemoveUserCommand::new(name)).await
+    }
+
+    pub async fn count_users(&self) -> Result<usize, Error> {
+        let bus = CqrsProvider::get_adapter(&self.context).await?;
+
+        bus.dispatch_cached_query(CountUsersQuery).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{sync::Arc, time::Duration};
+    use std::{
+        any::type_name,
+        collections::BTreeMap,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicU32, Ordering},
+        },
+        time::{Duration, SystemTime},
+    };
 
+    use futures::{StreamExt, future, future::BoxFuture, poll, stream};
+    use handlers::{
+        audit_user_removed_handler::AuditUserRemovedHandler,
+        count_users_handler::CountUsersHandler, list_users_handler::ListUsersHandler,
+        remove_user_handler::RemoveUserHandler, users_by_name_loader::UsersByNameLoader,
+    };
     use ioc_container_rs::{
         container::di::{DI, InjectAdapter},
         context::container_context::ContainerContext,
     };
-    use kti_cqrs_provider_rs::di::create_cqrs_provider_di::create_cqrs_provider_di;
-    use services::user_service::UserService;
-    use tokio::{sync::RwLock, time::sleep};
+    use kti_cqrs_provider_rs::kti_cqrs_rs::ports::{
+        bus::{event_bus_port::EventBusPort, service_bus_port::ServiceBusPort},
+        handler::{
+            command_handler_port::CommandHandlerPort, event_handler_port::EventHandlerPort,
+            query_handler_port::QueryHandlerPort,
+        },
+    };
+    use kti_cqrs_provider_rs::{
+        adapters::{
+            file_event_store_adapter::FileEventStoreAdapter,
+            file_outbox_store_adapter::FileOutboxStoreAdapter,
+            in_memory_checkpoint_store_adapter::InMemoryCheckpointStoreAdapter,
+            in_memory_dead_letter_store_adapter::InMemoryDeadLetterStoreAdapter,
+            in_memory_event_store_adapter::InMemoryEventStoreAdapter,
+            in_memory_idempotency_store_adapter::InMemoryIdempotencyStoreAdapter,
+            in_memory_outbox_store_adapter::InMemoryOutboxStoreAdapter,
+            in_memory_schedule_store_adapter::InMemoryScheduleStoreAdapter,
+            manual_clock_adapter::ManualClockAdapter,
+        },
+        di::create_cqrs_provider_di::create_cqrs_provider_di,
+        errors::{
+            concurrency_error::ConcurrencyError,
+            idempotency_conflict_error::IdempotencyConflictError,
+            projection_error::ProjectionError, shared_error::SharedError,
+            timeout_error::TimeoutError, transient_error::TransientError,
+        },
+        models::{
+            cache_policy::CachePolicy, cancellation_token::CancellationToken,
+            cron_schedule::CronSchedule, data_loader::DataLoader, event_data::EventData,
+            event_failure::EventFailure, expected_version::ExpectedVersion,
+            message_context::MessageContext, message_envelope::MessageEnvelope,
+            message_info::MessageInfo, message_kind::MessageKind, overflow_policy::OverflowPolicy,
+            page_sort::PageSort, poison_event_policy::PoisonEventPolicy, principal::Principal,
+            recurring_job::RecurringJob, retry_policy::RetryPolicy, saga_status::SagaStatus,
+            scheduled_record::ScheduledRecord, stored_event::StoredEvent,
+        },
+        ports::{
+            aggregate_event_port::AggregateEventPort,
+            aggregate_port::AggregatePort,
+            clock_port::ClockPort,
+            event_failure_handler_port::EventFailureHandlerPort,
+            event_message_port::EventMessagePort,
+            event_store_port::EventStorePort,
+            event_subscriber_port::EventSubscriberPort,
+            idempotency_store_port::IdempotencyStorePort,
+            middleware_port::{MiddlewareNext, MiddlewarePort},
+            outbox_message_port::OutboxMessagePort,
+            outbox_store_port::OutboxStorePort,
+            projection_port::ProjectionPort,
+            query_message_port::QueryMessagePort,
+            saga_handler_port::SagaHandlerPort,
+            saga_port::SagaPort,
+            schedule_store_port::ScheduleStorePort,
+            stream_query_handler_port::StreamQueryHandlerPort,
+        },
+        provider::{
+            batch_loader_provider::BatchLoaderProvider, dead_letter_provider::DeadLetterProvider,
+            event_bus_limits_provider::EventBusLimitsProvider,
+            event_failure_provider::EventFailureProvider, event_store_provider::EventStoreProvider,
+            event_subscriptions_provider::EventSubscriptionsProvider,
+            handler_registry_provider::HandlerRegistryProvider,
+            idempotency_provider::IdempotencyProvider, middleware_provider::MiddlewareProvider,
+            outbox_provider::OutboxProvider, projection_provider::ProjectionProvider,
+            recurring_jobs_provider::RecurringJobsProvider, retry_provider::RetryProvider,
+            scheduler_provider::SchedulerProvider, timeout_provider::TimeoutProvider,
+        },
+    };
+    use messages::{
+        user_created_event::UserCreatedEvent, user_email_changed_event::UserEmailChangedEvent,
+        user_name_changed_event::UserNameChangedEvent, user_removed_event::UserRemovedEvent,
+    };
+    use projections::users_projection::UsersProjection;
+    use services::{audit_service::AuditService, user_service::UserService};
+    use tokio::{
+        sync::{
+            RwLock, Semaphore,
+            mpsc::{self, UnboundedSender},
+        },
+        time::{sleep, timeout},
+    };
 
     use super::*;
 
-    fn get_users() -> Vec<User> {
-        vec![
-            User::new("Andrey", "andrey@mail.domain"),
-            User::new("Daria", "daria@mail.domain"),
-            User::new("Kirill", "kirill@mail.domain"),
-        ]
+    struct CountingStreamQuery {
+        produced: Arc<AtomicU32>,
+        stall_after: Option<u32>,
     }
 
-    async fn create_di() -> Result<DI, Error> {
-        let di = DI::new(Arc::new(ContainerContext::new()));
+    #[async_trait]
+    impl StreamQueryHandlerPort for CountingStreamQuery {
+        type Item = u32;
 
-        let di = create_cqrs_provider_di(di).await?;
+        async fn execute(&self, _: Arc<dyn ContextPort>) -> Result<QueryStream<u32>, Error> {
+            let stall_after = self.stall_after;
 
-        let store = Arc::new(RwLock::new(get_users()));
+            let items = stream::unfold(self.produced.clone(), move |produced| async move {
+                if stall_after == Some(produced.load(Ordering::SeqCst)) {
+                    future::pending::<()>().await;
+                }
 
-        let di = di
-            .inject(InjectAdapter {
-                token: UserService::token(),
-                factory: Arc::new(move |_| UserService::new(store.clone())),
-            })
-            .await?;
+                let item = produced.fetch_add(1, Ordering::SeqCst) + 1;
 
-        let di = di
-            .inject(InjectAdapter {
-                token: UserController::token(),
-                factory: Arc::new(|context| UserController::new(context)),
-            })
-            .await?;
+                Some((Ok(item), produced))
+            });
 
-        Ok(di)
+            Ok(Box::pin(items))
+        }
     }
 
-    #[tokio::test]
-    async fn should_get_user_by_name() {
-        let di = create_di().await.expect("Cant create DI");
+    struct ChannelEventFailureHandler {
+        sender: UnboundedSender<EventFailure>,
+    }
 
-        let context = di.get_context();
+    #[async_trait]
+    impl EventFailureHandlerPort for ChannelEventFailureHandler {
+        async fn handle(&self, failure: EventFailure) {
+            let _ = self.sender.send(failure);
+        }
+    }
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+    struct SlowEvent {
+        delay: Duration,
+    }
 
-        let user_name = "Andrey";
+    #[async_trait]
+    impl EventHandlerPort for SlowEvent {
+        type Context = Arc<dyn ContextPort>;
 
-        let user = controller
-            .get_user_by_name(&user_name)
-            .await
-            .expect("Cant get user");
+        async fn execute(&self, _: Self::Context) -> Result<(), Error> {
+            sleep(self.delay).await;
 
-        assert!(user.is_some());
+            Ok(())
+        }
+    }
 
-        let user = user.unwrap();
+    struct StepEvent {
+        name: &'static str,
+        delay: Duration,
+        records: Arc<std::sync::Mutex<Vec<String>>>,
+    }
 
-        assert_eq!(user.get_name(), user_name);
+    #[async_trait]
+    impl EventHandlerPort for StepEvent {
+        type Context = Arc<dyn ContextPort>;
+
+        async fn execute(&self, _: Self::Context) -> Result<(), Error> {
+            self.records
+                .lock()
+                .unwrap()
+                .push(format!("start {}", self.name));
+
+            sleep(self.delay).await;
+
+            self.records
+                .lock()
+                .unwrap()
+                .push(format!("end {}", self.name));
+
+            Ok(())
+        }
     }
 
-    #[tokio::test]
-    async fn should_create_new_user() {
-        let di = create_di().await.expect("Cant create DI");
+    struct RelayEvent {
+        delivered: Arc<AtomicU32>,
+        nested: bool,
+    }
 
-        let context = di.get_context();
+    #[async_trait]
+    impl EventHandlerPort for RelayEvent {
+        type Context = Arc<dyn ContextPort>;
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+        async fn execute(&self, context: Self::Context) -> Result<(), Error> {
+            if self.nested {
+                self.delivered.fetch_add(1, Ordering::SeqCst);
 
-        let user_name = "Rita";
+                return Ok(());
+            }
 
-        let user_email = "rita@mail.domain";
+            let bus = CqrsProvider::get_adapter(&context).await?;
 
-        controller
-            .create_user(&user_name, &user_email)
+            bus.send_event(Box::new(RelayEvent {
+                delivered: self.delivered.clone(),
+                nested: true,
+            }))
             .await
-            .expect("Cant create user");
+        }
+    }
 
-        let user = controller
-            .get_user_by_name(&user_name)
-            .await
-            .expect("Cant get user");
+    struct RecordingMiddleware {
+        records: Arc<std::sync::Mutex<Vec<String>>>,
+        rejected: Option<MessageKind>,
+    }
 
-        assert!(user.is_some());
+    #[async_trait]
+    impl MiddlewarePort for RecordingMiddleware {
+        async fn before(&self, message: &MessageInfo) -> Result<(), Error> {
+            self.records
+                .lock()
+                .unwrap()
+                .push(format!("before {:?}", message.get_kind()));
 
-        let user = user.unwrap();
+            if self.rejected == Some(message.get_kind()) {
+                return Err("Forbidden".into());
+            }
 
-        assert_eq!(user.get_name(), user_name);
-        assert_eq!(user.get_email(), user_email);
+            Ok(())
+        }
+
+        fn after(&self, message: &MessageInfo, result: Result<(), &Error>) -> Result<(), Error> {
+            self.records.lock().unwrap().push(format!(
+                "after {:?} {}",
+                message.get_kind(),
+                result.is_ok()
+            ));
+
+            Ok(())
+        }
     }
 
-    #[tokio::test]
-    async fn should_be_created_safe_user() {
-        let di = create_di().await.expect("Cant create DI");
+    struct AroundMiddleware {
+        records: Arc<std::sync::Mutex<Vec<String>>>,
+    }
 
-        let context = di.get_context();
+    #[async_trait]
+    impl MiddlewarePort for AroundMiddleware {
+        async fn around(
+            &self,
+            message: &MessageInfo,
+            next: MiddlewareNext<'_>,
+        ) -> Result<(), Error> {
+            let name = message.get_type_name().unwrap_or("unknown");
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+            self.records.lock().unwrap().push(format!("enter {}", name));
 
-        let user_name = "Rita";
-        let user_email = "rita@mail.domain";
+            let result = next.await;
 
-        controller
-            .create_safe_user(&user_name, &user_email)
-            .await
-            .expect("Cant create user");
+            self.records
+                .lock()
+                .unwrap()
+                .push(format!("exit {} {}", name, result.is_ok()));
 
-        let user = controller
-            .get_user_by_name(&user_name)
-            .await
-            .expect("Cant get user");
+            result
+        }
+    }
 
-        assert!(user.is_some());
+    struct FailingSubscriber;
 
-        let user = user.unwrap();
+    #[async_trait]
+    impl EventSubscriberPort<UserRemovedEvent> for FailingSubscriber {
+        async fn handle(&self, _: &UserRemovedEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            Err("Mailbox is unavailable".into())
+        }
+    }
 
-        assert_eq!(user.get_name(), user_name);
-        assert_eq!(user.get_email(), user_email);
+    struct FlakyCommand {
+        attempts: Arc<AtomicU32>,
+        failures: u32,
+        transient: bool,
     }
 
-    #[tokio::test]
-    async fn should_be_not_created_safe_user() {
-        let di = create_di().await.expect("Cant create DI");
+    #[async_trait]
+    impl CommandHandlerPort for FlakyCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = u32;
 
-        let context = di.get_context();
+        async fn execute(&self, _: Self::Context) -> Result<Self::Output, Error> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+            if attempt <= self.failures && self.transient {
+                return Err(TransientError::new("Lock contention").into());
+            }
 
-        let user_name = "Andrey";
-        let user_email = "andrey@mail.domain";
+            if attempt <= self.failures {
+                return Err("Name is invalid".into());
+            }
+
+            Ok(attempt)
+        }
+    }
 
-        let user_creation = controller.create_safe_user(&user_name, &user_email).await;
+    struct FlakyEvent;
 
-        assert!(user_creation.is_err());
+    impl EventMessagePort for FlakyEvent {}
+
+    struct FlakySubscriber {
+        attempts: Arc<AtomicU32>,
+        failures: u32,
     }
 
-    #[tokio::test]
-    async fn should_update_user_email() {
-        let di = create_di().await.expect("Cant create DI");
+    #[async_trait]
+    impl EventSubscriberPort<FlakyEvent> for FlakySubscriber {
+        async fn handle(&self, _: &FlakyEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
 
-        let context = di.get_context();
+            if attempt <= self.failures {
+                return Err("Temporary I/O error".into());
+            }
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+            Ok(())
+        }
+    }
 
-        let user_name = "Andrey";
+    struct FlakyUserCreatedSubscriber {
+        attempts: Arc<AtomicU32>,
+    }
 
-        let user_email = "andreyddk@mail.domain";
+    #[async_trait]
+    impl EventSubscriberPort<UserCreatedEvent> for FlakyUserCreatedSubscriber {
+        async fn handle(&self, _: &UserCreatedEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err("Mailbox is unavailable".into());
+            }
 
-        controller
-            .update_user_email(&user_name, &user_email)
-            .await
-            .expect("Cant update user");
+            Ok(())
+        }
+    }
 
-        let user = controller
-            .get_user_by_name(&user_name)
-            .await
-            .expect("Cant get user");
+    struct SlowQuery {
+        cancelled: Arc<AtomicU32>,
+    }
 
-        assert!(user.is_some());
+    #[async_trait]
+    impl QueryHandlerPort for SlowQuery {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
 
-        let user = user.unwrap();
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let cancellation = MessageContext::from_context(&context)
+                .ok_or("Message context is missing")?
+                .get_cancellation();
 
-        assert_eq!(user.get_name(), user_name);
-        assert_eq!(user.get_email(), user_email);
+            let slow = Box::pin(sleep(Duration::from_secs(10)));
+
+            if let future::Either::Left(_) =
+                future::select(Box::pin(cancellation.cancelled()), slow).await
+            {
+                self.cancelled.fetch_add(1, Ordering::SeqCst);
+
+                return Err("Query was cancelled".into());
+            }
+
+            Ok(())
+        }
     }
 
-    #[tokio::test]
-    async fn should_update_user_name_by_event() {
-        let di = create_di().await.expect("Cant create DI");
+    struct OrderedEvent {
+        key: &'static str,
+        index: u64,
+    }
 
-        let context = di.get_context();
+    impl EventMessagePort for OrderedEvent {
+        fn get_partition_key(&self) -> Option<String> {
+            Some(self.key.to_string())
+        }
+    }
 
-        let controller = UserController::get_adapter(&context)
-            .await
-            .expect("Cant resolve USER_CONTROLLER");
+    struct OrderedSubscriber {
+        records: Arc<RwLock<Vec<String>>>,
+    }
 
-        let current_user_name = "Daria";
-        let new_user_name = "Rita";
+    #[async_trait]
+    impl EventSubscriberPort<OrderedEvent> for OrderedSubscriber {
+        async fn handle(&self, event: &OrderedEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            sleep(Duration::from_millis(50 - event.index * 10)).await;
 
-        controller
-            .update_user_name(&current_user_name, &new_user_name)
-            .await
-            .expect("Cant update user");
+            self.records
+                .write()
+                .await
+                .push(format!("{}{}", event.key, event.index));
 
-        sleep(Duration::from_secs(1)).await;
+            Ok(())
+        }
+    }
 
-        let user = controller
-            .get_user_by_name(&new_user_name)
-            .await
-            .expect("Cant get user");
+    struct AuditedEvent {
+        message: String,
+    }
 
-        assert!(user.is_some());
+    impl EventMessagePort for AuditedEvent {}
 
-        let user = user.unwrap();
+    impl OutboxMessagePort for AuditedEvent {
+        fn get_name() -> &'static str {
+            "AuditedEvent"
+        }
 
-        assert_eq!(user.get_name(), new_user_name);
+        fn encode(&self) -> Result<String, Error> {
+            Ok(self.message.clone())
+        }
+
+        fn decode(payload: &str) -> Result<Self, Error> {
+            Ok(Self {
+                message: payload.to_string(),
+            })
+        }
+    }
+
+    struct ToggleSubscriber {
+        failing: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl EventSubscriberPort<AuditedEvent> for ToggleSubscriber {
+        async fn handle(&self, _: &AuditedEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err("Audit is unavailable".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    struct ToggleEvent {
+        failing: Arc<AtomicBool>,
+        attempts: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl EventHandlerPort for ToggleEvent {
+        type Context = Arc<dyn ContextPort>;
+
+        async fn execute(&self, _: Self::Context) -> Result<(), Error> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+
+            if self.failing.load(Ordering::SeqCst) {
+                return Err("Event is unavailable".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    struct AuditedSubscriber;
+
+    #[async_trait]
+    impl EventSubscriberPort<AuditedEvent> for AuditedSubscriber {
+        async fn handle(
+            &self,
+            event: &AuditedEvent,
+            context: Arc<dyn ContextPort>,
+        ) -> Result<(), Error> {
+            let audit = AuditService::get_adapter(&context).await?;
+
+            audit.record(&event.message).await
+        }
+    }
+
+    struct EnvelopeMiddleware {
+        envelopes: Arc<std::sync::Mutex<Vec<(MessageKind, MessageEnvelope)>>>,
+    }
+
+    #[async_trait]
+    impl MiddlewarePort for EnvelopeMiddleware {
+        async fn before(&self, message: &MessageInfo) -> Result<(), Error> {
+            self.envelopes
+                .lock()
+                .unwrap()
+                .push((message.get_kind(), message.get_envelope().clone()));
+
+            Ok(())
+        }
+    }
+
+    struct EnvelopeSubscriber;
+
+    #[async_trait]
+    impl EventSubscriberPort<AuditedEvent> for EnvelopeSubscriber {
+        async fn handle(
+            &self,
+            _: &AuditedEvent,
+            context: Arc<dyn ContextPort>,
+        ) -> Result<(), Error> {
+            let envelope =
+                MessageEnvelope::from_context(&context).ok_or("Event has no envelope")?;
+
+            let audit = AuditService::get_adapter(&context).await?;
+
+            audit
+                .record(&format!(
+                    "{} {:?} {:?}",
+                    envelope.get_correlation_id(),
+                    envelope.get_causation_id().map(|id| id.to_string()),
+                    envelope.get_header("request-id")
+                ))
+                .await
+        }
+    }
+
+    struct EnvelopeProbeCommand;
+
+    #[async_trait]
+    impl CommandHandlerPort for EnvelopeProbeCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = MessageEnvelope;
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let envelope = MessageEnvelope::from_context(&context)
+                .ok_or("Command has no envelope")?
+                .clone();
+
+            let bus = CqrsProvider::get_adapter(&context).await?;
+
+            bus.publish_and_wait(AuditedEvent {
+                message: "probe".to_string(),
+            })
+            .await?;
+
+            Ok(envelope)
+        }
+    }
+
+    struct DeferredProbeCommand {
+        outbox: bool,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for DeferredProbeCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = MessageEnvelope;
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let envelope = MessageEnvelope::from_context(&context)
+                .ok_or("Command has no envelope")?
+                .clone();
+
+            let bus = CqrsProvider::get_adapter(&context).await?;
+
+            let event = AuditedEvent {
+                message: "deferred".to_string(),
+            };
+
+            match self.outbox {
+                true => bus.publish_with_outbox(event).await?,
+                false => bus.publish(event).await?,
+            }
+
+            Ok(envelope)
+        }
+    }
+
+    struct DeferredProbeSubscriber;
+
+    #[async_trait]
+    impl EventSubscriberPort<AuditedEvent> for DeferredProbeSubscriber {
+        async fn handle(
+            &self,
+            _: &AuditedEvent,
+            context: Arc<dyn ContextPort>,
+        ) -> Result<(), Error> {
+            let message = MessageContext::from_context(&context).ok_or("Event has no context")?;
+
+            let audit = AuditService::get_adapter(&context).await?;
+
+            audit
+                .record(&format!(
+                    "{} {}",
+                    message.get_envelope().get_correlation_id(),
+                    message.get_cancellation().is_cancelled()
+                ))
+                .await
+        }
+    }
+
+    struct AuditedCommand {
+        message: &'static str,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for AuditedCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let bus = CqrsProvider::get_adapter(&context).await?;
+
+            bus.publish_with_outbox(AuditedEvent {
+                message: self.message.to_string(),
+            })
+            .await?;
+
+            if self.fail {
+                return Err("Command failed after raising event".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    struct FlakyAuditedCommand {
+        attempts: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for FlakyAuditedCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let bus = CqrsProvider::get_adapter(&context).await?;
+
+            bus.publish_with_outbox(AuditedEvent {
+                message: format!("Attempt {}", attempt),
+            })
+            .await?;
+
+            if attempt == 1 {
+                return Err(TransientError::new("Lock contention").into());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct UserAggregate {
+        name: String,
+        email: String,
+    }
+
+    enum UserAggregateEvent {
+        Registered { name: String, email: String },
+        Renamed { name: String },
+    }
+
+    impl AggregateEventPort for UserAggregateEvent {
+        fn get_name(&self) -> &'static str {
+            match self {
+                Self::Registered { .. } => "UserRegistered",
+                Self::Renamed { .. } => "UserRenamed",
+            }
+        }
+
+        fn encode(&self) -> Result<String, Error> {
+            match self {
+                Self::Registered { name, email } => Ok(format!("{}\n{}", name, email)),
+                Self::Renamed { name } => Ok(name.clone()),
+            }
+        }
+
+        fn decode(name: &str, payload: &str) -> Result<Self, Error> {
+            match name {
+                "UserRegistered" => {
+                    let (name, email) = payload
+                        .split_once('\n')
+                        .ok_or("Malformed UserRegistered payload")?;
+
+                    Ok(Self::Registered {
+                        name: name.to_string(),
+                        email: email.to_string(),
+                    })
+                }
+                "UserRenamed" => Ok(Self::Renamed {
+                    name: payload.to_string(),
+                }),
+                _ => Err(format!("Unknown user event {}", name).into()),
+            }
+        }
+    }
+
+    impl AggregatePort for UserAggregate {
+        type Event = UserAggregateEvent;
+
+        fn get_name() -> &'static str {
+            "user"
+        }
+
+        fn apply(&mut self, event: &Self::Event) {
+            match event {
+                UserAggregateEvent::Registered { name, email } => {
+                    self.name = name.clone();
+                    self.email = email.clone();
+                }
+                UserAggregateEvent::Renamed { name } => self.name = name.clone(),
+            }
+        }
+    }
+
+    struct RenameUserAggregateCommand {
+        id: &'static str,
+        name: &'static str,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for RenameUserAggregateCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = u64;
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            let bus = CqrsProvider::get_adapter(&context).await?;
+
+            let mut user = bus.load_aggregate::<UserAggregate>(self.id).await?;
+
+            if user.get_version() == 0 {
+                return Err("User is not registered".into());
+            }
+
+            if user.get_state().name == self.name {
+                return Ok(user.get_version());
+            }
+
+            let events = vec![UserAggregateEvent::Renamed {
+                name: self.name.to_string(),
+            }];
+
+            bus.save_aggregate(&mut user, events).await
+        }
+    }
+
+    struct BrokenProjection;
+
+    #[async_trait]
+    impl ProjectionPort for BrokenProjection {
+        fn get_name(&self) -> &'static str {
+            "broken"
+        }
+
+        async fn apply(&self, _: &StoredEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            Err("Projection is broken".into())
+        }
+
+        async fn reset(&self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct UserDirectoryProjection {
+        names: RwLock<BTreeMap<String, String>>,
+    }
+
+    #[async_trait]
+    impl ProjectionPort for UserDirectoryProjection {
+        fn get_name(&self) -> &'static str {
+            "user_directory"
+        }
+
+        async fn apply(&self, event: &StoredEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+            let name = match UserAggregateEvent::decode(event.get_name(), event.get_payload())? {
+                UserAggregateEvent::Registered { name, .. } => name,
+                UserAggregateEvent::Renamed { name } => name,
+            };
+
+            let mut names = self.names.write().await;
+
+            names.insert(event.get_stream_id().to_string(), name);
+
+            Ok(())
+        }
+
+        async fn reset(&self) -> Result<(), Error> {
+            self.names.write().await.clear();
+
+            Ok(())
+        }
+    }
+
+    struct OnboardingStartedEvent {
+        name: String,
+    }
+
+    impl EventMessagePort for OnboardingStartedEvent {}
+
+    struct MailboxProvisionedEvent {
+        name: String,
+    }
+
+    impl EventMessagePort for MailboxProvisionedEvent {}
+
+    struct OnboardingCommand {
+        step: &'static str,
+        name: String,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for OnboardingCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            if self.step == "welcome" && self.name == "Broken" {
+                return Err("Mail server rejected welcome".into());
+            }
+
+            let audit = AuditService::get_adapter(&context).await?;
+
+            audit.record(&format!("{}:{}", self.step, self.name)).await
+        }
+    }
+
+    #[derive(Default)]
+    struct OnboardingState {
+        mailbox: bool,
+    }
+
+    struct OnboardingSaga;
+
+    #[async_trait]
+    impl SagaPort for OnboardingSaga {
+        type State = OnboardingState;
+
+        fn get_name(&self) -> &'static str {
+            "onboarding"
+        }
+
+        fn encode(state: &Self::State) -> Result<String, Error> {
+            Ok(state.mailbox.to_string())
+        }
+
+        fn decode(state: &str) -> Result<Self::State, Error> {
+            Ok(OnboardingState {
+                mailbox: state.parse()?,
+            })
+        }
+
+        async fn compensate(
+            &self,
+            correlation_id: &str,
+            state: &Self::State,
+            provider: &CqrsProvider,
+        ) -> Result<(), Error> {
+            if !state.mailbox {
+                return Ok(());
+            }
+
+            provider
+                .send_command(Box::new(OnboardingCommand {
+                    step: "mailbox-removed",
+                    name: correlation_id.to_string(),
+                }))
+                .await
+        }
+    }
+
+    #[async_trait]
+    impl SagaHandlerPort<OnboardingStartedEvent> for OnboardingSaga {
+        fn get_correlation_id(&self, event: &OnboardingStartedEvent) -> String {
+            event.name.clone()
+        }
+
+        async fn apply(
+            &self,
+            _: &OnboardingStartedEvent,
+            state: &mut Self::State,
+        ) -> Result<SagaStatus, Error> {
+            state.mailbox = true;
+
+            Ok(SagaStatus::Running)
+        }
+
+        // Waits for the next step of the same saga, which needs the saga lock.
+        async fn dispatch(
+            &self,
+            event: &OnboardingStartedEvent,
+            _: &Self::State,
+            provider: &CqrsProvider,
+        ) -> Result<(), Error> {
+            provider
+                .send_command(Box::new(OnboardingCommand {
+                    step: "mailbox",
+                    name: event.name.clone(),
+                }))
+                .await?;
+
+            provider
+                .publish_and_wait(MailboxProvisionedEvent {
+                    name: event.name.clone(),
+                })
+                .await
+        }
+    }
+
+    #[async_trait]
+    impl SagaHandlerPort<MailboxProvisionedEvent> for OnboardingSaga {
+        fn get_correlation_id(&self, event: &MailboxProvisionedEvent) -> String {
+            event.name.clone()
+        }
+
+        async fn apply(
+            &self,
+            _: &MailboxProvisionedEvent,
+            _: &mut Self::State,
+        ) -> Result<SagaStatus, Error> {
+            Ok(SagaStatus::Completed)
+        }
+
+        async fn dispatch(
+            &self,
+            event: &MailboxProvisionedEvent,
+            _: &Self::State,
+            provider: &CqrsProvider,
+        ) -> Result<(), Error> {
+            provider
+                .send_command(Box::new(OnboardingCommand {
+                    step: "welcome",
+                    name: event.name.clone(),
+                }))
+                .await
+        }
+    }
+
+    struct GatedCommand {
+        gate: Arc<Semaphore>,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for GatedCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
+
+        async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+            self.gate.acquire().await?.forget();
+
+            let audit = AuditService::get_adapter(&context).await?;
+
+            audit.record("sync").await
+        }
+    }
+
+    struct HeldCommand {
+        started: Arc<AtomicBool>,
+        gate: Arc<Semaphore>,
+    }
+
+    #[async_trait]
+    impl CommandHandlerPort for HeldCommand {
+        type Context = Arc<dyn ContextPort>;
+        type Output = ();
+
+        async fn execute(&self, _: Self::Context) -> Result<Self::Output, Error> {
+            self.started.store(true, Ordering::SeqCst);
+
+            self.gate.acquire().await?.forget();
+
+            Ok(())
+        }
+    }
+
+    struct GatedQuery {
+        gate: Arc<Semaphore>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl QueryHandlerPort for GatedQuery {
+        type Context = Arc<dyn ContextPort>;
+        type Output = String;
+
+        async fn execute(&self, _: Self::Context) -> Result<Self::Output, Error> {
+            self.gate.acquire().await?.forget();
+
+            if self.fail {
+                return Err("Gated query failed".into());
+            }
+
+            Ok("report".to_string())
+        }
+    }
+
+    fn get_users() -> Vec<User> {
+        vec![
+            User::new("Andrey", "andrey@mail.domain"),
+            User::new("Daria", "daria@mail.domain"),
+            User::new("Kirill", "kirill@mail.domain"),
+        ]
+    }
+
+    type Injection = Box<dyn FnOnce(DI) -> BoxFuture<'static, Result<DI, Error>> + Send>;
+
+    // Builds the example container, optional providers are injected before the defaults.
+    struct ExampleDiBuilder {
+        subscriptions: EventSubscriptionsProvider,
+        injections: Vec<Injection>,
+    }
+
+    impl ExampleDiBuilder {
+        fn new() -> Self {
+            Self {
+                subscriptions: EventSubscriptionsProvider::new()
+                    .with_subscriber::<UserRemovedEvent>(Arc::new(AuditUserRemovedHandler)),
+                injections: Vec::new(),
+            }
+        }
+
+        fn with_subscriptions(mut self, subscriptions: EventSubscriptionsProvider) -> Self {
+            self.subscriptions = subscriptions;
+
+            self
+        }
+
+        fn with_provider<T>(mut self, provider: T) -> Self
+        where
+            T: AdapterPort<T> + Clone + Send + Sync + 'static,
+        {
+            self.injections.push(Box::new(move |di| {
+                Box::pin(async move {
+                    di.inject(InjectAdapter {
+                        token: T::token(),
+                        factory: Arc::new(move |_| provider.clone()),
+                    })
+                    .await
+                })
+            }));
+
+            self
+        }
+
+        async fn build(self) -> Result<DI, Error> {
+            let mut di = DI::new(Arc::new(ContainerContext::new()));
+
+            for injection in self.injections {
+                di = injection(di).await?;
+            }
+
+            create_example_di(di, self.subscriptions).await
+        }
+    }
+
+    async fn create_di() -> Result<DI, Error> {
+        ExampleDiBuilder::new().build().await
+    }
+
+    async fn create_example_di(
+        di: DI,
+        subscriptions: EventSubscriptionsProvider,
+    ) -> Result<DI, Error> {
+        let di = create_cqrs_provider_di(di).await?;
+
+        let service = UserService::new(Arc::new(RwLock::new(Vec::new())));
+
+        let users = service.clone();
+
+        let di = di
+            .inject(InjectAdapter {
+                token: UserService::token(),
+                factory: Arc::new(move |_| users.clone()),
+            })
+            .await?;
+
+        // Tests that bring their own projections inject them before.
+        let di = if di
+            .get_context()
+            .has_provider(ProjectionProvider::token())
+            .await
+        {
+            di
+        } else {
+            let events = EventStoreProvider::new(Arc::new(InMemoryEventStoreAdapter::new()));
+
+            for user in get_users() {
+                events
+                    .append_event(
+                        &UserCreatedEvent::new(user.get_name(), user.get_email()),
+                        None,
+                    )
+                    .await?;
+            }
+
+            let projections =
+                ProjectionProvider::new(Arc::new(InMemoryCheckpointStoreAdapter::new()))
+                    .with_projection(Arc::new(UsersProjection::new(service)))
+                    .with_poison_policy(PoisonEventPolicy::Skip { attempts: 3 });
+
+            projections
+                .catch_up(events.get_store().as_ref(), di.get_context())
+                .await?;
+
+            let di = di
+                .inject(InjectAdapter {
+                    token: EventStoreProvider::token(),
+                    factory: Arc::new(move |_| events.clone()),
+                })
+                .await?;
+
+            di.inject(InjectAdapter {
+                token: ProjectionProvider::token(),
+                factory: Arc::new(move |_| projections.clone()),
+            })
+            .await?
+        };
+
+        let subscriptions = subscriptions
+            .with_projection::<UserCreatedEvent>()
+            .with_projection::<UserEmailChangedEvent>()
+            .with_projection::<UserNameChangedEvent>()
+            .with_projection::<UserRemovedEvent>();
+
+        let records = Arc::new(RwLock::new(Vec::new()));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: AuditService::token(),
+                factory: Arc::new(move |_| AuditService::new(records.clone())),
+            })
+            .await?;
+
+        let di = di
+            .inject(InjectAdapter {
+                token: EventSubscriptionsProvider::token(),
+                factory: Arc::new(move |_| subscriptions.clone()),
+            })
+            .await?;
+
+        let registry = HandlerRegistryProvider::new()
+            .with_command::<RemoveUserCommand>(Arc::new(RemoveUserHandler))
+            .with_query::<CountUsersQuery>(Arc::new(CountUsersHandler))
+            .with_query::<ListUsersQuery>(Arc::new(ListUsersHandler));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: HandlerRegistryProvider::token(),
+                factory: Arc::new(move |_| registry.clone()),
+            })
+            .await?;
+
+        let loaders = BatchLoaderProvider::new().with_loader(Arc::new(UsersByNameLoader::new()));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: BatchLoaderProvider::token(),
+                factory: Arc::new(move |_| loaders.clone()),
+            })
+            .await?;
+
+        let di = di
+            .inject(InjectAdapter {
+                token: UserController::token(),
+                factory: Arc::new(UserController::new),
+            })
+            .await?;
+
+        Ok(di)
+    }
+
+    #[tokio::test]
+    async fn should_get_user_by_name() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let user_name = "Andrey";
+
+        let user = controller
+            .get_user_by_name(user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let user = user.unwrap();
+
+        assert_eq!(user.get_name(), user_name);
+    }
+
+    #[tokio::test]
+    async fn should_create_new_user() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let user_name = "Rita";
+
+        let user_email = "rita@mail.domain";
+
+        controller
+            .create_user(user_name, user_email)
+            .await
+            .expect("Cant create user");
+
+        let user = controller
+            .get_user_by_name(user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let user = user.unwrap();
+
+        assert_eq!(user.get_name(), user_name);
+        assert_eq!(user.get_email(), user_email);
+    }
+
+    #[tokio::test]
+    async fn should_be_created_safe_user() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let user_name = "Rita";
+        let user_email = "rita@mail.domain";
+
+        controller
+            .create_safe_user(user_name, user_email)
+            .await
+            .expect("Cant create user");
+
+        let user = controller
+            .get_user_by_name(user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let user = user.unwrap();
+
+        assert_eq!(user.get_name(), user_name);
+        assert_eq!(user.get_email(), user_email);
+    }
+
+    #[tokio::test]
+    async fn should_be_not_created_safe_user() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let user_name = "Andrey";
+        let user_email = "andrey@mail.domain";
+
+        let user_creation = controller.create_safe_user(user_name, user_email).await;
+
+        assert!(user_creation.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_update_user_email() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let user_name = "Andrey";
+
+        let user_email = "andreyddk@mail.domain";
+
+        controller
+            .update_user_email(user_name, user_email)
+            .await
+            .expect("Cant update user");
+
+        let user = controller
+            .get_user_by_name(user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let user = user.unwrap();
+
+        assert_eq!(user.get_name(), user_name);
+        assert_eq!(user.get_email(), user_email);
+    }
+
+    #[tokio::test]
+    async fn should_update_user_name_by_event() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let current_user_name = "Daria";
+        let new_user_name = "Rita";
+
+        controller
+            .update_user_name(current_user_name, new_user_name)
+            .await
+            .expect("Cant update user");
+
+        // Waits for the event in flight instead of a fixed delay.
+        CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER")
+            .drain()
+            .await
+            .expect("Cant drain");
+
+        let user = controller
+            .get_user_by_name(new_user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let user = user.unwrap();
+
+        assert_eq!(user.get_name(), new_user_name);
+    }
+
+    #[tokio::test]
+    async fn should_report_failed_event() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = create_di().await.expect("Cant create DI");
+
+        let di = di
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        controller
+            .update_user_name("Unknown", "Rita")
+            .await
+            .expect("Cant send event");
+
+        let failure = timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("Event failure was not reported")
+            .expect("Failure channel closed");
+
+        assert_eq!(failure.get_error().to_string(), "Cant find user by name.");
+    }
+
+    #[tokio::test]
+    async fn should_report_failed_awaited_event() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = create_di().await.expect("Cant create DI");
+
+        let di = di
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let error = controller
+            .update_user_name_and_wait("Unknown", "Rita")
+            .await
+            .expect_err("Event should fail");
+
+        let failure = receiver.try_recv().expect("Event failure was not reported");
+
+        assert_eq!(error.to_string(), "Cant find user by name.");
+        assert_eq!(failure.get_error().to_string(), "Cant find user by name.");
+    }
+
+    #[tokio::test]
+    async fn should_update_user_name_by_awaited_event() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let current_user_name = "Kirill";
+        let new_user_name = "Rita";
+
+        controller
+            .update_user_name_and_wait(current_user_name, new_user_name)
+            .await
+            .expect("Cant update user");
+
+        let user = controller
+            .get_user_by_name(new_user_name)
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let result = controller
+            .update_user_name_and_wait(current_user_name, new_user_name)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_drain_in_flight_events() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        controller
+            .update_user_name("Daria", "Rita")
+            .await
+            .expect("Cant update user");
+
+        bus.drain().await.expect("Cant drain events");
+
+        let user = controller
+            .get_user_by_name("Rita")
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+
+        let result = controller.update_user_name("Rita", "Daria").await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_abort_in_flight_events_on_shutdown() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let handle = bus
+            .event_with_handle(Box::new(SlowEvent {
+                delay: Duration::from_secs(10),
+            }))
+            .await
+            .expect("Cant send event");
+
+        let report = bus
+            .shutdown(Duration::from_millis(50))
+            .await
+            .expect("Cant shutdown");
+
+        assert_eq!(report.get_aborted(), &[handle.get_id()]);
+
+        let events = bus.get_event_bus().await.expect("Cant get event bus");
+
+        assert_eq!(events.get_in_flight(), 0);
+        assert!(handle.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_run_middleware_around_nested_messages() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: None,
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        controller
+            .create_safe_user("Rita", "rita@mail.domain")
+            .await
+            .expect("Cant create user");
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                "before Command",
+                "before Query",
+                "after Query true",
+                "after Command true",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_wrap_typed_messages_in_around_middleware() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let pipeline = MiddlewareProvider::default().with(Arc::new(AroundMiddleware {
+            records: records.clone(),
+        }));
+
+        let di = create_di()
+            .await
+            .expect("Cant create DI")
+            .inject(InjectAdapter {
+                token: MiddlewareProvider::token(),
+                factory: Arc::new(move |_| pipeline.clone()),
+            })
+            .await
+            .expect("Cant inject MIDDLEWARE_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.dispatch_command(RemoveUserCommand::new("Andrey"))
+            .await
+            .expect("Cant remove user");
+
+        let count = bus
+            .dispatch_query(CountUsersQuery)
+            .await
+            .expect("Cant count users");
+
+        assert_eq!(count, 2);
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                format!("enter {}", type_name::<RemoveUserCommand>()),
+                format!("exit {} true", type_name::<RemoveUserCommand>()),
+                format!("enter {}", type_name::<CountUsersQuery>()),
+                format!("exit {} true", type_name::<CountUsersQuery>()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_ignore_provider_injected_after_first_dispatch() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.dispatch_query(CountUsersQuery)
+            .await
+            .expect("Cant count users");
+
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let pipeline = MiddlewareProvider::default().with(Arc::new(AroundMiddleware {
+            records: records.clone(),
+        }));
+
+        di.inject(InjectAdapter {
+            token: MiddlewareProvider::token(),
+            factory: Arc::new(move |_| pipeline.clone()),
+        })
+        .await
+        .expect("Cant inject MIDDLEWARE_PROVIDER");
+
+        bus.dispatch_query(CountUsersQuery)
+            .await
+            .expect("Cant count users");
+
+        assert!(records.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_command_in_middleware() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: Some(MessageKind::Command),
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let result = controller.create_user("Rita", "rita@mail.domain").await;
+
+        assert!(result.is_err());
+
+        let user = controller
+            .get_user_by_name("Rita")
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_remove_user_by_registered_handler() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        controller
+            .remove_user("Andrey")
+            .await
+            .expect("Cant remove user");
+
+        let count = controller.count_users().await.expect("Cant count users");
+
+        assert_eq!(count, 2);
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        struct UnknownQuery;
+
+        impl QueryMessagePort for UnknownQuery {
+            type Output = ();
+        }
+
+        assert!(bus.dispatch_query(UnknownQuery).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_fan_out_event_to_isolated_subscribers() {
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_subscriber::<UserRemovedEvent>(Arc::new(FailingSubscriber))
+            .with_subscriber::<UserRemovedEvent>(Arc::new(AuditUserRemovedHandler));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let result = controller.remove_user("Daria").await;
+
+        assert!(result.is_err());
+
+        let audit = AuditService::get_adapter(&context)
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        let records = audit.get_records().await.expect("Cant get records");
+
+        assert_eq!(records, vec!["User removed: Daria"]);
+    }
+
+    async fn inject_retry_provider(di: DI, retry: RetryProvider) -> Result<DI, Error> {
+        di.inject(InjectAdapter {
+            token: RetryProvider::token(),
+            factory: Arc::new(move |_| retry.clone()),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn should_retry_failed_command() {
+        let retry = RetryProvider::new(RetryPolicy::none()).with_policy::<FlakyCommand>(
+            RetryPolicy::new(3)
+                .with_exponential_backoff(Duration::from_millis(1), Duration::from_millis(10))
+                .with_jitter(0.5),
+        );
+
+        let di = create_di().await.expect("Cant create DI");
+
+        let di = inject_retry_provider(di, retry)
+            .await
+            .expect("Cant inject RETRY_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let attempt = bus
+            .send_command(Box::new(FlakyCommand {
+                attempts: attempts.clone(),
+                failures: 2,
+                transient: true,
+            }))
+            .await
+            .expect("Cant execute command");
+
+        assert_eq!(attempt, 3);
+
+        let result = bus
+            .send_command(Box::new(FlakyCommand {
+                attempts: Arc::new(AtomicU32::new(0)),
+                failures: 3,
+                transient: true,
+            }))
+            .await;
+
+        assert!(result.is_err());
+
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let result = bus
+            .send_command(Box::new(FlakyCommand {
+                attempts: attempts.clone(),
+                failures: 1,
+                transient: false,
+            }))
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_retry_event_by_message_type_policy() {
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<FlakyEvent>(
+            Arc::new(FlakySubscriber {
+                attempts: attempts.clone(),
+                failures: 2,
+            }),
+        );
+
+        let retry = RetryProvider::new(RetryPolicy::none()).with_policy::<FlakyEvent>(
+            RetryPolicy::new(5)
+                .with_fixed_backoff(Duration::from_millis(1))
+                .with_retryable(|error| error.to_string().starts_with("Temporary")),
+        );
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let di = inject_retry_provider(di, retry)
+            .await
+            .expect("Cant inject RETRY_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.publish_and_wait(FlakyEvent)
+            .await
+            .expect("Cant publish event");
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn should_dead_letter_and_replay_failed_event() {
+        let failing = Arc::new(AtomicBool::new(true));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<AuditedEvent>(
+            Arc::new(ToggleSubscriber {
+                failing: failing.clone(),
+            }),
+        );
+
+        let dead_letters =
+            DeadLetterProvider::new(Arc::new(InMemoryDeadLetterStoreAdapter::new(10)))
+                .with_message::<AuditedEvent>();
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .with_provider(dead_letters)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.publish(AuditedEvent {
+            message: "Rita joined".to_string(),
+        })
+        .await
+        .expect("Cant publish event");
+
+        let letters = timeout(Duration::from_secs(1), async {
+            loop {
+                let letters = bus.get_dead_letters().await.expect("Cant list letters");
+
+                if !letters.is_empty() {
+                    return letters;
+                }
+
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Event was not dead lettered");
+
+        assert_eq!(letters.len(), 1);
+
+        let id = letters[0].get_id();
+
+        assert_eq!(letters[0].get_name(), "AuditedEvent");
+        assert_eq!(letters[0].get_payload(), "Rita joined");
+        assert_eq!(letters[0].get_subscriber(), type_name::<ToggleSubscriber>());
+        assert_eq!(letters[0].get_attempts(), 1);
+        assert_eq!(letters[0].get_error(), "Audit is unavailable");
+
+        assert!(bus.replay_dead_letter(id).await.is_err());
+
+        let letter = bus
+            .get_dead_letter(id)
+            .await
+            .expect("Cant get letter")
+            .expect("Dead letter was not kept");
+
+        assert_eq!(letter.get_attempts(), 2);
+
+        failing.store(false, Ordering::SeqCst);
+
+        bus.replay_dead_letter(id).await.expect("Cant replay event");
+
+        let letter = bus.get_dead_letter(id).await.expect("Cant get letter");
+
+        assert!(letter.is_none());
+
+        assert_eq!(bus.purge_dead_letters().await.expect("Cant purge"), 0);
+    }
+
+    #[tokio::test]
+    async fn should_retry_and_dead_letter_boxed_and_awaited_events() {
+        let failing = Arc::new(AtomicBool::new(true));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<AuditedEvent>(
+            Arc::new(ToggleSubscriber {
+                failing: failing.clone(),
+            }),
+        );
+
+        let dead_letters =
+            DeadLetterProvider::new(Arc::new(InMemoryDeadLetterStoreAdapter::new(10)))
+                .with_message::<AuditedEvent>();
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .with_provider(dead_letters)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let retry = RetryProvider::new(RetryPolicy::new(2).with_retryable(|_| true));
+
+        let di = inject_retry_provider(di, retry)
+            .await
+            .expect("Cant inject RETRY_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let event = || {
+            Box::new(ToggleEvent {
+                failing: failing.clone(),
+                attempts: attempts.clone(),
+            })
+        };
+
+        bus.event_and_wait(event())
+            .await
+            .expect_err("Awaited event should fail");
+
+        bus.send_event(event()).await.expect("Cant send event");
+
+        let event_bus = bus.get_event_bus().await.expect("Cant get event bus");
+
+        EventBusPort::send(event_bus, event(), di.get_context());
+
+        bus.publish_and_wait(AuditedEvent {
+            message: "Awaited".to_string(),
+        })
+        .await
+        .expect_err("Awaited publish should fail");
+
+        let letters = timeout(Duration::from_secs(1), async {
+            loop {
+                let letters = bus.get_dead_letters().await.expect("Cant list letters");
+
+                if letters.len() == 4 {
+                    return letters;
+                }
+
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Events were not dead lettered");
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 6);
+
+        assert!(letters.iter().all(|letter| letter.get_attempts() == 2));
+
+        let boxed = letters
+            .iter()
+            .filter(|letter| letter.get_event().is_some())
+            .count();
+
+        assert_eq!(boxed, 3);
+
+        let awaited = letters
+            .iter()
+            .find(|letter| letter.get_name() == "AuditedEvent")
+            .expect("Awaited publish was not dead lettered");
+
+        assert_eq!(awaited.get_payload(), "Awaited");
+
+        failing.store(false, Ordering::SeqCst);
+
+        for letter in &letters {
+            bus.replay_dead_letter(letter.get_id())
+                .await
+                .expect("Cant replay letter");
+        }
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 9);
+        assert!(
+            bus.get_dead_letters()
+                .await
+                .expect("Cant list letters")
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_boxed_event_failure_without_dead_letter() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve USER_CONTROLLER");
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        controller
+            .update_user_name("Unknown", "Rita")
+            .await
+            .expect("Cant send event");
+
+        assert!(bus.get_dead_letters().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_cancel_query_on_call_timeout() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cancelled = Arc::new(AtomicU32::new(0));
+
+        let error = bus
+            .query_with_timeout(
+                Box::new(SlowQuery {
+                    cancelled: cancelled.clone(),
+                }),
+                Duration::from_millis(50),
+            )
+            .await
+            .expect_err("Query should time out");
+
+        let error = error
+            .downcast_ref::<TimeoutError>()
+            .expect("Error should be a timeout");
+
+        assert_eq!(error.get_kind(), MessageKind::Query);
+        assert_eq!(error.get_timeout(), Duration::from_millis(50));
+
+        timeout(Duration::from_secs(1), async {
+            while cancelled.load(Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Handler was not cancelled");
+
+        let user = bus
+            .query_with_timeout(
+                Box::new(GetUserByNameQuery::new("Andrey")),
+                Duration::from_secs(1),
+            )
+            .await
+            .expect("Cant get user");
+
+        assert!(user.is_some());
+    }
+
+    #[tokio::test]
+    async fn should_run_middleware_after_on_timeout() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: None,
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cancelled = Arc::new(AtomicU32::new(0));
+
+        let result = bus
+            .query_with_timeout(
+                Box::new(SlowQuery {
+                    cancelled: cancelled.clone(),
+                }),
+                Duration::from_millis(50),
+            )
+            .await;
+
+        assert!(result.is_err_and(|error| error.is::<TimeoutError>()));
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec!["before Query", "after Query false"]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_cancel_query_with_caller_token() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cancellation = CancellationToken::new();
+
+        let cancelled = Arc::new(AtomicU32::new(0));
+
+        let query = SlowQuery {
+            cancelled: cancelled.clone(),
+        };
+
+        let scoped = bus.with_cancellation(cancellation.clone());
+
+        let handle = tokio::spawn(async move { scoped.send_query(Box::new(query)).await });
+
+        cancellation.cancel();
+
+        let result = timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("Query was not cancelled")
+            .expect("Cant join query");
+
+        assert_eq!(
+            result.expect_err("Query should fail").to_string(),
+            "Query was cancelled"
+        );
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_apply_default_timeout_to_queries() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let timeouts = TimeoutProvider::default().with_query_timeout(Duration::from_millis(50));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: TimeoutProvider::token(),
+                factory: Arc::new(move |_| timeouts),
+            })
+            .await
+            .expect("Cant inject TIMEOUT_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let result = bus
+            .send_query(Box::new(SlowQuery {
+                cancelled: Arc::new(AtomicU32::new(0)),
+            }))
+            .await;
+
+        assert!(result.is_err_and(|error| error.is::<TimeoutError>()));
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        controller
+            .create_user("Rita", "rita@mail.domain")
+            .await
+            .expect("Command should not be limited by query timeout");
+    }
+
+    fn slow_event(delay: u64) -> Box<SlowEvent> {
+        Box::new(SlowEvent {
+            delay: Duration::from_millis(delay),
+        })
+    }
+
+    #[tokio::test]
+    async fn should_reject_events_over_capacity() {
+        let limits = EventBusLimitsProvider::new(1, 1).with_overflow(OverflowPolicy::Reject);
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(limits)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let first = bus
+            .event_with_handle(slow_event(200))
+            .await
+            .expect("Cant send event");
+
+        let second = bus
+            .event_with_handle(slow_event(10))
+            .await
+            .expect("Cant queue event");
+
+        let events = bus.get_event_bus().await.expect("Cant get event bus");
+
+        timeout(Duration::from_secs(1), async {
+            while events.get_active_workers() == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Worker was not started");
+
+        assert_eq!(events.get_active_workers(), 1);
+        assert_eq!(events.get_queue_depth(), 1);
+
+        assert!(bus.event_with_handle(slow_event(10)).await.is_err());
+
+        first.await.expect("First event failed");
+        second.await.expect("Second event failed");
+
+        assert_eq!(events.get_in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_block_publisher_until_worker_is_free() {
+        let di = ExampleDiBuilder::new()
+            .with_provider(EventBusLimitsProvider::new(1, 0))
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let first = bus
+            .event_with_handle(slow_event(100))
+            .await
+            .expect("Cant send event");
+
+        assert!(
+            timeout(
+                Duration::from_millis(20),
+                bus.event_with_handle(slow_event(10))
+            )
+            .await
+            .is_err()
+        );
+
+        let second = bus
+            .event_with_handle(slow_event(10))
+            .await
+            .expect("Cant send event");
+
+        first.await.expect("First event failed");
+        second.await.expect("Second event failed");
+    }
+
+    #[tokio::test]
+    async fn should_drop_oldest_queued_event() {
+        let limits = EventBusLimitsProvider::new(1, 1).with_overflow(OverflowPolicy::DropOldest);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(limits)
+            .build()
+            .await
+            .expect("Cant create DI")
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let first = bus
+            .event_with_handle(slow_event(100))
+            .await
+            .expect("Cant send event");
+
+        let events = bus.get_event_bus().await.expect("Cant get event bus");
+
+        timeout(Duration::from_secs(1), async {
+            while events.get_active_workers() == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Worker was not started");
+
+        let dropped = bus
+            .event_with_handle(slow_event(10))
+            .await
+            .expect("Cant queue event");
+
+        let latest = bus
+            .event_with_handle(slow_event(10))
+            .await
+            .expect("Cant queue event");
+
+        let dropped_id = dropped.get_id();
+
+        assert!(dropped.await.is_err());
+
+        first.await.expect("First event failed");
+        latest.await.expect("Latest event failed");
+
+        let failure = receiver.try_recv().expect("Dropped event was not reported");
+
+        assert_eq!(failure.get_id(), dropped_id);
+        assert_eq!(events.get_dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_keep_partition_order_after_dropping_event() {
+        let limits = EventBusLimitsProvider::new(2, 0).with_overflow(OverflowPolicy::DropOldest);
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(limits)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let step = |name, delay| {
+            Box::new(StepEvent {
+                name,
+                delay: Duration::from_millis(delay),
+                records: records.clone(),
+            })
+        };
+
+        let first = bus
+            .event_with_handle_and_partition(step("first", 100), "user")
+            .await
+            .expect("Cant send event");
+
+        let events = bus.get_event_bus().await.expect("Cant get event bus");
+
+        timeout(Duration::from_secs(1), async {
+            while events.get_active_workers() == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Worker was not started");
+
+        let dropped = bus
+            .event_with_handle_and_partition(step("dropped", 10), "user")
+            .await
+            .expect("Cant queue event");
+
+        let last = bus
+            .event_with_handle_and_partition(step("last", 10), "user")
+            .await
+            .expect("Cant queue event");
+
+        assert!(dropped.await.is_err());
+
+        first.await.expect("First event failed");
+        last.await.expect("Last event failed");
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec!["start first", "end first", "start last", "end last"]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_admit_nested_events_of_blocked_worker() {
+        let di = ExampleDiBuilder::new()
+            .with_provider(EventBusLimitsProvider::new(1, 0))
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let delivered = Arc::new(AtomicU32::new(0));
+
+        let handle = bus
+            .event_with_handle(Box::new(RelayEvent {
+                delivered: delivered.clone(),
+                nested: false,
+            }))
+            .await
+            .expect("Cant send event");
+
+        timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("Nested publish deadlocked")
+            .expect("Event failed");
+
+        let events = bus.get_event_bus().await.expect("Cant get event bus");
+
+        timeout(Duration::from_secs(1), events.drain())
+            .await
+            .expect("Nested event was not delivered");
+
+        assert_eq!(delivered.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_deliver_events_in_order_per_partition() {
+        let records = Arc::new(RwLock::new(Vec::new()));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<OrderedEvent>(
+            Arc::new(OrderedSubscriber {
+                records: records.clone(),
+            }),
+        );
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        for index in 0..5 {
+            bus.publish(OrderedEvent { key: "a", index })
+                .await
+                .expect("Cant publish event");
+        }
+
+        bus.publish(OrderedEvent { key: "b", index: 4 })
+            .await
+            .expect("Cant publish event");
+
+        bus.drain().await.expect("Cant drain");
+
+        let records = records.read().await.clone();
+
+        let ordered = records
+            .iter()
+            .filter(|record| record.starts_with('a'))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        assert_eq!(ordered, vec!["a0", "a1", "a2", "a3", "a4"]);
+        assert_eq!(records[0], "b4");
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_outbox_events_only_after_command_succeeds() {
+        let store = Arc::new(InMemoryOutboxStoreAdapter::new());
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<AuditedEvent>();
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(
+                EventSubscriptionsProvider::new()
+                    .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+            )
+            .with_provider(outbox)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let result = bus
+            .send_command(Box::new(AuditedCommand {
+                message: "Discarded",
+                fail: true,
+            }))
+            .await;
+
+        assert!(result.is_err());
+
+        bus.send_command(Box::new(AuditedCommand {
+            message: "Committed",
+            fail: false,
+        }))
+        .await
+        .expect("Cant execute command");
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        let records = timeout(Duration::from_secs(1), async {
+            loop {
+                let records = audit.get_records().await.expect("Cant get records");
+
+                if !records.is_empty() {
+                    return records;
+                }
+
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Event was not dispatched");
+
+        bus.drain().await.expect("Cant drain");
+
+        assert_eq!(records, vec!["Committed"]);
+        assert!(store.list().await.expect("Cant list outbox").is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_keep_envelope_but_not_cancellation_of_command_for_deferred_events() {
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_subscriber::<AuditedEvent>(Arc::new(DeferredProbeSubscriber));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let outbox = OutboxProvider::new(Arc::new(InMemoryOutboxStoreAdapter::new()))
+            .with_message::<AuditedEvent>();
+
+        let di = di
+            .inject(InjectAdapter {
+                token: OutboxProvider::token(),
+                factory: Arc::new(move |_| outbox.clone()),
+            })
+            .await
+            .expect("Cant inject OUTBOX_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        let mut expected = Vec::new();
+
+        for outbox in [false, true] {
+            let cancellation = CancellationToken::new();
+
+            let command = bus
+                .with_cancellation(cancellation.clone())
+                .send_command(Box::new(DeferredProbeCommand { outbox }))
+                .await
+                .expect("Cant run command");
+
+            // The caller is done with its request, events raised by the command still run.
+            cancellation.cancel();
+
+            expected.push(format!("{} false", command.get_correlation_id()));
+
+            let records = timeout(Duration::from_secs(1), async {
+                loop {
+                    let records = audit.get_records().await.expect("Cant get records");
+
+                    if records.len() == expected.len() {
+                        return records;
+                    }
+
+                    sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("Event was not dispatched");
+
+            assert_eq!(records, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_store_outbox_record_before_command_returns() {
+        let failing = Arc::new(AtomicBool::new(true));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<AuditedEvent>(
+            Arc::new(ToggleSubscriber {
+                failing: failing.clone(),
+            }),
+        );
+
+        let store = Arc::new(InMemoryOutboxStoreAdapter::new());
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<AuditedEvent>();
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI")
+            .inject(InjectAdapter {
+                token: OutboxProvider::token(),
+                factory: Arc::new(move |_| outbox.clone()),
+            })
+            .await
+            .expect("Cant inject OUTBOX_PROVIDER")
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.send_command(Box::new(AuditedCommand {
+            message: "Committed",
+            fail: false,
+        }))
+        .await
+        .expect("Cant execute command");
+
+        assert_eq!(store.list().await.expect("Cant list outbox").len(), 1);
+
+        timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("Delivery failure was not reported")
+            .expect("Failure channel closed");
+
+        assert_eq!(store.list().await.expect("Cant list outbox").len(), 1);
+
+        failing.store(false, Ordering::SeqCst);
+
+        assert_eq!(bus.relay_outbox().await.expect("Cant relay outbox"), 1);
+        assert!(store.list().await.expect("Cant list outbox").is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_store_outbox_record_before_service_bus_command_returns() {
+        let failing = Arc::new(AtomicBool::new(true));
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<AuditedEvent>(
+            Arc::new(ToggleSubscriber {
+                failing: failing.clone(),
+            }),
+        );
+
+        let store = Arc::new(InMemoryOutboxStoreAdapter::new());
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<AuditedEvent>();
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI")
+            .inject(InjectAdapter {
+                token: OutboxProvider::token(),
+                factory: Arc::new(move |_| outbox.clone()),
+            })
+            .await
+            .expect("Cant inject OUTBOX_PROVIDER")
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let result = ServiceBusPort::command(
+            &*bus,
+            Box::new(AuditedCommand {
+                message: "Discarded",
+                fail: true,
+            }),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(store.list().await.expect("Cant list outbox").is_empty());
+
+        ServiceBusPort::command(
+            &*bus,
+            Box::new(AuditedCommand {
+                message: "Committed",
+                fail: false,
+            }),
+        )
+        .await
+        .expect("Cant execute command");
+
+        let records = store.list().await.expect("Cant list outbox");
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get_payload(), "Committed");
+
+        timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("Delivery failure was not reported")
+            .expect("Failure channel closed");
+
+        failing.store(false, Ordering::SeqCst);
+
+        assert_eq!(bus.relay_outbox().await.expect("Cant relay outbox"), 1);
+        assert!(store.list().await.expect("Cant list outbox").is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_discard_outbox_events_of_failed_attempts() {
+        let store = Arc::new(InMemoryOutboxStoreAdapter::new());
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<AuditedEvent>();
+
+        let retry = RetryProvider::new(RetryPolicy::none())
+            .with_policy::<FlakyAuditedCommand>(RetryPolicy::new(2));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(
+                EventSubscriptionsProvider::new()
+                    .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+            )
+            .with_provider(outbox)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let di = inject_retry_provider(di, retry)
+            .await
+            .expect("Cant inject RETRY_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.send_command(Box::new(FlakyAuditedCommand {
+            attempts: Arc::new(AtomicU32::new(0)),
+        }))
+        .await
+        .expect("Cant execute command");
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        timeout(Duration::from_secs(1), async {
+            while !store.list().await.expect("Cant list outbox").is_empty() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Outbox record was not delivered");
+
+        assert_eq!(
+            audit.get_records().await.expect("Cant get records"),
+            vec!["Attempt 2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_relay_outbox_records_left_by_crash() {
+        let path = std::env::temp_dir().join(format!("outbox-{}.log", std::process::id()));
+
+        let _ = std::fs::remove_file(&path);
+
+        let payload = "User\tRita\nrenamed";
+
+        let envelope = MessageEnvelope::new()
+            .with_principal(Principal::new("admin").with_tenant("acme"))
+            .with_header("request-id", "r-3");
+
+        let crashed = FileOutboxStoreAdapter::new(&path).expect("Cant open outbox");
+
+        crashed
+            .append(
+                AuditedEvent::get_name(),
+                payload.to_string(),
+                Some(envelope.clone()),
+            )
+            .await
+            .expect("Cant append record");
+
+        drop(crashed);
+
+        // A crash in the middle of the next append leaves a torn line.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("Cant open outbox");
+
+        std::io::Write::write_all(&mut file, b"2\t17\tAuditedEvent\tTorn")
+            .expect("Cant tear outbox");
+
+        drop(file);
+
+        let store = Arc::new(FileOutboxStoreAdapter::new(&path).expect("Cant reopen outbox"));
+
+        let records = store.list().await.expect("Cant list outbox");
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get_envelope(), Some(&envelope));
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<AuditedEvent>();
+
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber))
+            .with_subscriber::<AuditedEvent>(Arc::new(EnvelopeSubscriber));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let di = di
+            .inject(InjectAdapter {
+                token: OutboxProvider::token(),
+                factory: Arc::new(move |_| outbox.clone()),
+            })
+            .await
+            .expect("Cant inject outbox");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        assert_eq!(bus.relay_outbox().await.expect("Cant relay outbox"), 1);
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        let mut records = audit.get_records().await.expect("Cant get records");
+
+        records.sort();
+
+        // The relayed event keeps the stored envelope.
+        let mut expected = vec![
+            payload.to_string(),
+            format!("{} None Some(\"r-3\")", envelope.get_correlation_id()),
+        ];
+
+        expected.sort();
+
+        assert_eq!(records, expected);
+
+        assert!(store.list().await.expect("Cant list outbox").is_empty());
+
+        let reopened = FileOutboxStoreAdapter::new(&path).expect("Cant reopen outbox");
+
+        assert!(reopened.list().await.expect("Cant list outbox").is_empty());
+
+        std::fs::remove_file(&path).expect("Cant remove outbox");
+    }
+
+    #[tokio::test]
+    async fn should_rebuild_aggregate_and_reject_stale_version() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let mut user = bus
+            .load_aggregate::<UserAggregate>("42")
+            .await
+            .expect("Cant load aggregate");
+
+        let registered = vec![UserAggregateEvent::Registered {
+            name: "Rita".to_string(),
+            email: "rita@mail.domain".to_string(),
+        }];
+
+        bus.save_aggregate(&mut user, registered)
+            .await
+            .expect("Cant save aggregate");
+
+        let version = bus
+            .send_command(Box::new(RenameUserAggregateCommand {
+                id: "42",
+                name: "Margarita",
+            }))
+            .await
+            .expect("Cant rename user");
+
+        assert_eq!(version, 2);
+
+        let user = bus
+            .load_aggregate::<UserAggregate>("42")
+            .await
+            .expect("Cant load aggregate");
+
+        assert_eq!(user.get_version(), 2);
+        assert_eq!(user.get_state().name, "Margarita");
+        assert_eq!(user.get_state().email, "rita@mail.domain");
+
+        let mut stale = bus
+            .load_aggregate::<UserAggregate>("42")
+            .await
+            .expect("Cant load aggregate");
+
+        bus.send_command(Box::new(RenameUserAggregateCommand {
+            id: "42",
+            name: "Rita",
+        }))
+        .await
+        .expect("Cant rename user");
+
+        let renamed = vec![UserAggregateEvent::Renamed {
+            name: "Marga".to_string(),
+        }];
+
+        let error = bus
+            .save_aggregate(&mut stale, renamed)
+            .await
+            .expect_err("Stale aggregate should conflict");
+
+        let error = error
+            .downcast_ref::<ConcurrencyError>()
+            .expect("Error should be a concurrency conflict");
+
+        assert_eq!(error.get_stream_id(), "user-42");
+        assert_eq!(error.get_expected(), ExpectedVersion::Exact(2));
+        assert_eq!(error.get_actual(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_read_file_event_store_after_reopen() {
+        let path = std::env::temp_dir().join(format!("events-{}.log", std::process::id()));
+
+        let _ = std::fs::remove_file(&path);
+
+        let store = FileEventStoreAdapter::new(&path).expect("Cant open event store");
+
+        store
+            .append(
+                "user-1",
+                ExpectedVersion::NoStream,
+                vec![
+                    EventData::new("UserRegistered", "Andrey\nandrey@mail.domain".to_string()),
+                    EventData::new("UserRenamed", "Andrei".to_string()),
+                ],
+            )
+            .await
+            .expect("Cant append events");
+
+        store
+            .append(
+                "user-2",
+                ExpectedVersion::Any,
+                vec![EventData::new(
+                    "UserRegistered",
+                    "Daria\ndaria@mail.domain".to_string(),
+                )],
+            )
+            .await
+            .expect("Cant append events");
+
+        drop(store);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("Cant open event log");
+
+        std::io::Write::write_all(&mut file, b"4\tuser-3\t1\t17").expect("Cant tear event log");
+
+        drop(file);
+
+        let store = FileEventStoreAdapter::new(&path).expect("Cant reopen event store");
+
+        let stream = store
+            .read_stream("user-1", 1)
+            .await
+            .expect("Cant read stream");
+
+        assert_eq!(stream.len(), 1);
+        assert_eq!(stream[0].get_version(), 2);
+        assert_eq!(stream[0].get_payload(), "Andrei");
+
+        let all = store.read_all(1, 10).await.expect("Cant read all");
+
+        let positions = all
+            .iter()
+            .map(|event| (event.get_position(), event.get_stream_id()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(positions, vec![(2, "user-1"), (3, "user-2")]);
+        assert_eq!(all[1].get_payload(), "Daria\ndaria@mail.domain");
+
+        let conflict = store
+            .append(
+                "user-2",
+                ExpectedVersion::NoStream,
+                vec![EventData::new("UserRenamed", "Dasha".to_string())],
+            )
+            .await;
+
+        assert!(conflict.is_err_and(|error| error.is::<ConcurrencyError>()));
+
+        store
+            .append(
+                "user-3",
+                ExpectedVersion::NoStream,
+                vec![EventData::new(
+                    "UserRegistered",
+                    "Rita\nrita@mail.domain".to_string(),
+                )],
+            )
+            .await
+            .expect("Cant append after torn line");
+
+        drop(store);
+
+        let store = FileEventStoreAdapter::new(&path).expect("Cant reopen event store");
+
+        let all = store.read_all(3, 10).await.expect("Cant read all");
+
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].get_position(), 4);
+        assert_eq!(all[0].get_stream_id(), "user-3");
+
+        std::fs::remove_file(&path).expect("Cant remove event store");
+    }
+
+    #[tokio::test]
+    async fn should_cache_queries_until_command_invalidates() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        for _ in 0..2 {
+            let user = controller
+                .get_cached_user_by_name("Kirill")
+                .await
+                .expect("Cant get user")
+                .expect("User not found");
+
+            assert_eq!(user.get_email(), "kirill@mail.domain");
+        }
+
+        let cache = bus.get_query_cache().await.expect("Cant get cache");
+
+        assert_eq!((cache.get_hits(), cache.get_misses()), (1, 1));
+
+        controller
+            .update_user_email("Kirill", "kirill@new.domain")
+            .await
+            .expect("Cant update user");
+
+        let user = controller
+            .get_cached_user_by_name("Kirill")
+            .await
+            .expect("Cant get user")
+            .expect("User not found");
+
+        assert_eq!(user.get_email(), "kirill@new.domain");
+        assert_eq!((cache.get_hits(), cache.get_misses()), (1, 2));
+
+        assert_eq!(controller.count_users().await.expect("Cant count"), 3);
+
+        controller.remove_user("Kirill").await.expect("Cant remove");
+
+        assert_eq!(controller.count_users().await.expect("Cant count"), 2);
+    }
+
+    #[tokio::test]
+    async fn should_run_middleware_on_cache_hits_and_scope_keys_by_principal() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: None,
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        for _ in 0..2 {
+            controller
+                .get_cached_user_by_name("Kirill")
+                .await
+                .expect("Cant get user");
+        }
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                "before Query",
+                "after Query true",
+                "before Query",
+                "after Query true",
+            ]
+        );
+
+        for user_id in ["admin", "guest", "admin"] {
+            let envelope =
+                MessageEnvelope::new().with_principal(Principal::new(user_id).with_tenant("acme"));
+
+            let query = GetUserByNameQuery::new("Kirill");
+
+            let policy = query.get_cache_policy();
+
+            bus.with_envelope(envelope)
+                .cached_query(Box::new(query), policy)
+                .await
+                .expect("Cant get user");
+        }
+
+        let cache = bus.get_query_cache().await.expect("Cant get cache");
+
+        assert_eq!((cache.get_hits(), cache.get_misses()), (2, 3));
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_result_of_query_racing_invalidation() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cache = bus.get_query_cache().await.expect("Cant get cache").clone();
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let policy = CachePolicy::new("report", Duration::from_secs(60)).with_tag("reports");
+
+        let handle = tokio::spawn({
+            let bus = bus.clone();
+            let gate = gate.clone();
+            let policy = policy.clone();
+
+            async move {
+                bus.cached_query(Box::new(GatedQuery { gate, fail: false }), policy)
+                    .await
+            }
+        });
+
+        wait_until(|| cache.get_misses() == 1).await;
+
+        cache.invalidate(&["reports".to_string()]);
+
+        gate.add_permits(1);
+
+        let report = handle
+            .await
+            .expect("Cant join query")
+            .expect("Cant execute query");
+
+        assert_eq!(report, "report");
+        assert!(cache.get_store().get("report").is_none());
+
+        gate.add_permits(1);
+
+        bus.cached_query(Box::new(GatedQuery { gate, fail: false }), policy)
+            .await
+            .expect("Cant execute query");
+
+        assert!(cache.get_store().get("report").is_some());
+    }
+
+    #[tokio::test]
+    async fn should_cache_result_of_query_racing_invalidation_of_other_tag() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cache = bus.get_query_cache().await.expect("Cant get cache").clone();
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let policy = CachePolicy::new("report", Duration::from_secs(60)).with_tag("reports");
+
+        let handle = tokio::spawn({
+            let bus = bus.clone();
+            let gate = gate.clone();
+
+            async move {
+                bus.cached_query(Box::new(GatedQuery { gate, fail: false }), policy)
+                    .await
+            }
+        });
+
+        wait_until(|| cache.get_misses() == 1).await;
+
+        cache.invalidate(&["users".to_string()]);
+
+        gate.add_permits(1);
+
+        handle
+            .await
+            .expect("Cant join query")
+            .expect("Cant execute query");
+
+        assert!(cache.get_store().get("report").is_some());
+    }
+
+    #[tokio::test]
+    async fn should_feed_projection_from_event_store_and_rebuild_it() {
+        let events = EventStoreProvider::new(Arc::new(InMemoryEventStoreAdapter::new()));
+
+        let directory = Arc::new(UserDirectoryProjection::default());
+
+        let projections = ProjectionProvider::new(Arc::new(InMemoryCheckpointStoreAdapter::new()))
+            .with_projection(directory.clone())
+            .with_batch_size(2);
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(EventSubscriptionsProvider::new())
+            .with_provider(events)
+            .with_provider(projections)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        for (id, name) in [("1", "Rita"), ("2", "Daria")] {
+            let mut user = bus
+                .load_aggregate::<UserAggregate>(id)
+                .await
+                .expect("Cant load aggregate");
+
+            let registered = vec![UserAggregateEvent::Registered {
+                name: name.to_string(),
+                email: format!("{}@mail.domain", name.to_lowercase()),
+            }];
+
+            bus.save_aggregate(&mut user, registered)
+                .await
+                .expect("Cant save aggregate");
+        }
+
+        bus.send_command(Box::new(RenameUserAggregateCommand {
+            id: "1",
+            name: "Margarita",
+        }))
+        .await
+        .expect("Cant rename user");
+
+        let projections = bus
+            .get_projections()
+            .await
+            .expect("Cant resolve PROJECTION_PROVIDER")
+            .expect("Projections are not registered");
+
+        timeout(Duration::from_secs(1), async {
+            while projections
+                .get_checkpoint("user_directory")
+                .await
+                .unwrap_or(0)
+                < 3
+            {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Projection did not catch up");
+
+        let expected = BTreeMap::from([
+            ("user-1".to_string(), "Margarita".to_string()),
+            ("user-2".to_string(), "Daria".to_string()),
+        ]);
+
+        assert_eq!(*directory.names.read().await, expected);
+
+        assert_eq!(bus.catch_up_projections().await.expect("Cant catch up"), 0);
+
+        directory
+            .names
+            .write()
+            .await
+            .insert("user-3".to_string(), "Stale".to_string());
+
+        let applied = bus
+            .rebuild_projection("user_directory")
+            .await
+            .expect("Cant rebuild projection");
+
+        assert_eq!(applied, 3);
+        assert_eq!(*directory.names.read().await, expected);
+    }
+
+    #[tokio::test]
+    async fn should_feed_users_projection_from_event_bus() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        controller
+            .create_user("Rita", "rita@mail.domain")
+            .await
+            .expect("Cant create user");
+
+        controller
+            .update_user_email("Rita", "rita@new.domain")
+            .await
+            .expect("Cant update user");
+
+        controller.remove_user("Andrey").await.expect("Cant remove");
+
+        // A user removed before the event is applied doesn't block the projection.
+        bus.publish_and_wait(UserRemovedEvent::new("Andrey"))
+            .await
+            .expect("Cant publish removed user");
+
+        let projections = bus
+            .get_projections()
+            .await
+            .expect("Cant resolve PROJECTION_PROVIDER")
+            .expect("Projections are not registered");
+
+        assert_eq!(projections.get_checkpoint("users").await.unwrap(), 7);
+
+        let applied = bus
+            .rebuild_projection("users")
+            .await
+            .expect("Cant rebuild projection");
+
+        assert_eq!(applied, 7);
+        assert_eq!(controller.count_users().await.expect("Cant count"), 3);
+
+        let user = controller
+            .get_user_by_name("Rita")
+            .await
+            .expect("Cant get user")
+            .expect("User not found");
+
+        assert_eq!(user.get_email(), "rita@new.domain");
+    }
+
+    #[tokio::test]
+    async fn should_store_projected_event_once_across_outbox_relays() {
+        let store = Arc::new(InMemoryOutboxStoreAdapter::new());
+
+        let outbox = OutboxProvider::new(store.clone()).with_message::<UserCreatedEvent>();
+
+        let subscriptions = EventSubscriptionsProvider::new().with_subscriber::<UserCreatedEvent>(
+            Arc::new(FlakyUserCreatedSubscriber {
+                attempts: Arc::new(AtomicU32::new(0)),
+            }),
+        );
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let di = di
+            .inject(InjectAdapter {
+                token: OutboxProvider::token(),
+                factory: Arc::new(move |_| outbox.clone()),
+            })
+            .await
+            .expect("Cant inject outbox");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        bus.publish_with_outbox(UserCreatedEvent::new("Rita", "rita@mail.domain"))
+            .await
+            .expect_err("Flaky subscriber should fail the first delivery");
+
+        assert_eq!(store.list().await.expect("Cant list outbox").len(), 1);
+
+        // The projection took the first delivery, the relay redelivers to every subscriber.
+        assert_eq!(bus.relay_outbox().await.expect("Cant relay outbox"), 1);
+
+        let events = bus
+            .get_event_store()
+            .await
+            .expect("Cant resolve EVENT_STORE_PROVIDER")
+            .get_store()
+            .read_stream(<UserCreatedEvent as OutboxMessagePort>::get_name(), 0)
+            .await
+            .expect("Cant read stream");
+
+        assert_eq!(events.len(), get_users().len() + 1);
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        assert_eq!(
+            controller.count_users().await.expect("Cant count"),
+            get_users().len() + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn should_skip_poison_event_after_failed_attempts() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let handler = Arc::new(ChannelEventFailureHandler { sender });
+
+        let di = DI::new(Arc::new(ContainerContext::new()))
+            .inject(InjectAdapter {
+                token: EventFailureProvider::token(),
+                factory: Arc::new(move |_| EventFailureProvider::new(handler.clone())),
+            })
+            .await
+            .expect("Cant inject EVENT_FAILURE_PROVIDER");
+
+        let store = Arc::new(InMemoryEventStoreAdapter::new());
+
+        let projections = ProjectionProvider::new(Arc::new(InMemoryCheckpointStoreAdapter::new()))
+            .with_projection(Arc::new(BrokenProjection))
+            .with_poison_policy(PoisonEventPolicy::Skip { attempts: 2 });
+
+        store
+            .append(
+                "user-1",
+                ExpectedVersion::NoStream,
+                vec![EventData::new(
+                    "UserRegistered",
+                    "Rita\nrita@mail.domain".to_string(),
+                )],
+            )
+            .await
+            .expect("Cant append events");
+
+        projections
+            .catch_up(store.as_ref(), di.get_context())
+            .await
+            .expect_err("First attempt should fail");
+
+        assert_eq!(projections.get_checkpoint("broken").await.unwrap(), 0);
+        assert!(projections.get_skipped_events().is_empty());
+
+        let applied = projections
+            .catch_up(store.as_ref(), di.get_context())
+            .await
+            .expect("Poison event should be skipped");
+
+        assert_eq!(applied, 0);
+        assert_eq!(projections.get_checkpoint("broken").await.unwrap(), 1);
+
+        let skipped = projections.get_skipped_events();
+
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].get_projection(), "broken");
+        assert_eq!(skipped[0].get_event().get_position(), 1);
+        assert_eq!(skipped[0].get_error(), "Projection is broken");
+        assert_eq!(skipped[0].get_attempts(), 2);
+
+        let failure = receiver.try_recv().expect("Skipped event was not reported");
+
+        assert_eq!(failure.get_id(), 1);
+        assert_eq!(failure.get_subscriber(), Some("broken"));
+    }
+
+    #[tokio::test]
+    async fn should_catch_up_projections_independently() {
+        let store = Arc::new(InMemoryEventStoreAdapter::new());
+
+        let directory = Arc::new(UserDirectoryProjection::default());
+
+        let projections = ProjectionProvider::new(Arc::new(InMemoryCheckpointStoreAdapter::new()))
+            .with_projection(Arc::new(BrokenProjection))
+            .with_projection(directory.clone());
+
+        store
+            .append(
+                "user-1",
+                ExpectedVersion::NoStream,
+                vec![EventData::new(
+                    "UserRegistered",
+                    "Rita\nrita@mail.domain".to_string(),
+                )],
+            )
+            .await
+            .expect("Cant append events");
+
+        let context = DI::new(Arc::new(ContainerContext::new())).get_context();
+
+        let error = projections
+            .catch_up(store.as_ref(), context)
+            .await
+            .expect_err("Broken projection should fail");
+
+        let error = error
+            .downcast_ref::<ProjectionError>()
+            .expect("Error should be a projection error");
+
+        assert_eq!(error.get_applied(), 1);
+        assert_eq!(error.get_failures().len(), 1);
+        assert_eq!(error.get_failures()[0].0, "broken");
+
+        assert_eq!(projections.get_checkpoint("broken").await.unwrap(), 0);
+        assert_eq!(
+            projections.get_checkpoint("user_directory").await.unwrap(),
+            1
+        );
+        assert_eq!(directory.names.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_complete_saga_and_compensate_failed_step() {
+        let saga = Arc::new(OnboardingSaga);
+
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_saga::<OnboardingStartedEvent, _>(saga.clone())
+            .with_saga::<MailboxProvisionedEvent, _>(saga);
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        for name in ["Rita", "Broken"] {
+            bus.publish_and_wait(OnboardingStartedEvent {
+                name: name.to_string(),
+            })
+            .await
+            .expect("Cant start onboarding");
+        }
+
+        let wait_for = |name: &'static str| {
+            let bus = bus.clone();
+
+            async move {
+                timeout(Duration::from_secs(1), async {
+                    loop {
+                        let record = bus
+                            .get_saga("onboarding", name)
+                            .await
+                            .expect("Cant load saga");
+
+                        if let Some(record) =
+                            record.filter(|record| record.get_status().is_finished())
+                        {
+                            return record;
+                        }
+
+                        sleep(Duration::from_millis(5)).await;
+                    }
+                })
+                .await
+                .expect("Saga did not finish")
+            }
+        };
+
+        let completed = wait_for("Rita").await;
+
+        assert_eq!(completed.get_status(), SagaStatus::Completed);
+        assert_eq!(completed.get_error(), None);
+
+        let compensated = wait_for("Broken").await;
+
+        assert_eq!(compensated.get_status(), SagaStatus::Compensated);
+        assert_eq!(
+            compensated.get_error(),
+            Some("Mail server rejected welcome")
+        );
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AuditService");
+
+        let mut records = audit.get_records().await.expect("Cant get records");
+
+        records.sort();
+
+        assert_eq!(
+            records,
+            vec![
+                "mailbox-removed:Broken",
+                "mailbox:Broken",
+                "mailbox:Rita",
+                "welcome:Rita"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_scheduled_messages_on_mock_clock() {
+        let clock = Arc::new(ManualClockAdapter::new(SystemTime::UNIX_EPOCH));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(
+                EventSubscriptionsProvider::new()
+                    .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+            )
+            .with_provider(SchedulerProvider::new(clock.clone()))
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let expire = bus
+            .schedule_command(
+                clock.now() + day,
+                Box::new(CreateUserCommand::new("Rita", "rita@mail.domain")),
+            )
+            .await
+            .expect("Cant schedule command");
+
+        bus.schedule_event(
+            Duration::from_secs(60 * 60),
+            Box::new(RenameUserEvent::new("Kirill", "Kir")),
+        )
+        .await
+        .expect("Cant schedule event");
+
+        let cancelled = bus
+            .schedule_event(
+                Duration::from_secs(60 * 60),
+                Box::new(RenameUserEvent::new("Daria", "Dasha")),
+            )
+            .await
+            .expect("Cant schedule event");
+
+        assert!(bus.cancel_schedule(cancelled).await.expect("Cant cancel"));
+        assert!(!bus.cancel_schedule(cancelled).await.expect("Cant cancel"));
+
+        clock.advance(Duration::from_secs(60 * 60));
+
+        timeout(Duration::from_secs(1), async {
+            while controller
+                .get_user_by_name("Kir")
+                .await
+                .ok()
+                .flatten()
+                .is_none()
+            {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Scheduled event did not run");
+
+        let scheduler = bus.get_scheduler().await.expect("Cant get scheduler");
+
+        assert_eq!(scheduler.get_pending(), vec![expire]);
+
+        assert!(
+            controller
+                .get_user_by_name("Daria")
+                .await
+                .expect("Cant get user")
+                .is_some()
+        );
+
+        clock.advance(day);
+
+        timeout(Duration::from_secs(1), async {
+            while controller
+                .get_user_by_name("Rita")
+                .await
+                .ok()
+                .flatten()
+                .is_none()
+            {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Scheduled command did not run");
+
+        assert!(scheduler.get_pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_restore_persisted_schedules_after_restart() {
+        let clock = Arc::new(ManualClockAdapter::new(SystemTime::UNIX_EPOCH));
+
+        let store = Arc::new(InMemoryScheduleStoreAdapter::new());
+
+        let create_scheduler = || {
+            SchedulerProvider::new(clock.clone())
+                .with_store(store.clone())
+                .with_message::<AuditedEvent>()
+                .with_command::<RemoveUserCommand>()
+        };
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(
+                EventSubscriptionsProvider::new()
+                    .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+            )
+            .with_provider(create_scheduler())
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let envelope = MessageEnvelope::new().with_header("request-id", "r-4");
+
+        let id = bus
+            .with_envelope(envelope.clone())
+            .schedule_persistent_event(
+                clock.now() + Duration::from_secs(60),
+                AuditedEvent {
+                    message: "Expired".to_string(),
+                },
+            )
+            .await
+            .expect("Cant schedule event");
+
+        let mut ids = vec![id];
+
+        for name in ["Andrey", "Nobody"] {
+            let id = bus
+                .schedule_persistent_command(
+                    clock.now() + Duration::from_secs(60),
+                    RemoveUserCommand::new(name),
+                )
+                .await
+                .expect("Cant schedule command");
+
+            ids.push(id);
+        }
+
+        // Simulates a crash, the pending tasks are gone but the records are kept.
+        let scheduler = bus.get_scheduler().await.expect("Cant get scheduler");
+
+        for id in &ids {
+            scheduler.cancel(*id);
+        }
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(
+                EventSubscriptionsProvider::new()
+                    .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+            )
+            .with_provider(create_scheduler())
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        // A restarted scheduler continues after the stored ids even before restoring them.
+        assert!(create_scheduler().next_id().await.expect("Cant get id") > ids[2]);
+
+        let records = store.list().await.expect("Cant list schedules");
+
+        assert_eq!(records[0].get_envelope(), Some(&envelope));
+        assert_eq!(records[1].get_envelope(), None);
+
+        assert_eq!(bus.restore_schedules().await.expect("Cant restore"), 3);
+        assert_eq!(bus.restore_schedules().await.expect("Cant restore"), 0);
+
+        let scheduler = bus.get_scheduler().await.expect("Cant get scheduler");
+
+        assert!(scheduler.next_id().await.expect("Cant get id") > ids[2]);
+
+        clock.advance(Duration::from_secs(60));
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AuditService");
+
+        wait_until(|| scheduler.get_pending().is_empty()).await;
+
+        assert_eq!(
+            audit.get_records().await.expect("Cant get records"),
+            vec!["Expired"]
+        );
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        assert_eq!(controller.count_users().await.expect("Cant count"), 2);
+
+        // The failed command keeps its record for the next restore.
+        let kept = store
+            .list()
+            .await
+            .expect("Cant list schedules")
+            .iter()
+            .map(ScheduledRecord::get_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(kept, vec![ids[2]]);
+        assert_eq!(bus.restore_schedules().await.expect("Cant restore"), 1);
+    }
+
+    #[tokio::test]
+    async fn should_run_schedules_without_cancellation_of_caller_and_keep_running_records() {
+        let clock = Arc::new(ManualClockAdapter::new(SystemTime::UNIX_EPOCH));
+
+        let store = Arc::new(InMemoryScheduleStoreAdapter::new());
+
+        let scheduler = SchedulerProvider::new(clock.clone()).with_store(store.clone());
+
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_subscriber::<AuditedEvent>(Arc::new(DeferredProbeSubscriber));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .with_provider(scheduler)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let cancellation = CancellationToken::new();
+
+        bus.with_cancellation(cancellation.clone())
+            .schedule_command(
+                clock.now() + Duration::from_secs(60),
+                Box::new(DeferredProbeCommand { outbox: false }),
+            )
+            .await
+            .expect("Cant schedule command");
+
+        // The request that scheduled the command is done before the command runs.
+        cancellation.cancel();
+
+        clock.advance(Duration::from_secs(60));
+
+        let audit = AuditService::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve AUDIT_SERVICE");
+
+        let records = timeout(Duration::from_secs(1), async {
+            loop {
+                let records = audit.get_records().await.expect("Cant get records");
+
+                if !records.is_empty() {
+                    return records;
+                }
+
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Scheduled command did not run");
+
+        assert!(records[0].ends_with(" false"));
+
+        let started = Arc::new(AtomicBool::new(false));
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let running = bus
+            .schedule_command(
+                clock.now(),
+                Box::new(HeldCommand {
+                    started: started.clone(),
+                    gate: gate.clone(),
+                }),
+            )
+            .await
+            .expect("Cant schedule command");
+
+        store
+            .save(ScheduledRecord::new(
+                running,
+                "HeldCommand",
+                String::new(),
+                clock.now(),
+            ))
+            .await
+            .expect("Cant save schedule");
+
+        wait_until(|| started.load(Ordering::SeqCst)).await;
+
+        assert!(!bus.cancel_schedule(running).await.expect("Cant cancel"));
+        assert_eq!(store.list().await.expect("Cant list schedules").len(), 1);
+
+        gate.add_permits(1);
+
+        let scheduler = bus.get_scheduler().await.expect("Cant get scheduler");
+
+        wait_until(|| scheduler.get_pending().is_empty()).await;
+
+        assert!(store.list().await.expect("Cant list schedules").is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_wait_for_running_schedules_on_drain_and_abort_them_on_shutdown() {
+        for drain in [true, false] {
+            let clock = Arc::new(ManualClockAdapter::new(SystemTime::UNIX_EPOCH));
+
+            let di = ExampleDiBuilder::new()
+                .with_subscriptions(
+                    EventSubscriptionsProvider::new()
+                        .with_subscriber::<AuditedEvent>(Arc::new(AuditedSubscriber)),
+                )
+                .with_provider(SchedulerProvider::new(clock.clone()))
+                .build()
+                .await
+                .expect("Cant create DI");
+
+            let bus = CqrsProvider::get_adapter(&di.get_context())
+                .await
+                .expect("Cant resolve CQRS_PROVIDER");
+
+            let started = Arc::new(AtomicBool::new(false));
+
+            let gate = Arc::new(Semaphore::new(0));
+
+            let running = bus
+                .schedule_command(
+                    clock.now(),
+                    Box::new(HeldCommand {
+                        started: started.clone(),
+                        gate: gate.clone(),
+                    }),
+                )
+                .await
+                .expect("Cant schedule command");
+
+            let future = bus
+                .schedule_command(
+                    clock.now() + Duration::from_secs(60),
+                    Box::new(CreateUserCommand::new("Rita", "rita@mail.domain")),
+                )
+                .await
+                .expect("Cant schedule command");
+
+            wait_until(|| started.load(Ordering::SeqCst)).await;
+
+            if drain {
+                let draining = tokio::spawn({
+                    let bus = bus.clone();
+
+                    async move { bus.drain().await }
+                });
+
+                sleep(Duration::from_millis(20)).await;
+
+                assert!(!draining.is_finished());
+
+                gate.add_permits(1);
+
+                draining
+                    .await
+                    .expect("Cant join drain")
+                    .expect("Cant drain");
+            } else {
+                let report = bus
+                    .shutdown(Duration::from_millis(20))
+                    .await
+                    .expect("Cant shut down");
+
+                assert_eq!(report.get_cancelled_schedules(), &[future]);
+                assert_eq!(report.get_aborted_schedules(), &[running]);
+                assert!(!report.is_clean());
+            }
+
+            let scheduler = bus.get_scheduler().await.expect("Cant get scheduler");
+
+            assert!(scheduler.get_pending().is_empty());
+        }
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        timeout(Duration::from_secs(1), async {
+            while !condition() {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Condition timed out");
+    }
+
+    #[tokio::test]
+    async fn should_skip_overlapping_recurring_runs_and_catch_up_missed_ones() {
+        let clock = Arc::new(ManualClockAdapter::new(SystemTime::UNIX_EPOCH));
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let job_gate = gate.clone();
+
+        let schedule = CronSchedule::parse("* * * * *").expect("Cant parse cron");
+
+        let jobs =
+            RecurringJobsProvider::new().with_job(RecurringJob::new("sync", schedule, move || {
+                Box::new(GatedCommand {
+                    gate: job_gate.clone(),
+                })
+            }));
+
+        let scheduler = SchedulerProvider::new(clock.clone());
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(EventSubscriptionsProvider::new())
+            .with_provider(scheduler)
+            .with_provider(jobs)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        assert_eq!(bus.start_recurring_jobs().await.expect("Cant start"), 1);
+        assert_eq!(bus.start_recurring_jobs().await.expect("Cant start"), 0);
+
+        let stats = bus
+            .get_recurring_jobs()
+            .await
+            .expect("Cant resolve RECURRING_JOBS_PROVIDER")
+            .and_then(|jobs| jobs.get_stats("sync"))
+            .expect("Job is not registered");
+
+        let minutes = |minutes: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(minutes * 60);
+
+        wait_until(|| stats.get_next_run() == Some(minutes(1))).await;
+
+        clock.advance(Duration::from_secs(60));
+
+        wait_until(|| stats.get_next_run() == Some(minutes(2)) && stats.is_running()).await;
+
+        clock.advance(Duration::from_secs(60));
+
+        wait_until(|| stats.get_next_run() == Some(minutes(3))).await;
+
+        assert_eq!(stats.get_skipped(), 1);
+
+        gate.add_permits(1);
+
+        wait_until(|| stats.get_runs() == 1 && !stats.is_running()).await;
+
+        clock.advance(Duration::from_secs(5 * 60));
+
+        wait_until(|| stats.get_next_run() == Some(minutes(8))).await;
+
+        gate.add_permits(1);
+
+        wait_until(|| stats.get_runs() == 2).await;
+
+        assert_eq!(stats.get_missed(), 4);
+        assert_eq!(stats.get_skipped(), 1);
+
+        clock.advance(Duration::from_secs(60));
+
+        wait_until(|| stats.is_running()).await;
+
+        let draining = tokio::spawn({
+            let bus = bus.clone();
+
+            async move { bus.drain().await }
+        });
+
+        sleep(Duration::from_millis(20)).await;
+
+        assert!(!draining.is_finished());
+
+        gate.add_permits(1);
+
+        draining
+            .await
+            .expect("Cant join drain")
+            .expect("Cant drain");
+
+        assert_eq!(stats.get_runs(), 3);
+        assert_eq!(stats.get_next_run(), None);
+
+        assert_eq!(bus.start_recurring_jobs().await.expect("Cant start"), 1);
+        wait_until(|| stats.get_next_run() == Some(minutes(9))).await;
+
+        clock.advance(Duration::from_secs(60));
+
+        wait_until(|| stats.is_running()).await;
+
+        let report = bus
+            .shutdown(Duration::from_millis(20))
+            .await
+            .expect("Cant shut down");
+
+        assert_eq!(report.get_aborted_jobs(), &["sync"]);
+        assert!(!report.is_clean());
+        assert!(!stats.is_running());
+        assert_eq!(stats.get_runs(), 3);
+
+        assert_eq!(bus.stop_recurring_jobs().await.expect("Cant stop"), 0);
+    }
+
+    #[tokio::test]
+    async fn should_deduplicate_commands_by_idempotency_key() {
+        let store = Arc::new(InMemoryIdempotencyStoreAdapter::new());
+
+        let idempotency = IdempotencyProvider::new(store.clone());
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(EventSubscriptionsProvider::new())
+            .with_provider(idempotency)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        for _ in 0..2 {
+            controller
+                .create_user_once("request-1", "Rita", "rita@mail.domain")
+                .await
+                .expect("Cant create user");
+        }
+
+        assert_eq!(controller.count_users().await.expect("Cant count"), 4);
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let first = tokio::spawn({
+            let bus = bus.clone();
+            let gate = gate.clone();
+
+            async move {
+                bus.command_with_idempotency_key(Box::new(GatedCommand { gate }), "sync-1")
+                    .await
+            }
+        });
+
+        timeout(Duration::from_secs(1), async {
+            while store.len().await < 2 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("First command did not start");
+
+        let conflict = bus
+            .command_with_idempotency_key(Box::new(GatedCommand { gate: gate.clone() }), "sync-1")
+            .await
+            .expect_err("Second command should be rejected");
+
+        let conflict = conflict
+            .downcast_ref::<IdempotencyConflictError>()
+            .expect("Error should be an idempotency conflict");
+
+        assert_eq!(conflict.get_key(), "sync-1");
+
+        gate.add_permits(1);
+
+        first
+            .await
+            .expect("Cant join command")
+            .expect("Cant execute command");
+
+        // The gate has no permits left, so only a stored outcome can return.
+        timeout(
+            Duration::from_secs(1),
+            bus.command_with_idempotency_key(Box::new(GatedCommand { gate }), "sync-1"),
+        )
+        .await
+        .expect("Stored outcome was not returned")
+        .expect("Cant execute command");
+    }
+
+    #[tokio::test]
+    async fn should_release_idempotency_key_of_dropped_or_expired_commands() {
+        let store = Arc::new(InMemoryIdempotencyStoreAdapter::new());
+
+        let idempotency =
+            IdempotencyProvider::new(store.clone()).with_lease(Duration::from_millis(50));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(EventSubscriptionsProvider::new())
+            .with_provider(idempotency)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let held = |gate: &Arc<Semaphore>| {
+            let started = Arc::new(AtomicBool::new(false));
+
+            let command = HeldCommand {
+                started: started.clone(),
+                gate: gate.clone(),
+            };
+
+            (started, Box::new(command))
+        };
+
+        let closed = Arc::new(Semaphore::new(0));
+
+        let (started, command) = held(&closed);
+
+        let dropped = tokio::spawn({
+            let bus = bus.clone();
+
+            async move { bus.command_with_idempotency_key(command, "job-1").await }
+        });
+
+        wait_until(|| started.load(Ordering::SeqCst)).await;
+
+        dropped.abort();
+
+        timeout(Duration::from_secs(1), async {
+            while !store.is_empty().await {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("Key of the dropped command was not released");
+
+        let open = Arc::new(Semaphore::new(2));
+
+        bus.command_with_idempotency_key(held(&open).1, "job-1")
+            .await
+            .expect("Cant retry dropped command");
+
+        // Another command type doesn't see the stored outcome.
+        bus.command_with_idempotency_key(Box::new(GatedCommand { gate: open }), "job-1")
+            .await
+            .expect("Cant execute command");
+
+        assert_eq!(store.len().await, 2);
+
+        let (started, command) = held(&closed);
+
+        tokio::spawn({
+            let bus = bus.clone();
+
+            async move { bus.command_with_idempotency_key(command, "job-2").await }
+        });
+
+        wait_until(|| started.load(Ordering::SeqCst)).await;
+
+        // A running command renews its lease.
+        sleep(Duration::from_millis(120)).await;
+
+        bus.command_with_idempotency_key(held(&closed).1, "job-2")
+            .await
+            .expect_err("Command should be in progress")
+            .downcast_ref::<IdempotencyConflictError>()
+            .expect("Error should be an idempotency conflict");
+
+        // The lease of a crashed owner expires, a stale owner can't release the next lease.
+        let crashed = format!("{}:job-3", std::any::type_name::<HeldCommand>());
+
+        store
+            .begin(&crashed, "crashed", Duration::from_millis(50))
+            .await
+            .expect("Cant begin");
+
+        bus.command_with_idempotency_key(held(&closed).1, "job-3")
+            .await
+            .expect_err("Command should be in progress")
+            .downcast_ref::<IdempotencyConflictError>()
+            .expect("Error should be an idempotency conflict");
+
+        sleep(Duration::from_millis(60)).await;
+
+        let (started, command) = held(&closed);
+
+        tokio::spawn({
+            let bus = bus.clone();
+
+            async move { bus.command_with_idempotency_key(command, "job-3").await }
+        });
+
+        wait_until(|| started.load(Ordering::SeqCst)).await;
+
+        assert!(
+            !store
+                .release(&crashed, "crashed")
+                .await
+                .expect("Cant release")
+        );
+
+        bus.command_with_idempotency_key(held(&closed).1, "job-3")
+            .await
+            .expect_err("Command should be in progress")
+            .downcast_ref::<IdempotencyConflictError>()
+            .expect("Error should be an idempotency conflict");
+    }
+
+    #[tokio::test]
+    async fn should_scope_idempotency_keys_of_boxed_commands() {
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(EventSubscriptionsProvider::new())
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let boxed = |gate: &Arc<Semaphore>| -> Box<
+            dyn CommandHandlerPort<Context = Arc<dyn ContextPort>, Output = ()>,
+        > { Box::new(GatedCommand { gate: gate.clone() }) };
+
+        let gate = Arc::new(Semaphore::new(2));
+
+        bus.command_with_idempotency_scope(boxed(&gate), "first", "key-1")
+            .await
+            .expect("Cant execute command");
+
+        bus.command_with_idempotency_scope(boxed(&gate), "second", "key-1")
+            .await
+            .expect("Cant execute command");
+
+        assert_eq!(gate.available_permits(), 0);
+
+        // The gate has no permits left, so only a stored outcome can return.
+        timeout(
+            Duration::from_secs(1),
+            bus.command_with_idempotency_scope(boxed(&gate), "first", "key-1"),
+        )
+        .await
+        .expect("Stored outcome was not returned")
+        .expect("Cant execute command");
+    }
+
+    #[tokio::test]
+    async fn should_share_single_flight_query_between_concurrent_callers() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let flights = bus
+            .get_single_flight()
+            .await
+            .expect("Cant resolve SINGLE_FLIGHT_PROVIDER")
+            .clone();
+
+        for (round, fail) in [(1, false), (2, true)] {
+            let gate = Arc::new(Semaphore::new(0));
+
+            let callers = (0..3)
+                .map(|_| {
+                    let bus = bus.clone();
+                    let gate = gate.clone();
+
+                    tokio::spawn(async move {
+                        bus.single_flight_query(Box::new(GatedQuery { gate, fail }), "report")
+                            .await
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            wait_until(|| flights.get_joined() == round * 2).await;
+
+            gate.add_permits(1);
+
+            for caller in callers {
+                let result = caller.await.expect("Cant join query");
+
+                if fail {
+                    let error = result.expect_err("Query should fail");
+
+                    assert!(error.downcast_ref::<SharedError>().is_some());
+                    assert_eq!(error.to_string(), "Gated query failed");
+                } else {
+                    assert_eq!(result.expect("Cant execute query"), "report");
+                }
+            }
+
+            assert_eq!(flights.get_executions(), round);
+            assert_eq!(flights.get_in_flight(), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_run_middleware_per_single_flight_caller_and_scope_keys_by_principal() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: None,
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let flights = bus
+            .get_single_flight()
+            .await
+            .expect("Cant resolve SINGLE_FLIGHT_PROVIDER")
+            .clone();
+
+        let gate = Arc::new(Semaphore::new(0));
+
+        let callers = ["admin", "admin", "guest"]
+            .into_iter()
+            .map(|user_id| {
+                let envelope = MessageEnvelope::new()
+                    .with_principal(Principal::new(user_id).with_tenant("acme"));
+
+                let bus = bus.with_envelope(envelope);
+                let gate = gate.clone();
+
+                tokio::spawn(async move {
+                    bus.single_flight_query(Box::new(GatedQuery { gate, fail: false }), "report")
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        wait_until(|| flights.get_joined() == 1 && flights.get_executions() == 2).await;
+
+        gate.add_permits(2);
+
+        for caller in callers {
+            let report = caller
+                .await
+                .expect("Cant join query")
+                .expect("Cant execute query");
+
+            assert_eq!(report, "report");
+        }
+
+        let records = records.lock().unwrap();
+
+        let before = records
+            .iter()
+            .filter(|record| *record == "before Query")
+            .count();
+
+        assert_eq!(before, 3);
+        assert_eq!(flights.get_in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_batch_loads_within_one_request() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve UserController");
+
+        let users = controller
+            .get_users_by_names(&["Andrey", "Daria", "Unknown", "Andrey"])
+            .await
+            .expect("Cant load users");
+
+        let names: Vec<Option<&str>> = users
+            .iter()
+            .map(|user| user.as_ref().map(User::get_name))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![Some("Andrey"), Some("Daria"), None, Some("Andrey")]
+        );
+
+        let loader = BatchLoaderProvider::resolve(&context)
+            .await
+            .expect("Cant resolve BATCH_LOADER_PROVIDER")
+            .get_loader::<UsersByNameLoader>()
+            .expect("Cant get UsersByNameLoader");
+
+        assert_eq!(loader.get_batches(), 1);
+        assert_eq!(loader.get_keys(), 3);
+
+        controller
+            .get_users_by_names(&["Kirill"])
+            .await
+            .expect("Cant load users");
+
+        assert_eq!(loader.get_batches(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_load_batch_of_dropped_first_caller() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let users = Arc::new(UsersByNameLoader::new());
+
+        let loader = DataLoader::new(users.clone(), Duration::from_millis(20));
+
+        let mut first = Box::pin(loader.load("Andrey".to_string(), di.get_context()));
+
+        assert!(poll!(&mut first).is_pending());
+
+        let second = loader.load("Daria".to_string(), di.get_context());
+
+        drop(first);
+
+        let user = second
+            .await
+            .expect("Cant load user")
+            .expect("User not found");
+
+        assert_eq!(user.get_name(), "Daria");
+        assert_eq!((users.get_batches(), users.get_keys()), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn should_stream_query_items_on_demand_through_middleware() {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = ExampleDiBuilder::new()
+            .with_provider(
+                MiddlewareProvider::default().with(Arc::new(RecordingMiddleware {
+                    records: records.clone(),
+                    rejected: None,
+                })),
+            )
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let controller = UserController::get_adapter(&context)
+            .await
+            .expect("Cant resolve UserController");
+
+        let users: Vec<String> = controller
+            .export_users()
+            .await
+            .expect("Cant export users")
+            .map(|user| user.expect("Cant read user").get_name().to_string())
+            .collect()
+            .await;
+
+        assert_eq!(users, vec!["Andrey", "Daria", "Kirill"]);
+
+        let produced = Arc::new(AtomicU32::new(0));
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let mut items = bus
+            .stream_query(Box::new(CountingStreamQuery {
+                produced: produced.clone(),
+                stall_after: None,
+            }))
+            .await
+            .expect("Cant open stream");
+
+        assert_eq!(items.next().await.expect("Stream ended").ok(), Some(1));
+        assert_eq!(items.next().await.expect("Stream ended").ok(), Some(2));
+
+        sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(produced.load(Ordering::SeqCst), 2);
+
+        drop(items);
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                "before Query",
+                "after Query true",
+                "before Query",
+                "after Query false"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_time_out_stalled_query_stream() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let timeouts = TimeoutProvider::default().with_query_timeout(Duration::from_millis(50));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: TimeoutProvider::token(),
+                factory: Arc::new(move |_| timeouts),
+            })
+            .await
+            .expect("Cant inject TIMEOUT_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let mut items = bus
+            .stream_query(Box::new(CountingStreamQuery {
+                produced: Arc::new(AtomicU32::new(0)),
+                stall_after: Some(1),
+            }))
+            .await
+            .expect("Cant open stream");
+
+        sleep(Duration::from_millis(80)).await;
+
+        assert_eq!(items.next().await.expect("Stream ended").ok(), Some(1));
+
+        let error = items
+            .next()
+            .await
+            .expect("Stream ended")
+            .expect_err("Stalled stream should time out");
+
+        assert!(error.downcast_ref::<TimeoutError>().is_some());
+
+        assert!(items.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_page_users_by_offset_and_cursor() {
+        let di = create_di().await.expect("Cant create DI");
+
+        let controller = UserController::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve UserController");
+
+        controller
+            .create_user("Boris", "boris@mail.domain")
+            .await
+            .expect("Cant create user");
+
+        let request = PageRequest::new(2).with_sort(PageSort::descending("name"));
+
+        let mut names = Vec::new();
+
+        let mut page = controller
+            .list_users(request.clone())
+            .await
+            .expect("Cant list users");
+
+        assert_eq!(page.get_total_estimate(), Some(4));
+
+        loop {
+            names.extend(
+                page.get_items()
+                    .iter()
+                    .map(|user| user.get_name().to_string()),
+            );
+
+            let Some(cursor) = page.get_next_cursor() else {
+                break;
+            };
+
+            page = controller
+                .list_users(request.clone().with_cursor(cursor))
+                .await
+                .expect("Cant list users");
+        }
+
+        assert_eq!(names, vec!["Kirill", "Daria", "Boris", "Andrey"]);
+
+        let page = controller
+            .list_users(request.clone())
+            .await
+            .expect("Cant list users");
+
+        let cursor = page.get_next_cursor().expect("Page has no cursor");
+
+        controller
+            .remove_user("Daria")
+            .await
+            .expect("Cant remove user");
+
+        let next = controller
+            .list_users(request.clone().with_cursor(cursor))
+            .await
+            .expect("Cant list users after the cursor item was removed");
+
+        let names: Vec<&str> = next.get_items().iter().map(User::get_name).collect();
+
+        assert_eq!(names, vec!["Boris", "Andrey"]);
+
+        let result = controller
+            .list_users(
+                PageRequest::new(2)
+                    .with_sort(PageSort::ascending("name"))
+                    .with_cursor(cursor),
+            )
+            .await;
+
+        assert!(result.is_err());
+
+        let page = controller
+            .list_users(PageRequest::new(2).with_offset(2))
+            .await
+            .expect("Cant list users");
+
+        let names: Vec<&str> = page.get_items().iter().map(User::get_name).collect();
+
+        assert_eq!(names, vec!["Kirill"]);
+        assert_eq!(page.get_next_cursor(), None);
+
+        let result = controller
+            .list_users(PageRequest::new(2).with_sort(PageSort::ascending("age")))
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_propagate_envelope_to_nested_messages() {
+        let envelopes = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let di = create_di().await.expect("Cant create DI");
+
+        let pipeline = MiddlewareProvider::default().with(Arc::new(EnvelopeMiddleware {
+            envelopes: envelopes.clone(),
+        }));
+
+        let di = di
+            .inject(InjectAdapter {
+                token: MiddlewareProvider::token(),
+                factory: Arc::new(move |_| pipeline.clone()),
+            })
+            .await
+            .expect("Cant inject MIDDLEWARE_PROVIDER");
+
+        let bus = CqrsProvider::get_adapter(&di.get_context())
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let request = MessageEnvelope::new()
+            .with_principal(Principal::new("admin").with_tenant("acme"))
+            .with_header("request-id", "r-1");
+
+        bus.with_envelope(request.clone())
+            .send_command(Box::new(CreateSafeUserCommand::new(
+                "Boris",
+                "boris@mail.domain",
+            )))
+            .await
+            .expect("Cant create user");
+
+        let envelopes = envelopes.lock().unwrap().clone();
+
+        assert_eq!(envelopes.len(), 2);
+
+        let (command_kind, command) = &envelopes[0];
+        let (query_kind, query) = &envelopes[1];
+
+        assert_eq!(*command_kind, MessageKind::Command);
+        assert_eq!(*query_kind, MessageKind::Query);
+
+        assert_eq!(command.get_correlation_id(), request.get_correlation_id());
+        assert_eq!(command.get_causation_id(), Some(request.get_id()));
+
+        assert_eq!(query.get_correlation_id(), request.get_correlation_id());
+        assert_eq!(query.get_causation_id(), Some(command.get_id()));
+
+        let principal = query.get_principal().expect("Query has no principal");
+
+        assert_eq!(principal.get_user_id(), "admin");
+        assert_eq!(principal.get_tenant_id(), Some("acme"));
+        assert_eq!(query.get_header("request-id"), Some("r-1"));
+    }
+
+    #[tokio::test]
+    async fn should_expose_envelope_to_command_and_event_handlers() {
+        let subscriptions = EventSubscriptionsProvider::new()
+            .with_subscriber::<AuditedEvent>(Arc::new(EnvelopeSubscriber));
+
+        let di = ExampleDiBuilder::new()
+            .with_subscriptions(subscriptions)
+            .build()
+            .await
+            .expect("Cant create DI");
+
+        let context = di.get_context();
+
+        let bus = CqrsProvider::get_adapter(&context)
+            .await
+            .expect("Cant resolve CQRS_PROVIDER");
+
+        let command = bus
+            .with_envelope(MessageEnvelope::new().with_header("request-id", "r-2"))
+            .send_command(Box::new(EnvelopeProbeCommand))
+            .await
+            .expect("Cant run command");
+
+        let audit = AuditService::get_adapter(&context)
+            .await
+            .expect("Cant resolve AuditService");
+
+        assert_eq!(
+            audit.get_records().await.expect("Cant get records"),
+            vec![format!(
+                "{} Some(\"{}\") Some(\"r-2\")",
+                command.get_correlation_id(),
+                command.get_id()
+            )]
+        );
+
+        let standalone = bus
+            .send_command(Box::new(EnvelopeProbeCommand))
+            .await
+            .expect("Cant run command");
+
+        assert_eq!(standalone.get_correlation_id(), standalone.get_id());
+        assert_eq!(standalone.get_causation_id(), None);
     }
 }
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+use kti_cqrs_provider_rs::{
+    models::cache_policy::CachePolicy,
+    ports::{cacheable_query_port::CacheableQueryPort, query_message_port::QueryMessagePort},
+};
+
+use crate::queries::get_user_by_name_query::USERS_CACHE_TAG;
+
+pub struct CountUsersQuery;
+
+impl QueryMessagePort for CountUsersQuery {
+    type Output = usize;
+}
+
+impl CacheableQueryPort for CountUsersQuery {
+    fn get_cache_policy(&self) -> CachePolicy {
+        CachePolicy::new("users:count", Duration::from_secs(60)).with_tag(USERS_CACHE_TAG)
+    }
+}
@@ -0,0 +1,24 @@
+use kti_cqrs_provider_rs::{
+    models::{page::Page, page_request::PageRequest},
+    ports::query_message_port::QueryMessagePort,
+};
+
+use crate::services::user_service::User;
+
+pub struct ListUsersQuery {
+    page: PageRequest,
+}
+
+impl ListUsersQuery {
+    pub fn new(page: PageRequest) -> Self {
+        Self { page }
+    }
+
+    pub fn get_page(&self) -> &PageRequest {
+        &self.page
+    }
+}
+
+impl QueryMessagePort for ListUsersQuery {
+    type Output = Page<User>;
+}
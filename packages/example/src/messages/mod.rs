@@ -0,0 +1,7 @@
+pub mod count_users_query;
+pub mod list_users_query;
+pub mod remove_user_command;
+pub mod user_created_event;
+pub mod user_email_changed_event;
+pub mod user_name_changed_event;
+pub mod user_removed_event;
@@ -0,0 +1,46 @@
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::{
+        command_message_port::CommandMessagePort, persistent_command_port::PersistentCommandPort,
+    },
+};
+
+use crate::queries::get_user_by_name_query::USERS_CACHE_TAG;
+
+pub struct RemoveUserCommand {
+    name: String,
+}
+
+impl RemoveUserCommand {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl CommandMessagePort for RemoveUserCommand {
+    type Output = ();
+
+    fn get_invalidated_tags(&self) -> Vec<String> {
+        vec![USERS_CACHE_TAG.to_string()]
+    }
+}
+
+impl PersistentCommandPort for RemoveUserCommand {
+    fn get_name() -> &'static str {
+        "RemoveUser"
+    }
+
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.name.clone())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        Ok(Self::new(payload))
+    }
+}
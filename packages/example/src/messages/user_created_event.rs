@@ -0,0 +1,46 @@
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::{event_message_port::EventMessagePort, outbox_message_port::OutboxMessagePort},
+};
+
+pub struct UserCreatedEvent {
+    name: String,
+    email: String,
+}
+
+impl UserCreatedEvent {
+    pub fn new(name: &str, email: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_email(&self) -> &str {
+        &self.email
+    }
+}
+
+impl EventMessagePort for UserCreatedEvent {}
+
+impl OutboxMessagePort for UserCreatedEvent {
+    fn get_name() -> &'static str {
+        "UserCreated"
+    }
+
+    fn encode(&self) -> Result<String, Error> {
+        Ok(format!("{}\n{}", self.name, self.email))
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        let (name, email) = payload
+            .split_once('\n')
+            .ok_or("Malformed UserCreated payload")?;
+
+        Ok(Self::new(name, email))
+    }
+}
@@ -0,0 +1,46 @@
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::{event_message_port::EventMessagePort, outbox_message_port::OutboxMessagePort},
+};
+
+pub struct UserEmailChangedEvent {
+    name: String,
+    email: String,
+}
+
+impl UserEmailChangedEvent {
+    pub fn new(name: &str, email: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_email(&self) -> &str {
+        &self.email
+    }
+}
+
+impl EventMessagePort for UserEmailChangedEvent {}
+
+impl OutboxMessagePort for UserEmailChangedEvent {
+    fn get_name() -> &'static str {
+        "UserEmailChanged"
+    }
+
+    fn encode(&self) -> Result<String, Error> {
+        Ok(format!("{}\n{}", self.name, self.email))
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        let (name, email) = payload
+            .split_once('\n')
+            .ok_or("Malformed UserEmailChanged payload")?;
+
+        Ok(Self::new(name, email))
+    }
+}
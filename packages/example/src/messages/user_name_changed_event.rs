@@ -0,0 +1,46 @@
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::{event_message_port::EventMessagePort, outbox_message_port::OutboxMessagePort},
+};
+
+pub struct UserNameChangedEvent {
+    current_name: String,
+    new_name: String,
+}
+
+impl UserNameChangedEvent {
+    pub fn new(current_name: &str, new_name: &str) -> Self {
+        Self {
+            current_name: current_name.to_string(),
+            new_name: new_name.to_string(),
+        }
+    }
+
+    pub fn get_current_name(&self) -> &str {
+        &self.current_name
+    }
+
+    pub fn get_new_name(&self) -> &str {
+        &self.new_name
+    }
+}
+
+impl EventMessagePort for UserNameChangedEvent {}
+
+impl OutboxMessagePort for UserNameChangedEvent {
+    fn get_name() -> &'static str {
+        "UserNameChanged"
+    }
+
+    fn encode(&self) -> Result<String, Error> {
+        Ok(format!("{}\n{}", self.current_name, self.new_name))
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        let (current_name, new_name) = payload
+            .split_once('\n')
+            .ok_or("Malformed UserNameChanged payload")?;
+
+        Ok(Self::new(current_name, new_name))
+    }
+}
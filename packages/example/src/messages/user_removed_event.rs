@@ -0,0 +1,36 @@
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::{event_message_port::EventMessagePort, outbox_message_port::OutboxMessagePort},
+};
+
+pub struct UserRemovedEvent {
+    name: String,
+}
+
+impl UserRemovedEvent {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl EventMessagePort for UserRemovedEvent {}
+
+impl OutboxMessagePort for UserRemovedEvent {
+    fn get_name() -> &'static str {
+        "UserRemoved"
+    }
+
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.name.clone())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        Ok(Self::new(payload))
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    models::stored_event::StoredEvent,
+    ports::{outbox_message_port::OutboxMessagePort, projection_port::ProjectionPort},
+};
+
+use crate::{
+    messages::{
+        user_created_event::UserCreatedEvent, user_email_changed_event::UserEmailChangedEvent,
+        user_name_changed_event::UserNameChangedEvent, user_removed_event::UserRemovedEvent,
+    },
+    services::user_service::{User, UserService},
+};
+
+// Keeps the `UserService` read model in line with the user events of the bus.
+pub struct UsersProjection {
+    service: UserService,
+}
+
+impl UsersProjection {
+    pub fn new(service: UserService) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl ProjectionPort for UsersProjection {
+    fn get_name(&self) -> &'static str {
+        "users"
+    }
+
+    async fn apply(&self, event: &StoredEvent, _: Arc<dyn ContextPort>) -> Result<(), Error> {
+        let payload = event.get_payload();
+
+        match event.get_name() {
+            name if name == <UserCreatedEvent as OutboxMessagePort>::get_name() => {
+                let event = UserCreatedEvent::decode(payload)?;
+
+                self.service
+                    .create_user(User::new(event.get_name(), event.get_email()))
+                    .await
+            }
+            name if name == <UserEmailChangedEvent as OutboxMessagePort>::get_name() => {
+                let event = UserEmailChangedEvent::decode(payload)?;
+
+                self.service
+                    .update_user_email(event.get_name(), event.get_email())
+                    .await
+            }
+            name if name == <UserNameChangedEvent as OutboxMessagePort>::get_name() => {
+                let event = UserNameChangedEvent::decode(payload)?;
+
+                self.service
+                    .update_user_name(event.get_current_name(), event.get_new_name())
+                    .await
+            }
+            name if name == <UserRemovedEvent as OutboxMessagePort>::get_name() => {
+                let event = UserRemovedEvent::decode(payload)?;
+
+                self.service.remove_user(event.get_name()).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn reset(&self) -> Result<(), Error> {
+        self.service.clear().await
+    }
+}
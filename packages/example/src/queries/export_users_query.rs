@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::stream;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    ports::stream_query_handler_port::{QueryStream, StreamQueryHandlerPort},
+};
+
+use crate::services::user_service::{User, UserService};
+
+pub struct ExportUsersQuery;
+
+#[async_trait]
+impl StreamQueryHandlerPort for ExportUsersQuery {
+    type Item = User;
+
+    // Reads one user per pulled item instead of copying the whole store.
+    async fn execute(&self, context: Arc<dyn ContextPort>) -> Result<QueryStream<User>, Error> {
+        let service = UserService::get_adapter(&context).await?;
+
+        let users = stream::unfold((service, 0), |(service, index)| async move {
+            match service.get_user_at(index).await {
+                Ok(Some(user)) => Some((Ok(user), (service, index + 1))),
+                Ok(None) => None,
+                Err(error) => Some((Err(error), (service, usize::MAX))),
+            }
+        });
+
+        Ok(Box::pin(users))
+    }
+}
@@ -1,13 +1,16 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_provider_rs::kti_cqrs_rs::{
-    errors::error::Error, ports::handler::query_handler_port::QueryHandlerPort,
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::{errors::error::Error, ports::handler::query_handler_port::QueryHandlerPort},
+    models::cache_policy::CachePolicy,
 };
 
 use crate::services::user_service::{User, UserService};
 
+pub const USERS_CACHE_TAG: &str = "users";
+
 pub struct GetUserByNameQuery {
     name: String,
 }
@@ -18,6 +21,11 @@ impl GetUserByNameQuery {
             name: name.to_string(),
         }
     }
+
+    pub fn get_cache_policy(&self) -> CachePolicy {
+        CachePolicy::new(format!("user:{}", self.name), Duration::from_secs(60))
+            .with_tag(USERS_CACHE_TAG)
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::{errors::error::Error, ports::handler::query_handler_port::QueryHandlerPort},
+    provider::cqrs_provider::CqrsProvider,
+};
+use tokio::task::JoinSet;
+
+use crate::{handlers::users_by_name_loader::UsersByNameLoader, services::user_service::User};
+
+pub struct GetUsersByNamesQuery {
+    names: Vec<String>,
+}
+
+impl GetUsersByNamesQuery {
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl QueryHandlerPort for GetUsersByNamesQuery {
+    type Context = Arc<dyn ContextPort>;
+    type Output = Vec<Option<User>>;
+
+    // Every name is loaded on its own, the loader folds them into one batch.
+    async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+        let bus = CqrsProvider::get_adapter(&context).await?;
+
+        let mut loads = JoinSet::new();
+
+        for (index, name) in self.names.iter().cloned().enumerate() {
+            let bus = bus.clone();
+
+            loads.spawn(async move { (index, bus.load::<UsersByNameLoader>(name).await) });
+        }
+
+        let mut users = vec![None; self.names.len()];
+
+        while let Some(load) = loads.join_next().await {
+            let (index, user) = load?;
+
+            users[index] = user?;
+        }
+
+        Ok(users)
+    }
+}
@@ -1 +1,3 @@
+pub mod export_users_query;
 pub mod get_user_by_name_query;
+pub mod get_users_by_names_query;
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::adapter_port::AdapterPort;
+use kti_cqrs_provider_rs::kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+#[derive(Clone)]
+pub struct AuditService {
+    records: Arc<RwLock<Vec<String>>>,
+}
+
+#[async_trait]
+impl AdapterPort<AuditService> for AuditService {
+    fn token() -> &'static str {
+        "AUDIT_SERVICE"
+    }
+}
+
+impl AuditService {
+    pub fn new(records: Arc<RwLock<Vec<String>>>) -> Self {
+        Self { records }
+    }
+
+    pub async fn record(&self, record: &str) -> Result<(), Error> {
+        let mut records = self.records.write().await;
+
+        records.push(record.to_string());
+
+        Ok(())
+    }
+
+    pub async fn get_records(&self) -> Result<Vec<String>, Error> {
+        let records = self.records.read().await;
+
+        Ok(records.clone())
+    }
+}
@@ -2,7 +2,11 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use ioc_container_rs::ports::adapter_port::AdapterPort;
-use kti_cqrs_provider_rs::kti_cqrs_rs::errors::error::Error;
+use kti_cqrs_provider_rs::{
+    kti_cqrs_rs::errors::error::Error,
+    models::{page::Page, page_request::PageRequest, sort_value::SortValue},
+    ports::page_item_port::PageItemPort,
+};
 use tokio::sync::RwLock;
 
 #[derive(Clone, Debug)]
@@ -28,6 +32,20 @@ impl User {
     }
 }
 
+impl PageItemPort for User {
+    fn get_cursor(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_sort_value(&self, field: &str) -> Option<SortValue> {
+        match field {
+            "name" => Some(SortValue::Text(self.name.clone())),
+            "email" => Some(SortValue::Text(self.email.clone())),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct UserService {
     users: Arc<RwLock<Vec<User>>>,
@@ -53,9 +71,37 @@ impl UserService {
         Ok(user)
     }
 
+    pub async fn get_user_at(&self, index: usize) -> Result<Option<User>, Error> {
+        let users = self.users.read().await;
+
+        Ok(users.get(index).cloned())
+    }
+
+    pub async fn get_users_by_names(&self, names: &[String]) -> Result<Vec<User>, Error> {
+        let users = self.users.read().await;
+
+        let found = users
+            .iter()
+            .filter(|i| names.contains(&i.name))
+            .cloned()
+            .collect();
+
+        Ok(found)
+    }
+
+    pub async fn list_users(&self, request: &PageRequest) -> Result<Page<User>, Error> {
+        let users = self.users.read().await;
+
+        Page::from_slice(&users, request)
+    }
+
+    // Changes are idempotent, so the projection may apply an event twice or for a user
+    // that was removed in between: creating replaces, missing users are skipped.
     pub async fn create_user(&self, user: User) -> Result<(), Error> {
         let mut users = self.users.write().await;
 
+        users.retain(|i| i.name != user.name);
+
         users.push(user);
 
         Ok(())
@@ -66,7 +112,7 @@ impl UserService {
 
         let index = match users.iter().position(|i| i.name == name) {
             Some(r) => r,
-            None => return Err("Cant find user by name.".into()),
+            None => return Ok(()),
         };
 
         users.remove(index);
@@ -81,15 +127,37 @@ impl UserService {
 
         let index = match users.iter().position(|i| i.name == current_name) {
             Some(r) => r,
-            None => return Err("Cant find user by name.".into()),
+            None => return Ok(()),
         };
 
         let user = users[index].clone();
 
         users.remove(index);
 
+        users.retain(|i| i.name != new_name);
+
         users.push(User::new(new_name, user.get_email()));
 
         Ok(())
     }
+
+    pub async fn remove_user(&self, name: &str) -> Result<(), Error> {
+        let mut users = self.users.write().await;
+
+        users.retain(|i| i.name != name);
+
+        Ok(())
+    }
+
+    pub async fn clear(&self) -> Result<(), Error> {
+        self.users.write().await.clear();
+
+        Ok(())
+    }
+
+    pub async fn count_users(&self) -> Result<usize, Error> {
+        let users = self.users.read().await;
+
+        Ok(users.len())
+    }
 }
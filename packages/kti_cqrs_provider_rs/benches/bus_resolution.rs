@@ -0,0 +1,79 @@
+use std::{
+    hint::black_box,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::{
+    container::di::DI,
+    context::container_context::ContainerContext,
+    ports::{adapter_port::AdapterPort, context_port::ContextPort},
+};
+use kti_cqrs_provider_rs::{
+    di::create_cqrs_provider_di::create_cqrs_provider_di,
+    kti_cqrs_rs::{errors::error::Error, ports::handler::query_handler_port::QueryHandlerPort},
+    provider::cqrs_provider::CqrsProvider,
+};
+
+const ITERATIONS: u32 = 100_000;
+
+struct PingQuery;
+
+#[async_trait]
+impl QueryHandlerPort for PingQuery {
+    type Context = Arc<dyn ContextPort>;
+    type Output = u32;
+
+    async fn execute(&self, _: Self::Context) -> Result<Self::Output, Error> {
+        Ok(1)
+    }
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<24} {:>10.0} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+    );
+}
+
+// A fresh provider has nothing cached, so every query resolves its bus & providers.
+async fn per_call_resolution(context: &Arc<dyn ContextPort>) -> Result<Duration, Error> {
+    let started_at = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        let bus = CqrsProvider::new(context.clone());
+
+        black_box(bus.send_query(Box::new(PingQuery)).await?);
+    }
+
+    Ok(started_at.elapsed())
+}
+
+async fn cached_resolution(context: &Arc<dyn ContextPort>) -> Result<Duration, Error> {
+    let bus = CqrsProvider::get_adapter(context).await?;
+
+    let started_at = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        black_box(bus.send_query(Box::new(PingQuery)).await?);
+    }
+
+    Ok(started_at.elapsed())
+}
+
+fn main() -> Result<(), Error> {
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+
+    runtime.block_on(async {
+        let di = create_cqrs_provider_di(DI::new(Arc::new(ContainerContext::new()))).await?;
+
+        let context = di.get_context();
+
+        report("per call resolution", per_call_resolution(&context).await?);
+        report("cached resolution", cached_resolution(&context).await?);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::{
+    errors::error::Error,
+    ports::handler::{
+        command_handler_port::CommandHandlerPort, event_handler_port::EventHandlerPort,
+    },
+};
+
+// Erases a boxed handler that may already be unsized, so typed helpers accept both.
+pub struct BoxedHandlerAdapter<H: ?Sized> {
+    handler: Box<H>,
+}
+
+impl<H: ?Sized> BoxedHandlerAdapter<H> {
+    pub fn new(handler: Box<H>) -> Self {
+        Self { handler }
+    }
+}
+
+#[async_trait]
+impl<H: CommandHandlerPort + ?Sized> CommandHandlerPort for BoxedHandlerAdapter<H>
+where
+    H::Context: Send + 'static,
+{
+    type Context = H::Context;
+    type Output = H::Output;
+
+    async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+        self.handler.execute(context).await
+    }
+}
+
+#[async_trait]
+impl<H: EventHandlerPort + ?Sized> EventHandlerPort for BoxedHandlerAdapter<H>
+where
+    H::Context: Send + 'static,
+{
+    type Context = H::Context;
+
+    async fn execute(&self, context: Self::Context) -> Result<(), Error> {
+        self.handler.execute(context).await
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    models::{
+        event_data::EventData, event_streams::EventStreams, expected_version::ExpectedVersion,
+        stored_event::StoredEvent,
+    },
+    ports::event_store_port::EventStorePort,
+};
+
+use super::line_codec;
+
+// Append only log, one event per line:
+// `position \t stream_id \t version \t recorded_at_ms \t name \t payload [\t message_id]`.
+// A line torn by a crash is cut off when the log is opened.
+pub struct FileEventStoreAdapter {
+    path: PathBuf,
+    streams: RwLock<EventStreams>,
+    writer: Mutex<()>,
+}
+
+impl FileEventStoreAdapter {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+
+        let mut streams = EventStreams::new();
+
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        let complete = content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |index| index + 1);
+
+        if complete < content.len() {
+            OpenOptions::new()
+                .write(true)
+                .open(&path)?
+                .set_len(complete as u64)?;
+        }
+
+        let content = std::str::from_utf8(&content[..complete])?;
+
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            streams.push(Self::decode_line(line)?);
+        }
+
+        Ok(Self {
+            path,
+            streams: RwLock::new(streams),
+            writer: Mutex::new(()),
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn encode_line(event: &StoredEvent) -> String {
+        let recorded_at = event
+            .get_recorded_at()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        let message_id = event
+            .get_message_id()
+            .map(|message_id| format!("\t{}", message_id))
+            .unwrap_or_default();
+
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}{}\n",
+            event.get_position(),
+            line_codec::escape(event.get_stream_id()),
+            event.get_version(),
+            recorded_at,
+            line_codec::escape(event.get_name()),
+            line_codec::escape(event.get_payload()),
+            message_id
+        )
+    }
+
+    fn decode_line(line: &str) -> Result<StoredEvent, Error> {
+        let mut fields = line.splitn(7, '\t');
+
+        let mut next = || {
+            fields
+                .next()
+                .ok_or_else(|| format!("Malformed event record: {}", line))
+        };
+
+        let position = next()?.parse::<u64>()?;
+        let stream_id = line_codec::unescape(next()?);
+        let version = next()?.parse::<u64>()?;
+        let recorded_at = UNIX_EPOCH + Duration::from_millis(next()?.parse::<u64>()?);
+        let name = line_codec::unescape(next()?);
+        let payload = line_codec::unescape(next()?);
+
+        let data = match fields.next() {
+            Some(message_id) => EventData::new(&name, payload).with_message_id(message_id.parse()?),
+            None => EventData::new(&name, payload),
+        };
+
+        Ok(StoredEvent::new(
+            position,
+            &stream_id,
+            version,
+            data,
+            recorded_at,
+        ))
+    }
+}
+
+#[async_trait]
+impl EventStorePort for FileEventStoreAdapter {
+    async fn append(
+        &self,
+        stream_id: &str,
+        expected: ExpectedVersion,
+        events: Vec<EventData>,
+    ) -> Result<u64, Error> {
+        // Serializes appends, readers only wait for the events to be applied.
+        let _writer = self.writer.lock().await;
+
+        let events = self
+            .streams
+            .read()
+            .await
+            .prepare(stream_id, expected, events)?;
+
+        let content = events
+            .iter()
+            .map(Self::encode_line)
+            .collect::<Vec<_>>()
+            .concat();
+
+        let path = self.path.clone();
+
+        tokio::task::spawn_blocking(move || -> Result<(), Error> {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+
+            Ok(())
+        })
+        .await??;
+
+        let mut streams = self.streams.write().await;
+
+        for event in events {
+            streams.push(event);
+        }
+
+        Ok(streams.get_version(stream_id))
+    }
+
+    async fn read_stream(
+        &self,
+        stream_id: &str,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, Error> {
+        Ok(self
+            .streams
+            .read()
+            .await
+            .read_stream(stream_id, after_version))
+    }
+
+    async fn read_all(&self, after_position: u64, limit: usize) -> Result<Vec<StoredEvent>, Error> {
+        Ok(self.streams.read().await.read_all(after_position, limit))
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::Mutex;
+
+use crate::{
+    models::{message_envelope::MessageEnvelope, outbox_record::OutboxRecord},
+    ports::outbox_store_port::OutboxStorePort,
+};
+
+use super::line_codec;
+
+struct FileOutboxState {
+    last_id: u64,
+    records: BTreeMap<u64, OutboxRecord>,
+}
+
+// One record per line: `id \t created_at_ms \t name \t payload [\t envelope]`, the
+// escaped envelope is left out for records without one. Removals append a `-id`
+// tombstone, which is compacted away on open. A line torn by a crash is cut off
+// when the file is opened.
+pub struct FileOutboxStoreAdapter {
+    path: PathBuf,
+    state: Mutex<FileOutboxState>,
+}
+
+impl FileOutboxStoreAdapter {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        let complete = content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |index| index + 1);
+
+        if complete < content.len() {
+            OpenOptions::new()
+                .write(true)
+                .open(&path)?
+                .set_len(complete as u64)?;
+        }
+
+        let content = std::str::from_utf8(&content[..complete])?;
+
+        let mut records = BTreeMap::new();
+        let mut last_id = 0;
+        let mut removed = false;
+
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            if let Some(id) = line.strip_prefix('-') {
+                records.remove(&id.parse::<u64>()?);
+
+                removed = true;
+
+                continue;
+            }
+
+            let record = Self::decode_line(line)?;
+
+            last_id = last_id.max(record.get_id());
+
+            records.insert(record.get_id(), record);
+        }
+
+        if removed {
+            Self::write_all(&path, &records)?;
+        }
+
+        Ok(Self {
+            path,
+            state: Mutex::new(FileOutboxState { last_id, records }),
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_all(path: &Path, records: &BTreeMap<u64, OutboxRecord>) -> Result<(), Error> {
+        let content = records
+            .values()
+            .map(Self::encode_line)
+            .collect::<Vec<_>>()
+            .concat();
+
+        let temporary = path.with_extension("tmp");
+
+        let mut file = fs::File::create(&temporary)?;
+
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+
+        fs::rename(&temporary, path)?;
+
+        Ok(())
+    }
+
+    // File writes run on the blocking pool, the state lock keeps lines in id order. A failed
+    // write is cut off again, so lines are stored all at once or not at all.
+    async fn append_line(&self, line: String) -> Result<(), Error> {
+        let path = self.path.clone();
+
+        tokio::task::spawn_blocking(move || -> Result<(), Error> {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+            let length = file.metadata()?.len();
+
+            let written = file
+                .write_all(line.as_bytes())
+                .and_then(|_| file.sync_all());
+
+            if let Err(error) = written {
+                file.set_len(length)?;
+
+                return Err(error.into());
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    fn encode_line(record: &OutboxRecord) -> String {
+        let created_at = record
+            .get_created_at()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        let envelope = record
+            .get_envelope()
+            .map(|envelope| format!("\t{}", line_codec::escape(&envelope.encode())))
+            .unwrap_or_default();
+
+        format!(
+            "{}\t{}\t{}\t{}{}\n",
+            record.get_id(),
+            created_at,
+            line_codec::escape(record.get_name()),
+            line_codec::escape(record.get_payload()),
+            envelope
+        )
+    }
+
+    fn decode_line(line: &str) -> Result<OutboxRecord, Error> {
+        let mut fields = line.splitn(5, '\t');
+
+        let mut next = || {
+            fields
+                .next()
+                .ok_or_else(|| format!("Malformed outbox record: {}", line))
+        };
+
+        let id = next()?.parse::<u64>()?;
+        let created_at = UNIX_EPOCH + Duration::from_millis(next()?.parse::<u64>()?);
+        let name = line_codec::unescape(next()?);
+        let payload = line_codec::unescape(next()?);
+
+        let envelope = fields
+            .next()
+            .map(|envelope| MessageEnvelope::decode(&line_codec::unescape(envelope)))
+            .transpose()?;
+
+        Ok(OutboxRecord::new(id, &name, payload, created_at).with_envelope(envelope))
+    }
+}
+
+#[async_trait]
+impl OutboxStorePort for FileOutboxStoreAdapter {
+    async fn append(
+        &self,
+        name: &str,
+        payload: String,
+        envelope: Option<MessageEnvelope>,
+    ) -> Result<OutboxRecord, Error> {
+        let mut state = self.state.lock().await;
+
+        let record = OutboxRecord::new(state.last_id + 1, name, payload, SystemTime::now())
+            .with_envelope(envelope);
+
+        self.append_line(Self::encode_line(&record)).await?;
+
+        state.last_id = record.get_id();
+
+        state.records.insert(record.get_id(), record.clone());
+
+        Ok(record)
+    }
+
+    async fn append_all(
+        &self,
+        records: Vec<(&str, String, Option<MessageEnvelope>)>,
+    ) -> Result<Vec<OutboxRecord>, Error> {
+        let mut state = self.state.lock().await;
+
+        let records = records
+            .into_iter()
+            .zip(state.last_id + 1..)
+            .map(|((name, payload, envelope), id)| {
+                OutboxRecord::new(id, name, payload, SystemTime::now()).with_envelope(envelope)
+            })
+            .collect::<Vec<_>>();
+
+        let lines = records.iter().map(Self::encode_line).collect::<Vec<_>>();
+
+        self.append_line(lines.concat()).await?;
+
+        for record in &records {
+            state.last_id = record.get_id();
+
+            state.records.insert(record.get_id(), record.clone());
+        }
+
+        Ok(records)
+    }
+
+    async fn list(&self) -> Result<Vec<OutboxRecord>, Error> {
+        let state = self.state.lock().await;
+
+        Ok(state.records.values().cloned().collect())
+    }
+
+    async fn remove(&self, id: u64) -> Result<bool, Error> {
+        let mut state = self.state.lock().await;
+
+        if !state.records.contains_key(&id) {
+            return Ok(false);
+        }
+
+        self.append_line(format!("-{}\n", id)).await?;
+
+        state.records.remove(&id);
+
+        Ok(true)
+    }
+}
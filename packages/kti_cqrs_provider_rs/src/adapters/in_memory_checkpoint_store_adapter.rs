@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::ports::checkpoint_store_port::CheckpointStorePort;
+
+#[derive(Default)]
+pub struct InMemoryCheckpointStoreAdapter {
+    positions: RwLock<HashMap<String, u64>>,
+}
+
+impl InMemoryCheckpointStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStorePort for InMemoryCheckpointStoreAdapter {
+    async fn load(&self, projection: &str) -> Result<u64, Error> {
+        let positions = self.positions.read().await;
+
+        Ok(positions.get(projection).copied().unwrap_or(0))
+    }
+
+    async fn save(&self, projection: &str, position: u64) -> Result<(), Error> {
+        let mut positions = self.positions.write().await;
+
+        positions.insert(projection.to_string(), position);
+
+        Ok(())
+    }
+}
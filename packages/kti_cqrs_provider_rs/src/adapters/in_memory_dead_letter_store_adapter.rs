@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{dead_letter::DeadLetter, message_id::MessageId},
+    ports::dead_letter_store_port::DeadLetterStorePort,
+};
+
+pub struct InMemoryDeadLetterStoreAdapter {
+    letters: RwLock<BTreeMap<MessageId, DeadLetter>>,
+    capacity: usize,
+}
+
+impl InMemoryDeadLetterStoreAdapter {
+    // A full store rejects new letters, their failures are still reported.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            letters: RwLock::new(BTreeMap::new()),
+            capacity,
+        }
+    }
+}
+
+#[async_trait]
+impl DeadLetterStorePort for InMemoryDeadLetterStoreAdapter {
+    async fn push(&self, letter: DeadLetter) -> Result<(), Error> {
+        let mut letters = self.letters.write().await;
+
+        if letters.len() >= self.capacity && !letters.contains_key(&letter.get_id()) {
+            return Err(format!("Dead letter store is full at {} letters", self.capacity).into());
+        }
+
+        letters.insert(letter.get_id(), letter);
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error> {
+        let letters = self.letters.read().await;
+
+        Ok(letters.values().cloned().collect())
+    }
+
+    async fn get(&self, id: MessageId) -> Result<Option<DeadLetter>, Error> {
+        let letters = self.letters.read().await;
+
+        Ok(letters.get(&id).cloned())
+    }
+
+    async fn remove(&self, id: MessageId) -> Result<Option<DeadLetter>, Error> {
+        let mut letters = self.letters.write().await;
+
+        Ok(letters.remove(&id))
+    }
+
+    async fn purge(&self) -> Result<usize, Error> {
+        let mut letters = self.letters.write().await;
+
+        let purged = letters.len();
+
+        letters.clear();
+
+        Ok(purged)
+    }
+}
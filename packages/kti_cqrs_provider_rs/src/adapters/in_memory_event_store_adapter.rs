@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{
+        event_data::EventData, event_streams::EventStreams, expected_version::ExpectedVersion,
+        stored_event::StoredEvent,
+    },
+    ports::event_store_port::EventStorePort,
+};
+
+#[derive(Default)]
+pub struct InMemoryEventStoreAdapter {
+    streams: RwLock<EventStreams>,
+}
+
+impl InMemoryEventStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EventStorePort for InMemoryEventStoreAdapter {
+    async fn append(
+        &self,
+        stream_id: &str,
+        expected: ExpectedVersion,
+        events: Vec<EventData>,
+    ) -> Result<u64, Error> {
+        let mut streams = self.streams.write().await;
+
+        for event in streams.prepare(stream_id, expected, events)? {
+            streams.push(event);
+        }
+
+        Ok(streams.get_version(stream_id))
+    }
+
+    async fn read_stream(
+        &self,
+        stream_id: &str,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, Error> {
+        Ok(self
+            .streams
+            .read()
+            .await
+            .read_stream(stream_id, after_version))
+    }
+
+    async fn read_all(&self, after_position: u64, limit: usize) -> Result<Vec<StoredEvent>, Error> {
+        Ok(self.streams.read().await.read_all(after_position, limit))
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::Mutex;
+
+use crate::{
+    models::idempotency_status::IdempotencyStatus,
+    ports::idempotency_store_port::IdempotencyStorePort,
+};
+
+struct IdempotencyEntry {
+    value: Option<String>,
+    owner: String,
+    expires_at: Instant,
+}
+
+impl IdempotencyEntry {
+    fn is_held_by(&self, owner: &str) -> bool {
+        self.value.is_none() && self.owner == owner
+    }
+}
+
+#[derive(Default)]
+struct IdempotencyEntries {
+    entries: HashMap<String, IdempotencyEntry>,
+    swept: usize,
+}
+
+impl IdempotencyEntries {
+    fn sweep(&mut self, now: Instant) {
+        if self.entries.len() < self.swept.max(32) * 2 {
+            return;
+        }
+
+        self.entries.retain(|_, entry| entry.expires_at > now);
+
+        self.swept = self.entries.len();
+    }
+}
+
+// Expired keys are replaced when they are touched and swept all at once whenever the map
+// doubled since the last sweep, so `begin` doesn't scan every key.
+#[derive(Default)]
+pub struct InMemoryIdempotencyStoreAdapter {
+    entries: Mutex<IdempotencyEntries>,
+}
+
+impl InMemoryIdempotencyStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn len(&self) -> usize {
+        let now = Instant::now();
+
+        let entries = self.entries.lock().await;
+
+        entries
+            .entries
+            .values()
+            .filter(|entry| entry.expires_at > now)
+            .count()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
+
+#[async_trait]
+impl IdempotencyStorePort for InMemoryIdempotencyStoreAdapter {
+    async fn begin(
+        &self,
+        key: &str,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<IdempotencyStatus, Error> {
+        let now = Instant::now();
+
+        let mut entries = self.entries.lock().await;
+
+        if let Some(entry) = entries
+            .entries
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+        {
+            return Ok(match &entry.value {
+                Some(value) => IdempotencyStatus::Completed(value.clone()),
+                None => IdempotencyStatus::InProgress,
+            });
+        }
+
+        entries.sweep(now);
+
+        entries.entries.insert(
+            key.to_string(),
+            IdempotencyEntry {
+                value: None,
+                owner: owner.to_string(),
+                expires_at: now + lease,
+            },
+        );
+
+        Ok(IdempotencyStatus::Started)
+    }
+
+    async fn renew(&self, key: &str, owner: &str, lease: Duration) -> Result<bool, Error> {
+        let mut entries = self.entries.lock().await;
+
+        let Some(entry) = entries.entries.get_mut(key) else {
+            return Ok(false);
+        };
+
+        if !entry.is_held_by(owner) {
+            return Ok(false);
+        }
+
+        entry.expires_at = Instant::now() + lease;
+
+        Ok(true)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        owner: &str,
+        value: String,
+        ttl: Duration,
+    ) -> Result<bool, Error> {
+        let mut entries = self.entries.lock().await;
+
+        let Some(entry) = entries.entries.get_mut(key) else {
+            return Ok(false);
+        };
+
+        if !entry.is_held_by(owner) {
+            return Ok(false);
+        }
+
+        entry.value = Some(value);
+        entry.expires_at = Instant::now() + ttl;
+
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool, Error> {
+        let mut entries = self.entries.lock().await;
+
+        if !entries
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.is_held_by(owner))
+        {
+            return Ok(false);
+        }
+
+        entries.entries.remove(key);
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::{message_envelope::MessageEnvelope, outbox_record::OutboxRecord},
+    ports::outbox_store_port::OutboxStorePort,
+};
+
+#[derive(Default)]
+pub struct InMemoryOutboxStoreAdapter {
+    last_id: AtomicU64,
+    records: RwLock<BTreeMap<u64, OutboxRecord>>,
+}
+
+impl InMemoryOutboxStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OutboxStorePort for InMemoryOutboxStoreAdapter {
+    async fn append(
+        &self,
+        name: &str,
+        payload: String,
+        envelope: Option<MessageEnvelope>,
+    ) -> Result<OutboxRecord, Error> {
+        let id = self.last_id.fetch_add(1, Ordering::AcqRel) + 1;
+
+        let record =
+            OutboxRecord::new(id, name, payload, SystemTime::now()).with_envelope(envelope);
+
+        self.records.write().await.insert(id, record.clone());
+
+        Ok(record)
+    }
+
+    async fn append_all(
+        &self,
+        records: Vec<(&str, String, Option<MessageEnvelope>)>,
+    ) -> Result<Vec<OutboxRecord>, Error> {
+        let mut stored = self.records.write().await;
+
+        let records = records
+            .into_iter()
+            .map(|(name, payload, envelope)| {
+                let id = self.last_id.fetch_add(1, Ordering::AcqRel) + 1;
+
+                OutboxRecord::new(id, name, payload, SystemTime::now()).with_envelope(envelope)
+            })
+            .collect::<Vec<_>>();
+
+        for record in &records {
+            stored.insert(record.get_id(), record.clone());
+        }
+
+        Ok(records)
+    }
+
+    async fn list(&self) -> Result<Vec<OutboxRecord>, Error> {
+        let records = self.records.read().await;
+
+        Ok(records.values().cloned().collect())
+    }
+
+    async fn remove(&self, id: u64) -> Result<bool, Error> {
+        let mut records = self.records.write().await;
+
+        Ok(records.remove(&id).is_some())
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::ports::query_cache_port::{CachedValue, QueryCachePort};
+
+struct CacheEntry {
+    value: CachedValue,
+    expires_at: Instant,
+    tags: Vec<String>,
+    used_at: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    recent: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: &str) {
+        self.clock += 1;
+
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recent.remove(&entry.used_at);
+
+            entry.used_at = self.clock;
+
+            self.recent.insert(self.clock, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.remove(key)?;
+
+        self.recent.remove(&entry.used_at);
+
+        Some(entry)
+    }
+}
+
+pub struct InMemoryQueryCacheAdapter {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl Default for InMemoryQueryCacheAdapter {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl InMemoryQueryCacheAdapter {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_state().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl QueryCachePort for InMemoryQueryCacheAdapter {
+    fn get(&self, key: &str) -> Option<CachedValue> {
+        let mut state = self.lock_state();
+
+        let expired = state.entries.get(key)?.expires_at <= Instant::now();
+
+        if expired {
+            state.remove(key);
+
+            return None;
+        }
+
+        state.touch(key);
+
+        state.entries.get(key).map(|entry| entry.value.clone())
+    }
+
+    fn insert(&self, key: &str, value: CachedValue, ttl: Duration, tags: &[String]) {
+        let mut state = self.lock_state();
+
+        state.remove(key);
+
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.recent.pop_first() else {
+                break;
+            };
+
+            state.entries.remove(&oldest);
+        }
+
+        state.entries.insert(
+            key.to_string(),
+            CacheEntry {
+                value,
+                expires_at: Instant::now() + ttl,
+                tags: tags.to_vec(),
+                used_at: 0,
+            },
+        );
+
+        state.touch(key);
+    }
+
+    fn invalidate(&self, tags: &[String]) -> usize {
+        let mut state = self.lock_state();
+
+        let keys = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.tags.iter().any(|tag| tags.contains(tag)))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        for key in &keys {
+            state.remove(key);
+        }
+
+        keys.len()
+    }
+
+    fn clear(&self) {
+        let mut state = self.lock_state();
+
+        state.entries.clear();
+        state.recent.clear();
+    }
+}
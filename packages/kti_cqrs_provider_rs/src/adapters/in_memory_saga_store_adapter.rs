@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::{models::saga_record::SagaRecord, ports::saga_store_port::SagaStorePort};
+
+#[derive(Default)]
+pub struct InMemorySagaStoreAdapter {
+    records: RwLock<BTreeMap<(String, String), SagaRecord>>,
+}
+
+impl InMemorySagaStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SagaStorePort for InMemorySagaStoreAdapter {
+    async fn load(&self, saga: &str, correlation_id: &str) -> Result<Option<SagaRecord>, Error> {
+        let records = self.records.read().await;
+
+        Ok(records
+            .get(&(saga.to_string(), correlation_id.to_string()))
+            .cloned())
+    }
+
+    async fn save(&self, record: SagaRecord) -> Result<(), Error> {
+        let key = (
+            record.get_saga().to_string(),
+            record.get_correlation_id().to_string(),
+        );
+
+        self.records.write().await.insert(key, record);
+
+        Ok(())
+    }
+
+    async fn list(&self, saga: &str) -> Result<Vec<SagaRecord>, Error> {
+        let records = self.records.read().await;
+
+        Ok(records
+            .values()
+            .filter(|record| record.get_saga() == saga)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::scheduled_record::ScheduledRecord, ports::schedule_store_port::ScheduleStorePort,
+};
+
+#[derive(Default)]
+pub struct InMemoryScheduleStoreAdapter {
+    records: RwLock<BTreeMap<u64, ScheduledRecord>>,
+}
+
+impl InMemoryScheduleStoreAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScheduleStorePort for InMemoryScheduleStoreAdapter {
+    async fn save(&self, record: ScheduledRecord) -> Result<(), Error> {
+        self.records.write().await.insert(record.get_id(), record);
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<ScheduledRecord>, Error> {
+        let records = self.records.read().await;
+
+        Ok(records.values().cloned().collect())
+    }
+
+    async fn remove(&self, id: u64) -> Result<bool, Error> {
+        let mut records = self.records.write().await;
+
+        Ok(records.remove(&id).is_some())
+    }
+}
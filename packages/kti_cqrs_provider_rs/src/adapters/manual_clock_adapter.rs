@@ -0,0 +1,52 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tokio::sync::Notify;
+
+use crate::ports::clock_port::ClockPort;
+
+pub struct ManualClockAdapter {
+    now: Mutex<SystemTime>,
+    changed: Notify,
+}
+
+impl ManualClockAdapter {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+            changed: Notify::new(),
+        }
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+
+        self.changed.notify_waiters();
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.set(self.now() + duration);
+    }
+}
+
+#[async_trait]
+impl ClockPort for ManualClockAdapter {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn sleep_until(&self, at: SystemTime) {
+        loop {
+            let changed = self.changed.notified();
+
+            if self.now() >= at {
+                return;
+            }
+
+            changed.await;
+        }
+    }
+}
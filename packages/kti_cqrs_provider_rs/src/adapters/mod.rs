@@ -0,0 +1,19 @@
+pub mod boxed_handler_adapter;
+pub mod file_event_store_adapter;
+pub mod file_outbox_store_adapter;
+pub mod in_memory_checkpoint_store_adapter;
+pub mod in_memory_dead_letter_store_adapter;
+pub mod in_memory_event_store_adapter;
+pub mod in_memory_idempotency_store_adapter;
+pub mod in_memory_outbox_store_adapter;
+pub mod in_memory_query_cache_adapter;
+pub mod in_memory_saga_store_adapter;
+pub mod in_memory_schedule_store_adapter;
+pub(crate) mod line_codec;
+pub mod manual_clock_adapter;
+pub mod projection_event_adapter;
+pub mod projection_subscriber_adapter;
+pub mod registered_command_adapter;
+pub mod registered_query_adapter;
+pub mod saga_subscriber_adapter;
+pub mod system_clock_adapter;
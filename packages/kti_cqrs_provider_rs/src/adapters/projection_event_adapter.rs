@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::{errors::error::Error, ports::handler::event_handler_port::EventHandlerPort};
+
+use crate::{
+    ports::event_store_port::EventStorePort, provider::projection_provider::ProjectionProvider,
+};
+
+pub struct ProjectionEventAdapter {
+    projections: ProjectionProvider,
+    store: Arc<dyn EventStorePort>,
+}
+
+impl ProjectionEventAdapter {
+    pub fn new(projections: ProjectionProvider, store: Arc<dyn EventStorePort>) -> Self {
+        Self { projections, store }
+    }
+}
+
+#[async_trait]
+impl EventHandlerPort for ProjectionEventAdapter {
+    type Context = Arc<dyn ContextPort>;
+
+    async fn execute(&self, context: Self::Context) -> Result<(), Error> {
+        self.projections
+            .catch_up(self.store.as_ref(), context)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    ports::{event_subscriber_port::EventSubscriberPort, outbox_message_port::OutboxMessagePort},
+    provider::cqrs_provider::CqrsProvider,
+};
+
+pub struct ProjectionSubscriberAdapter<E: OutboxMessagePort> {
+    event: PhantomData<fn(&E)>,
+}
+
+impl<E: OutboxMessagePort> Default for ProjectionSubscriberAdapter<E> {
+    fn default() -> Self {
+        Self { event: PhantomData }
+    }
+}
+
+#[async_trait]
+impl<E: OutboxMessagePort> EventSubscriberPort<E> for ProjectionSubscriberAdapter<E> {
+    fn get_name(&self) -> &'static str {
+        "projections"
+    }
+
+    async fn handle(&self, event: &E, context: Arc<dyn ContextPort>) -> Result<(), Error> {
+        let provider = CqrsProvider::get_adapter(&context).await?;
+
+        provider.project_event(event).await?;
+
+        Ok(())
+    }
+}
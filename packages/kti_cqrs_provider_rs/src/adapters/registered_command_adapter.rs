@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::{errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort};
+
+use crate::ports::{
+    command_message_handler_port::CommandMessageHandlerPort,
+    command_message_port::CommandMessagePort,
+};
+
+pub struct RegisteredCommandAdapter<M: CommandMessagePort> {
+    command: M,
+    handler: Arc<dyn CommandMessageHandlerPort<M>>,
+}
+
+impl<M: CommandMessagePort> RegisteredCommandAdapter<M> {
+    pub fn new(command: M, handler: Arc<dyn CommandMessageHandlerPort<M>>) -> Self {
+        Self { command, handler }
+    }
+}
+
+#[async_trait]
+impl<M: CommandMessagePort> CommandHandlerPort for RegisteredCommandAdapter<M> {
+    type Context = Arc<dyn ContextPort>;
+    type Output = M::Output;
+
+    async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+        self.handler.handle(&self.command, context).await
+    }
+}
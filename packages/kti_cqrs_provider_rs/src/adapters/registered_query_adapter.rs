@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::{errors::error::Error, ports::handler::query_handler_port::QueryHandlerPort};
+
+use crate::ports::{
+    query_message_handler_port::QueryMessageHandlerPort, query_message_port::QueryMessagePort,
+};
+
+pub struct RegisteredQueryAdapter<M: QueryMessagePort> {
+    query: M,
+    handler: Arc<dyn QueryMessageHandlerPort<M>>,
+}
+
+impl<M: QueryMessagePort> RegisteredQueryAdapter<M> {
+    pub fn new(query: M, handler: Arc<dyn QueryMessageHandlerPort<M>>) -> Self {
+        Self { query, handler }
+    }
+}
+
+#[async_trait]
+impl<M: QueryMessagePort> QueryHandlerPort for RegisteredQueryAdapter<M> {
+    type Context = Arc<dyn ContextPort>;
+    type Output = M::Output;
+
+    async fn execute(&self, context: Self::Context) -> Result<Self::Output, Error> {
+        self.handler.handle(&self.query, context).await
+    }
+}
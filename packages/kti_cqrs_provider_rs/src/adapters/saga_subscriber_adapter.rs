@@ -0,0 +1,180 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    models::{saga_record::SagaRecord, saga_status::SagaStatus},
+    ports::{
+        event_message_port::EventMessagePort, event_subscriber_port::EventSubscriberPort,
+        saga_handler_port::SagaHandlerPort, saga_store_port::SagaStorePort,
+    },
+    provider::{cqrs_provider::CqrsProvider, saga_provider::SagaProvider},
+};
+
+pub struct SagaSubscriberAdapter<E: EventMessagePort, S: SagaHandlerPort<E>> {
+    saga: Arc<S>,
+    event: PhantomData<fn(&E)>,
+}
+
+impl<E: EventMessagePort, S: SagaHandlerPort<E>> SagaSubscriberAdapter<E, S> {
+    pub fn new(saga: Arc<S>) -> Self {
+        Self {
+            saga,
+            event: PhantomData,
+        }
+    }
+
+    // Called with the saga lock held.
+    async fn compensate(
+        &self,
+        store: &dyn SagaStorePort,
+        provider: &CqrsProvider,
+        correlation_id: &str,
+        state: &S::State,
+        error: String,
+    ) -> Result<(), Error> {
+        let name = self.saga.get_name();
+
+        let record = SagaRecord::new(
+            name,
+            correlation_id,
+            SagaStatus::Compensating,
+            S::encode(state)?,
+        )
+        .with_error(error.clone());
+
+        store.save(record).await?;
+
+        // A failed compensation stays `Compensating`, so a retried event runs it again.
+        self.saga
+            .compensate(correlation_id, state, provider)
+            .await?;
+
+        let record = SagaRecord::new(
+            name,
+            correlation_id,
+            SagaStatus::Compensated,
+            S::encode(state)?,
+        )
+        .with_error(error);
+
+        store.save(record).await
+    }
+}
+
+#[async_trait]
+impl<E: EventMessagePort, S: SagaHandlerPort<E>> EventSubscriberPort<E>
+    for SagaSubscriberAdapter<E, S>
+{
+    fn get_name(&self) -> &'static str {
+        self.saga.get_name()
+    }
+
+    async fn handle(&self, event: &E, context: Arc<dyn ContextPort>) -> Result<(), Error> {
+        let sagas = SagaProvider::resolve(&context).await?;
+
+        let provider = CqrsProvider::get_adapter(&context).await?;
+
+        let name = self.saga.get_name();
+
+        let correlation_id = self.saga.get_correlation_id(event);
+
+        let store = sagas.get_store();
+
+        let (state, status) = {
+            let _guard = sagas.lock(name, &correlation_id).await;
+
+            let record = store.load(name, &correlation_id).await?;
+
+            let mut state = match &record {
+                Some(record) => S::decode(record.get_state())?,
+                None => S::State::default(),
+            };
+
+            if let Some(record) = &record {
+                if record.get_status().is_finished() {
+                    return Ok(());
+                }
+
+                if record.get_status() == SagaStatus::Compensating {
+                    let error = record.get_error().unwrap_or_default().to_string();
+
+                    return self
+                        .compensate(store.as_ref(), &provider, &correlation_id, &state, error)
+                        .await;
+                }
+            }
+
+            let status = match self.saga.apply(event, &mut state).await {
+                Ok(status) => status,
+                Err(error) => {
+                    return self
+                        .compensate(
+                            store.as_ref(),
+                            &provider,
+                            &correlation_id,
+                            &state,
+                            error.to_string(),
+                        )
+                        .await;
+                }
+            };
+
+            // The step stays `Running` until its follow-ups went out.
+            let record = SagaRecord::new(
+                name,
+                &correlation_id,
+                SagaStatus::Running,
+                S::encode(&state)?,
+            );
+
+            store.save(record).await?;
+
+            (state, status)
+        };
+
+        let result = self.saga.dispatch(event, &state, &provider).await;
+
+        if result.is_ok() && status == SagaStatus::Running {
+            return Ok(());
+        }
+
+        let _guard = sagas.lock(name, &correlation_id).await;
+
+        // Events routed back to the saga may have moved it on in the meantime.
+        let Some(record) = store.load(name, &correlation_id).await? else {
+            return result;
+        };
+
+        if record.get_status() != SagaStatus::Running {
+            return result;
+        }
+
+        match result {
+            Ok(()) => {
+                let record = SagaRecord::new(
+                    name,
+                    &correlation_id,
+                    status,
+                    record.get_state().to_string(),
+                );
+
+                store.save(record).await
+            }
+            Err(error) => {
+                let state = S::decode(record.get_state())?;
+
+                self.compensate(
+                    store.as_ref(),
+                    &provider,
+                    &correlation_id,
+                    &state,
+                    error.to_string(),
+                )
+                .await
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+use crate::ports::clock_port::ClockPort;
+
+#[derive(Clone, Copy, Default)]
+pub struct SystemClockAdapter;
+
+impl SystemClockAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ClockPort for SystemClockAdapter {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    async fn sleep_until(&self, at: SystemTime) {
+        if let Ok(delay) = at.duration_since(self.now()) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
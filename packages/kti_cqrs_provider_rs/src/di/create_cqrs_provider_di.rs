@@ -1,42 +1,148 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use ioc_container_rs::{
-    container::di::{InjectAdapter, DI},
+    container::di::{DI, InjectAdapter},
     ports::adapter_port::AdapterPort,
 };
 use kti_cqrs_rs::errors::error::Error;
 
-use crate::provider::{
-    command_bus_provider::CommandBusProvider, cqrs_provider::CqrsProvider,
-    event_bus_provider::EventBusProvider, query_bus_provider::QueryBusProvider,
+use crate::{
+    models::event_tracker::EventTracker,
+    provider::{
+        command_bus_provider::CommandBusProvider, cqrs_provider::CqrsProvider,
+        event_bus_limits_provider::EventBusLimitsProvider, event_bus_provider::EventBusProvider,
+        idempotency_provider::IdempotencyProvider, query_bus_provider::QueryBusProvider,
+        query_cache_provider::QueryCacheProvider, saga_provider::SagaProvider,
+        scheduler_provider::SchedulerProvider, single_flight_provider::SingleFlightProvider,
+    },
 };
 
 pub async fn create_cqrs_provider_di(di: DI) -> Result<DI, Error> {
     let di = di
         .inject(InjectAdapter {
             token: QueryBusProvider::token(),
-            factory: Arc::new(|context| QueryBusProvider::new(context)),
+            factory: Arc::new(QueryBusProvider::new),
         })
         .await?;
 
     let di = di
         .inject(InjectAdapter {
             token: CommandBusProvider::token(),
-            factory: Arc::new(|context| CommandBusProvider::new(context)),
+            factory: Arc::new(CommandBusProvider::new),
         })
         .await?;
 
+    let tracker = if di
+        .get_context()
+        .has_provider(EventBusLimitsProvider::token())
+        .await
+    {
+        let limits = EventBusLimitsProvider::get_adapter(&di.get_context()).await?;
+
+        EventTracker::bounded(
+            limits.get_max_workers(),
+            limits.get_queue_capacity(),
+            limits.get_overflow(),
+        )
+    } else {
+        EventTracker::new()
+    };
+
+    let tracker = Arc::new(tracker);
+
     let di = di
         .inject(InjectAdapter {
             token: EventBusProvider::token(),
-            factory: Arc::new(|context| EventBusProvider::new(context)),
+            factory: Arc::new(move |context| EventBusProvider::new(context, tracker.clone())),
         })
         .await?;
 
+    let di = if di
+        .get_context()
+        .has_provider(QueryCacheProvider::token())
+        .await
+    {
+        di
+    } else {
+        let cache = QueryCacheProvider::default();
+
+        di.inject(InjectAdapter {
+            token: QueryCacheProvider::token(),
+            factory: Arc::new(move |_| cache.clone()),
+        })
+        .await?
+    };
+
+    let di = if di.get_context().has_provider(SagaProvider::token()).await {
+        di
+    } else {
+        let sagas = SagaProvider::default();
+
+        di.inject(InjectAdapter {
+            token: SagaProvider::token(),
+            factory: Arc::new(move |_| sagas.clone()),
+        })
+        .await?
+    };
+
+    let di = if di
+        .get_context()
+        .has_provider(SchedulerProvider::token())
+        .await
+    {
+        di
+    } else {
+        let scheduler = SchedulerProvider::default();
+
+        di.inject(InjectAdapter {
+            token: SchedulerProvider::token(),
+            factory: Arc::new(move |_| scheduler.clone()),
+        })
+        .await?
+    };
+
+    let di = if di
+        .get_context()
+        .has_provider(IdempotencyProvider::token())
+        .await
+    {
+        di
+    } else {
+        let idempotency = IdempotencyProvider::default();
+
+        di.inject(InjectAdapter {
+            token: IdempotencyProvider::token(),
+            factory: Arc::new(move |_| idempotency.clone()),
+        })
+        .await?
+    };
+
+    let di = if di
+        .get_context()
+        .has_provider(SingleFlightProvider::token())
+        .await
+    {
+        di
+    } else {
+        let flights = SingleFlightProvider::new();
+
+        di.inject(InjectAdapter {
+            token: SingleFlightProvider::token(),
+            factory: Arc::new(move |_| flights.clone()),
+        })
+        .await?
+    };
+
+    // One provider per container shares the buses it resolved on first dispatch. Optional
+    // providers belong before this call, one injected after the first dispatch is ignored.
+    let provider = OnceLock::new();
+
     let di = di
         .inject(InjectAdapter {
             token: CqrsProvider::token(),
-            factory: Arc::new(|context| CqrsProvider::new(context)),
+            factory: Arc::new(move |context| {
+                provider.get_or_init(|| CqrsProvider::new(context)).clone()
+            }),
         })
         .await?;
 
@@ -0,0 +1,26 @@
+use std::fmt;
+
+use crate::models::message_kind::MessageKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelledError {
+    kind: MessageKind,
+}
+
+impl CancelledError {
+    pub fn new(kind: MessageKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn get_kind(&self) -> MessageKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for CancelledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} was cancelled before it finished", self.kind)
+    }
+}
+
+impl std::error::Error for CancelledError {}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+use crate::models::expected_version::ExpectedVersion;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrencyError {
+    stream_id: String,
+    expected: ExpectedVersion,
+    actual: u64,
+}
+
+impl ConcurrencyError {
+    pub fn new(stream_id: &str, expected: ExpectedVersion, actual: u64) -> Self {
+        Self {
+            stream_id: stream_id.to_string(),
+            expected,
+            actual,
+        }
+    }
+
+    pub fn get_stream_id(&self) -> &str {
+        &self.stream_id
+    }
+
+    pub fn get_expected(&self) -> ExpectedVersion {
+        self.expected
+    }
+
+    pub fn get_actual(&self) -> u64 {
+        self.actual
+    }
+}
+
+impl fmt::Display for ConcurrencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Stream {} expected version {:?} but is at {}",
+            self.stream_id, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ConcurrencyError {}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyConflictError {
+    key: String,
+}
+
+impl IdempotencyConflictError {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for IdempotencyConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Command with idempotency key {} is in progress",
+            self.key
+        )
+    }
+}
+
+impl std::error::Error for IdempotencyConflictError {}
@@ -0,0 +1,7 @@
+pub mod cancelled_error;
+pub mod concurrency_error;
+pub mod idempotency_conflict_error;
+pub mod projection_error;
+pub mod shared_error;
+pub mod timeout_error;
+pub mod transient_error;
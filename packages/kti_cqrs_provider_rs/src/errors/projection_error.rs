@@ -0,0 +1,38 @@
+use std::fmt;
+
+use kti_cqrs_rs::errors::error::Error;
+
+// Projections that failed during a catch-up, the others still ran.
+#[derive(Debug)]
+pub struct ProjectionError {
+    applied: usize,
+    failures: Vec<(&'static str, Error)>,
+}
+
+impl ProjectionError {
+    pub fn new(applied: usize, failures: Vec<(&'static str, Error)>) -> Self {
+        Self { applied, failures }
+    }
+
+    pub fn get_applied(&self) -> usize {
+        self.applied
+    }
+
+    pub fn get_failures(&self) -> &[(&'static str, Error)] {
+        &self.failures
+    }
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures = self
+            .failures
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect::<Vec<_>>();
+
+        write!(f, "Projections failed: {}", failures.join(", "))
+    }
+}
+
+impl std::error::Error for ProjectionError {}
@@ -0,0 +1,33 @@
+use std::{fmt, sync::Arc};
+
+use kti_cqrs_rs::errors::error::Error;
+
+// Error of a single flight execution handed to every caller that joined it.
+#[derive(Debug, Clone)]
+pub struct SharedError {
+    error: Arc<Error>,
+}
+
+impl SharedError {
+    pub fn new(error: Error) -> Self {
+        Self {
+            error: Arc::new(error),
+        }
+    }
+
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref().as_ref())
+    }
+}
@@ -0,0 +1,31 @@
+use std::{fmt, time::Duration};
+
+use crate::models::message_kind::MessageKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutError {
+    kind: MessageKind,
+    timeout: Duration,
+}
+
+impl TimeoutError {
+    pub fn new(kind: MessageKind, timeout: Duration) -> Self {
+        Self { kind, timeout }
+    }
+
+    pub fn get_kind(&self) -> MessageKind {
+        self.kind
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} timed out after {:?}", self.kind, self.timeout)
+    }
+}
+
+impl std::error::Error for TimeoutError {}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+use kti_cqrs_rs::errors::error::Error;
+
+// Marks a failure worth retrying, the default retry policy retries nothing else.
+#[derive(Debug)]
+pub struct TransientError {
+    error: Error,
+}
+
+impl TransientError {
+    pub fn new(error: impl Into<Error>) -> Self {
+        Self {
+            error: error.into(),
+        }
+    }
+
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+
+    // Looks through wrapping errors such as `SharedError`.
+    pub fn is_transient(error: &Error) -> bool {
+        let mut current: Option<&(dyn std::error::Error + 'static)> = Some(error.as_ref());
+
+        while let Some(error) = current {
+            if error.is::<Self>() {
+                return true;
+            }
+
+            current = error.source();
+        }
+
+        false
+    }
+}
+
+impl fmt::Display for TransientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for TransientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
@@ -1,3 +1,7 @@
+pub mod adapters;
 pub mod di;
+pub mod errors;
+pub mod models;
+pub mod ports;
 pub mod provider;
 pub use kti_cqrs_rs;
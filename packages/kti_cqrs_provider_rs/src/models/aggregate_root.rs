@@ -0,0 +1,39 @@
+use crate::ports::aggregate_port::AggregatePort;
+
+pub struct AggregateRoot<A: AggregatePort> {
+    id: String,
+    state: A,
+    version: u64,
+}
+
+impl<A: AggregatePort> AggregateRoot<A> {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            state: A::default(),
+            version: 0,
+        }
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_stream_id(&self) -> String {
+        format!("{}-{}", A::get_name(), self.id)
+    }
+
+    pub fn get_state(&self) -> &A {
+        &self.state
+    }
+
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn apply(&mut self, event: &A::Event) {
+        self.state.apply(event);
+
+        self.version += 1;
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    None,
+    Fixed(Duration),
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    pub fn get_delay(&self, attempt: u32) -> Duration {
+        match self {
+            Self::None => Duration::ZERO,
+            Self::Fixed(delay) => *delay,
+            Self::Exponential { initial, max } => initial
+                .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .map_or(*max, |delay| delay.min(*max)),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use kti_cqrs_rs::errors::error::Error;
+
+// Loaders of one request, shared by nested messages and dropped with the request.
+#[derive(Default)]
+pub struct BatchScope {
+    loaders: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl BatchScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_try_insert_with<T: Any + Send + Sync>(
+        &self,
+        create: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<Arc<T>, Error> {
+        let mut loaders = self.loaders.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let loader = match loaders.get(&TypeId::of::<T>()) {
+            Some(loader) => loader.clone(),
+            None => {
+                let loader: Arc<dyn Any + Send + Sync> = Arc::new(create()?);
+
+                loaders.insert(TypeId::of::<T>(), loader.clone());
+
+                loader
+            }
+        };
+
+        loader
+            .downcast::<T>()
+            .map_err(|_| "Batch scope entry has another type".into())
+    }
+}
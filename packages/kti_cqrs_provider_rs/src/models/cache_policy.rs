@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    key: String,
+    ttl: Duration,
+    tags: Vec<String>,
+}
+
+impl CachePolicy {
+    pub fn new(key: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            key: key.into(),
+            ttl,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+
+        self
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+}
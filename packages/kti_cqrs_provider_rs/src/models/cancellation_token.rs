@@ -0,0 +1,70 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use futures::future;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancellationState>,
+    parent: Option<Box<CancellationToken>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Cancelled with this token, cancelling the child leaves this token untouched.
+    pub fn child(&self) -> Self {
+        Self {
+            state: Arc::default(),
+            parent: Some(Box::new(self.clone())),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        self.state.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|parent| parent.is_cancelled())
+    }
+
+    pub async fn cancelled(&self) {
+        match &self.parent {
+            Some(parent) => {
+                let own = Box::pin(self.own_cancelled());
+                let inherited = Box::pin(parent.cancelled());
+
+                future::select(own, inherited).await;
+            }
+            None => self.own_cancelled().await,
+        }
+    }
+
+    async fn own_cancelled(&self) {
+        loop {
+            let notified = self.state.notify.notified();
+
+            if self.state.cancelled.load(Ordering::Acquire) {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use kti_cqrs_rs::errors::error::Error;
+
+const MINUTE: u64 = 60;
+const DAY: u64 = 24 * 60 * MINUTE;
+
+// Five field cron expression (minute hour day month weekday) evaluated in UTC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, Error> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expression => expression,
+        };
+
+        let fields = expanded.split_whitespace().collect::<Vec<_>>();
+
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!("Cron expression {} must have 5 fields", expression).into());
+        };
+
+        let weekday_mask = parse_field(weekdays, 0, 7)?;
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            // Both 0 and 7 mean Sunday.
+            weekdays: (weekday_mask | weekday_mask >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+
+    pub fn get_expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let seconds = after.duration_since(UNIX_EPOCH).ok()?.as_secs();
+
+        let start = (seconds / MINUTE + 1) * MINUTE;
+
+        let start_day = start / DAY;
+
+        let start_hour = start % DAY / 3600;
+
+        let start_minute = start % 3600 / MINUTE;
+
+        // Eight years always contain every month, day and weekday combination.
+        for day in start_day..start_day + 8 * 366 {
+            if !self.matches_day(day) {
+                continue;
+            }
+
+            let first_hour = if day == start_day { start_hour } else { 0 };
+
+            for hour in (first_hour..24).filter(|hour| has(self.hours, *hour)) {
+                let first_minute = if day == start_day && hour == start_hour {
+                    start_minute
+                } else {
+                    0
+                };
+
+                if let Some(minute) = (first_minute..60).find(|minute| has(self.minutes, *minute)) {
+                    let seconds = day * DAY + hour * 3600 + minute * MINUTE;
+
+                    return Some(UNIX_EPOCH + Duration::from_secs(seconds));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, day: u64) -> bool {
+        let (month, day_of_month) = civil_from_days(day);
+
+        if !has(self.months, month) {
+            return false;
+        }
+
+        // 1970-01-01 was a Thursday.
+        let weekday = (day + 4) % 7;
+
+        let day_matches = has(self.days, day_of_month);
+
+        let weekday_matches = has(self.weekdays, weekday);
+
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        }
+    }
+}
+
+fn has(mask: u64, value: u64) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_field(field: &str, min: u64, max: u64) -> Result<u64, Error> {
+    let mut mask = 0;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(parse_value(step, 1, max.max(1))?)),
+            None => (part, None),
+        };
+
+        let (from, to) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((from, to)) => (parse_value(from, min, max)?, parse_value(to, min, max)?),
+            None => {
+                let from = parse_value(range, min, max)?;
+
+                (from, if step.is_some() { max } else { from })
+            }
+        };
+
+        if from > to {
+            return Err(format!("Cron range {} is reversed", range).into());
+        }
+
+        for value in (from..=to).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u64, max: u64) -> Result<u64, Error> {
+    let parsed = value
+        .parse::<u64>()
+        .map_err(|_| format!("Cron value {} is not a number", value))?;
+
+    if !(min..=max).contains(&parsed) {
+        return Err(format!("Cron value {} is out of range {}-{}", value, min, max).into());
+    }
+
+    Ok(parsed)
+}
+
+// Returns month and day of month for days since 1970-01-01.
+fn civil_from_days(days: u64) -> (u64, u64) {
+    let days = days + 719_468;
+
+    let day_of_era = days % 146_097;
+
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+
+    let month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+
+    (if month < 10 { month + 3 } else { month - 9 }, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::CronSchedule;
+
+    #[test]
+    fn should_compute_next_cron_runs() {
+        let minutes = |minutes: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(minutes * 60);
+
+        let weekdays = CronSchedule::parse("30 9 * * 1-5").expect("Cant parse cron");
+
+        let runs = std::iter::successors(weekdays.next_after(SystemTime::UNIX_EPOCH), |run| {
+            weekdays.next_after(*run)
+        })
+        .take(3)
+        .collect::<Vec<_>>();
+
+        // 1970-01-01 was a Thursday, the weekend is skipped.
+        let day = 24 * 60;
+
+        assert_eq!(
+            runs,
+            vec![
+                minutes(9 * 60 + 30),
+                minutes(day + 9 * 60 + 30),
+                minutes(4 * day + 9 * 60 + 30)
+            ]
+        );
+
+        let leap = CronSchedule::parse("0 0 29 2 *").expect("Cant parse cron");
+
+        assert_eq!(
+            leap.next_after(SystemTime::UNIX_EPOCH),
+            Some(minutes((365 + 365 + 31 + 28) * day))
+        );
+
+        let every_quarter = CronSchedule::parse("*/15 * * * *").expect("Cant parse cron");
+
+        assert_eq!(every_quarter.next_after(minutes(15)), Some(minutes(30)));
+
+        assert!(CronSchedule::parse("* * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::watch;
+
+use crate::{errors::shared_error::SharedError, ports::batch_loader_port::BatchLoaderPort};
+
+type BatchResult<L> = Option<
+    Result<Arc<HashMap<<L as BatchLoaderPort>::Key, <L as BatchLoaderPort>::Value>>, SharedError>,
+>;
+
+struct PendingBatch<L: BatchLoaderPort> {
+    keys: Vec<L::Key>,
+    result: watch::Receiver<BatchResult<L>>,
+}
+
+pub struct DataLoader<L: BatchLoaderPort> {
+    loader: Arc<L>,
+    window: Duration,
+    pending: Arc<Mutex<Option<PendingBatch<L>>>>,
+}
+
+impl<L: BatchLoaderPort> DataLoader<L> {
+    pub fn new(loader: Arc<L>, window: Duration) -> Self {
+        Self {
+            loader,
+            window,
+            pending: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn get_window(&self) -> Duration {
+        self.window
+    }
+
+    pub async fn load(
+        &self,
+        key: L::Key,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<Option<L::Value>, Error> {
+        let mut values = self.load_many(vec![key], context).await?;
+
+        Ok(values.pop().flatten())
+    }
+
+    // The first caller of a batch spawns a task that waits for the window, then loads every
+    // key collected meanwhile, so the callers still get results when the first one is dropped.
+    pub async fn load_many(
+        &self,
+        keys: Vec<L::Key>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<Vec<Option<L::Value>>, Error> {
+        let mut result = {
+            let mut pending = lock_pending(&self.pending);
+
+            match pending.as_mut() {
+                Some(batch) => {
+                    for key in &keys {
+                        if !batch.keys.contains(key) {
+                            batch.keys.push(key.clone());
+                        }
+                    }
+
+                    batch.result.clone()
+                }
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+
+                    let mut unique = Vec::with_capacity(keys.len());
+
+                    for key in &keys {
+                        if !unique.contains(key) {
+                            unique.push(key.clone());
+                        }
+                    }
+
+                    *pending = Some(PendingBatch {
+                        keys: unique,
+                        result: receiver.clone(),
+                    });
+
+                    tokio::spawn(run_batch(
+                        self.loader.clone(),
+                        self.pending.clone(),
+                        self.window,
+                        context,
+                        sender,
+                    ));
+
+                    receiver
+                }
+            }
+        };
+
+        let loaded = result
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| "Batch was cancelled before it was loaded")?
+            .clone()
+            .unwrap_or_else(|| unreachable!("Batch result is set"));
+
+        let values = loaded?;
+
+        Ok(keys.iter().map(|key| values.get(key).cloned()).collect())
+    }
+}
+
+async fn run_batch<L: BatchLoaderPort>(
+    loader: Arc<L>,
+    pending: Arc<Mutex<Option<PendingBatch<L>>>>,
+    window: Duration,
+    context: Arc<dyn ContextPort>,
+    sender: watch::Sender<BatchResult<L>>,
+) {
+    tokio::time::sleep(window).await;
+
+    let batch = lock_pending(&pending)
+        .take()
+        .map(|batch| batch.keys)
+        .unwrap_or_default();
+
+    let loaded = match loader.load(batch, context).await {
+        Ok(values) => Ok(Arc::new(values)),
+        Err(error) => Err(SharedError::new(error)),
+    };
+
+    let _ = sender.send(Some(loaded));
+}
+
+fn lock_pending<L: BatchLoaderPort>(
+    pending: &Mutex<Option<PendingBatch<L>>>,
+) -> MutexGuard<'_, Option<PendingBatch<L>>> {
+    pending.lock().unwrap_or_else(PoisonError::into_inner)
+}
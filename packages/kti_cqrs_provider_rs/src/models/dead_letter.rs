@@ -0,0 +1,118 @@
+use std::{sync::Arc, time::SystemTime};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::ports::handler::event_handler_port::EventHandlerPort;
+
+use super::{
+    in_process_event::InProcessEvent, message_envelope::MessageEnvelope, message_id::MessageId,
+};
+
+pub type SharedEvent = Arc<dyn EventHandlerPort<Context = Arc<dyn ContextPort>>>;
+
+// Encoded like `OutboxRecord`, so stores may persist letters and replay them after a restart.
+// Letters of boxed events keep the event itself instead and are replayed in process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadLetter {
+    id: MessageId,
+    name: String,
+    payload: String,
+    subscriber: String,
+    error: String,
+    attempts: u32,
+    first_attempt_at: SystemTime,
+    failed_at: SystemTime,
+    envelope: Option<MessageEnvelope>,
+    event: Option<InProcessEvent>,
+}
+
+impl DeadLetter {
+    pub fn new(
+        id: MessageId,
+        name: &str,
+        payload: String,
+        subscriber: &str,
+        error: String,
+        attempts: u32,
+        first_attempt_at: SystemTime,
+    ) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            payload,
+            subscriber: subscriber.to_string(),
+            error,
+            attempts,
+            first_attempt_at,
+            failed_at: SystemTime::now(),
+            envelope: None,
+            event: None,
+        }
+    }
+
+    // The envelope of the failed event, a replay delivers it with the same id.
+    pub fn with_envelope(mut self, envelope: Option<MessageEnvelope>) -> Self {
+        self.envelope = envelope;
+
+        self
+    }
+
+    pub fn with_event(mut self, event: SharedEvent) -> Self {
+        self.event = Some(InProcessEvent::new(event));
+
+        self
+    }
+
+    pub fn with_failed_at(mut self, failed_at: SystemTime) -> Self {
+        self.failed_at = failed_at;
+
+        self
+    }
+
+    pub fn with_failed_attempts(mut self, error: String, attempts: u32) -> Self {
+        self.error = error;
+        self.attempts += attempts;
+        self.failed_at = SystemTime::now();
+
+        self
+    }
+
+    pub fn get_id(&self) -> MessageId {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_payload(&self) -> &str {
+        &self.payload
+    }
+
+    pub fn get_subscriber(&self) -> &str {
+        &self.subscriber
+    }
+
+    pub fn get_error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn get_first_attempt_at(&self) -> SystemTime {
+        self.first_attempt_at
+    }
+
+    pub fn get_failed_at(&self) -> SystemTime {
+        self.failed_at
+    }
+
+    pub fn get_envelope(&self) -> Option<&MessageEnvelope> {
+        self.envelope.as_ref()
+    }
+
+    pub fn get_event(&self) -> Option<SharedEvent> {
+        self.event.as_ref().map(InProcessEvent::get_event)
+    }
+}
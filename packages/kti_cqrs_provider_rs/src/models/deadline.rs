@@ -0,0 +1,89 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::errors::timeout_error::TimeoutError;
+
+use super::{
+    cancellation_token::CancellationToken, message_context::MessageContext,
+    message_kind::MessageKind,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    kind: MessageKind,
+    timeout: Duration,
+    grace: Duration,
+    at: Instant,
+}
+
+impl Deadline {
+    pub fn new(kind: MessageKind, timeout: Duration, grace: Duration) -> Self {
+        Self {
+            kind,
+            timeout,
+            grace,
+            at: Instant::now() + timeout,
+        }
+    }
+
+    // Bounds a handler by the deadline of its message context, if it has one.
+    pub async fn enforce<F, O>(context: &Arc<dyn ContextPort>, handler: F) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        let message = MessageContext::from_context(context);
+
+        let Some(deadline) = message.and_then(MessageContext::get_deadline) else {
+            return handler.await;
+        };
+
+        let cancellation = message
+            .map(MessageContext::get_cancellation)
+            .unwrap_or_default();
+
+        deadline.run(&cancellation, handler).await
+    }
+
+    pub fn get_kind(&self) -> MessageKind {
+        self.kind
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn get_grace(&self) -> Duration {
+        self.grace
+    }
+
+    pub fn get_at(&self) -> Instant {
+        self.at
+    }
+
+    // Cancels at the deadline, then polls the handler for the grace period so it can observe
+    // the cancellation. Output finished within the grace period is kept.
+    pub async fn run<F, O>(&self, cancellation: &CancellationToken, handler: F) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        let mut handler = pin!(handler);
+
+        if let Ok(result) = tokio::time::timeout_at(self.at.into(), handler.as_mut()).await {
+            return result;
+        }
+
+        cancellation.cancel();
+
+        match tokio::time::timeout(self.grace, handler).await {
+            Ok(Ok(output)) => Ok(output),
+            _ => Err(TimeoutError::new(self.kind, self.timeout).into()),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::pin::Pin;
+
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::provider::cqrs_provider::CqrsProvider;
+
+use super::{message_envelope::MessageEnvelope, unit_of_work::DeferredEvent};
+
+pub type DeferredDelivery = Box<
+    dyn FnOnce(CqrsProvider, u64) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> + Send,
+>;
+
+// Outbox messages are stored with their own envelope when the outermost command commits
+// and delivered with the id of their record.
+pub enum DeferredMessage {
+    Event(DeferredEvent),
+    Outbox {
+        name: &'static str,
+        payload: String,
+        envelope: Box<MessageEnvelope>,
+        deliver: DeferredDelivery,
+    },
+}
@@ -0,0 +1,38 @@
+use super::message_id::MessageId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventData {
+    name: String,
+    payload: String,
+    message_id: Option<MessageId>,
+}
+
+impl EventData {
+    pub fn new(name: &str, payload: String) -> Self {
+        Self {
+            name: name.to_string(),
+            payload,
+            message_id: None,
+        }
+    }
+
+    // Stores skip an event whose message id is already stored, so a redelivered
+    // message is appended once.
+    pub fn with_message_id(mut self, message_id: MessageId) -> Self {
+        self.message_id = Some(message_id);
+
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_payload(&self) -> &str {
+        &self.payload
+    }
+
+    pub fn get_message_id(&self) -> Option<MessageId> {
+        self.message_id
+    }
+}
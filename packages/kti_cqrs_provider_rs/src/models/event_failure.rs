@@ -0,0 +1,40 @@
+use kti_cqrs_rs::errors::error::Error;
+
+#[derive(Debug)]
+pub struct EventFailure {
+    id: u64,
+    subscriber: Option<&'static str>,
+    error: Error,
+}
+
+impl EventFailure {
+    pub fn new(id: u64, error: Error) -> Self {
+        Self {
+            id,
+            subscriber: None,
+            error,
+        }
+    }
+
+    pub fn with_subscriber(mut self, subscriber: &'static str) -> Self {
+        self.subscriber = Some(subscriber);
+
+        self
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_subscriber(&self) -> Option<&'static str> {
+        self.subscriber
+    }
+
+    pub fn get_error(&self) -> &Error {
+        &self.error
+    }
+
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
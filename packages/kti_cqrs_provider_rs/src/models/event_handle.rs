@@ -0,0 +1,43 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use kti_cqrs_rs::errors::error::Error;
+use tokio::task::JoinHandle;
+
+pub struct EventHandle {
+    id: u64,
+    handle: JoinHandle<Result<(), Error>>,
+}
+
+impl EventHandle {
+    pub fn new(id: u64, handle: JoinHandle<Result<(), Error>>) -> Self {
+        Self { id, handle }
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn abort(&self) {
+        self.handle.abort()
+    }
+}
+
+impl Future for EventHandle {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle)
+            .poll(cx)
+            .map(|result| match result {
+                Ok(result) => result,
+                Err(error) => Err(error.into()),
+            })
+    }
+}
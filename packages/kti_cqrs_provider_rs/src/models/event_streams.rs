@@ -0,0 +1,112 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::SystemTime,
+};
+
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::errors::concurrency_error::ConcurrencyError;
+
+use super::{
+    event_data::EventData, expected_version::ExpectedVersion, message_id::MessageId,
+    stored_event::StoredEvent,
+};
+
+#[derive(Default)]
+pub struct EventStreams {
+    events: Vec<StoredEvent>,
+    streams: HashMap<String, Vec<usize>>,
+    message_ids: HashSet<MessageId>,
+}
+
+impl EventStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_position(&self) -> u64 {
+        self.events.last().map_or(0, StoredEvent::get_position)
+    }
+
+    pub fn get_version(&self, stream_id: &str) -> u64 {
+        self.streams
+            .get(stream_id)
+            .map_or(0, |indexes| indexes.len() as u64)
+    }
+
+    pub fn prepare(
+        &self,
+        stream_id: &str,
+        expected: ExpectedVersion,
+        events: Vec<EventData>,
+    ) -> Result<Vec<StoredEvent>, Error> {
+        let current = self.get_version(stream_id);
+
+        if !expected.matches(current) {
+            return Err(ConcurrencyError::new(stream_id, expected, current).into());
+        }
+
+        let position = self.get_position();
+        let recorded_at = SystemTime::now();
+
+        let mut message_ids = HashSet::new();
+
+        // Events with a stored message id are skipped, the version check still applies.
+        let events = events
+            .into_iter()
+            .filter(|data| {
+                data.get_message_id().is_none_or(|message_id| {
+                    !self.message_ids.contains(&message_id) && message_ids.insert(message_id)
+                })
+            })
+            .enumerate()
+            .map(|(index, data)| {
+                let offset = index as u64 + 1;
+
+                StoredEvent::new(
+                    position + offset,
+                    stream_id,
+                    current + offset,
+                    data,
+                    recorded_at,
+                )
+            })
+            .collect();
+
+        Ok(events)
+    }
+
+    pub fn push(&mut self, event: StoredEvent) {
+        if let Some(message_id) = event.get_message_id() {
+            self.message_ids.insert(message_id);
+        }
+
+        self.streams
+            .entry(event.get_stream_id().to_string())
+            .or_default()
+            .push(self.events.len());
+
+        self.events.push(event);
+    }
+
+    pub fn read_stream(&self, stream_id: &str, after_version: u64) -> Vec<StoredEvent> {
+        self.streams
+            .get(stream_id)
+            .map(|indexes| {
+                indexes
+                    .iter()
+                    .skip(after_version as usize)
+                    .map(|index| self.events[*index].clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn read_all(&self, after_position: u64, limit: usize) -> Vec<StoredEvent> {
+        let start = self
+            .events
+            .partition_point(|event| event.get_position() <= after_position);
+
+        self.events[start..].iter().take(limit).cloned().collect()
+    }
+}
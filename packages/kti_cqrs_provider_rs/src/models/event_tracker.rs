@@ -0,0 +1,436 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::future::{self, Either};
+use kti_cqrs_rs::errors::error::Error;
+use tokio::{
+    sync::{Notify, Semaphore, oneshot},
+    task::{AbortHandle, JoinHandle},
+};
+
+use super::{overflow_policy::OverflowPolicy, shutdown_report::ShutdownReport};
+
+tokio::task_local! {
+    static EVENT_WORKER: ();
+}
+
+enum TrackedState {
+    Queued(oneshot::Sender<()>),
+    Dropped,
+    Started,
+}
+
+struct TrackedTask {
+    abort: AbortHandle,
+    state: TrackedState,
+}
+
+struct EventTrackerLimits {
+    workers: Arc<Semaphore>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+}
+
+pub struct EventTracker {
+    accepting: AtomicBool,
+    tasks: Mutex<HashMap<u64, TrackedTask>>,
+    partitions: Mutex<HashMap<String, (u64, oneshot::Receiver<()>)>>,
+    idle: Notify,
+    released: Notify,
+    limits: Option<EventTrackerLimits>,
+    dropped: AtomicU64,
+}
+
+struct EventTrackerGuard {
+    id: u64,
+    partition: Option<(String, oneshot::Sender<()>)>,
+    tracker: Arc<EventTracker>,
+}
+
+impl EventTrackerGuard {
+    // Returns false for a task dropped from the queue before it got a worker.
+    fn start(&self) -> bool {
+        let mut tasks = self.tracker.lock_tasks();
+
+        let Some(task) = tasks.get_mut(&self.id) else {
+            return false;
+        };
+
+        if let TrackedState::Dropped = task.state {
+            return false;
+        }
+
+        task.state = TrackedState::Started;
+
+        true
+    }
+}
+
+impl Drop for EventTrackerGuard {
+    fn drop(&mut self) {
+        if let Some((key, _)) = &self.partition {
+            let mut partitions = self.tracker.lock_partitions();
+
+            if partitions.get(key).is_some_and(|(id, _)| *id == self.id) {
+                partitions.remove(key);
+            }
+        }
+
+        let mut tasks = self.tracker.lock_tasks();
+
+        tasks.remove(&self.id);
+
+        self.tracker.released.notify_waiters();
+
+        if tasks.is_empty() {
+            self.tracker.idle.notify_waiters();
+        }
+    }
+}
+
+impl Default for EventTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventTracker {
+    pub fn new() -> Self {
+        Self {
+            accepting: AtomicBool::new(true),
+            tasks: Mutex::new(HashMap::new()),
+            partitions: Mutex::new(HashMap::new()),
+            idle: Notify::new(),
+            released: Notify::new(),
+            limits: None,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn bounded(max_workers: usize, queue_capacity: usize, overflow: OverflowPolicy) -> Self {
+        let max_workers = max_workers.max(1);
+
+        Self {
+            limits: Some(EventTrackerLimits {
+                workers: Arc::new(Semaphore::new(max_workers)),
+                capacity: max_workers + queue_capacity,
+                overflow,
+            }),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.accepting.load(Ordering::Acquire)
+    }
+
+    pub fn get_in_flight(&self) -> usize {
+        self.lock_tasks().len()
+    }
+
+    pub fn get_active_workers(&self) -> usize {
+        self.lock_tasks()
+            .values()
+            .filter(|task| matches!(task.state, TrackedState::Started))
+            .count()
+    }
+
+    pub fn get_queue_depth(&self) -> usize {
+        self.lock_tasks()
+            .values()
+            .filter(|task| matches!(task.state, TrackedState::Queued(_)))
+            .count()
+    }
+
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Acquire)
+    }
+
+    // Never waits, with `OverflowPolicy::Block` a full queue hands the task to a background
+    // waiter. The task gets the admission error if it never runs.
+    pub fn spawn<T, F>(
+        self: &Arc<Self>,
+        id: u64,
+        partition: Option<String>,
+        task: T,
+    ) -> Result<(), Error>
+    where
+        T: FnOnce(Result<(), Error>) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        {
+            let mut tasks = self.lock_tasks();
+
+            if self.admit(&mut tasks)? {
+                self.spawn_tracked(&mut tasks, id, partition, task);
+
+                return Ok(());
+            }
+        }
+
+        let tracker = self.clone();
+
+        tokio::spawn(async move {
+            if let Err((error, task)) = tracker.wait_and_spawn(id, partition, task).await {
+                task(Err(error)).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    // Handlers publishing from an event worker skip `OverflowPolicy::Block`,
+    // waiting for their own queue would deadlock.
+    pub async fn spawn_or_wait<T, F>(
+        self: &Arc<Self>,
+        id: u64,
+        partition: Option<String>,
+        task: T,
+    ) -> Result<JoinHandle<F::Output>, Error>
+    where
+        T: FnOnce(Result<(), Error>) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.wait_and_spawn(id, partition, task)
+            .await
+            .map_err(|(error, _)| error)
+    }
+
+    pub async fn drain(&self) {
+        self.accepting.store(false, Ordering::Release);
+
+        self.released.notify_waiters();
+
+        loop {
+            let idle = self.idle.notified();
+
+            if self.lock_tasks().is_empty() {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        if tokio::time::timeout(timeout, self.drain()).await.is_ok() {
+            return ShutdownReport::new(Vec::new());
+        }
+
+        let tasks = self
+            .lock_tasks()
+            .iter()
+            .map(|(id, task)| (*id, task.abort.clone()))
+            .collect::<Vec<_>>();
+
+        let mut aborted = tasks
+            .into_iter()
+            .map(|(id, task)| {
+                task.abort();
+
+                id
+            })
+            .collect::<Vec<_>>();
+
+        aborted.sort_unstable();
+
+        // Aborted tasks stop at their next await, their guards release them once they did.
+        loop {
+            let released = self.released.notified();
+
+            {
+                let tasks = self.lock_tasks();
+
+                if aborted.iter().all(|id| !tasks.contains_key(id)) {
+                    break;
+                }
+            }
+
+            released.await;
+        }
+
+        ShutdownReport::new(aborted)
+    }
+
+    // Hands the task back when it can't be admitted.
+    async fn wait_and_spawn<T, F>(
+        self: &Arc<Self>,
+        id: u64,
+        partition: Option<String>,
+        task: T,
+    ) -> Result<JoinHandle<F::Output>, (Error, T)>
+    where
+        T: FnOnce(Result<(), Error>) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        loop {
+            let released = self.released.notified();
+
+            {
+                let mut tasks = self.lock_tasks();
+
+                match self.admit(&mut tasks) {
+                    Ok(true) => return Ok(self.spawn_tracked(&mut tasks, id, partition, task)),
+                    Ok(false) => {}
+                    Err(error) => return Err((error, task)),
+                }
+            }
+
+            released.await;
+        }
+    }
+
+    fn admit(&self, tasks: &mut HashMap<u64, TrackedTask>) -> Result<bool, Error> {
+        // A draining bus still admits nested publishes, so in-flight handlers can finish.
+        if !self.is_accepting() && EVENT_WORKER.try_with(|_| ()).is_err() {
+            return Err("Event bus is shut down".into());
+        }
+
+        let Some(limits) = &self.limits else {
+            return Ok(true);
+        };
+
+        // Dropped tasks only report themselves, so they don't take capacity.
+        let admitted = tasks
+            .values()
+            .filter(|task| !matches!(task.state, TrackedState::Dropped))
+            .count();
+
+        if admitted < limits.capacity {
+            return Ok(true);
+        }
+
+        match limits.overflow {
+            OverflowPolicy::Block if EVENT_WORKER.try_with(|_| ()).is_ok() => Ok(true),
+            OverflowPolicy::Block => Ok(false),
+            OverflowPolicy::DropOldest => {
+                let oldest = tasks
+                    .iter()
+                    .filter(|(_, task)| matches!(task.state, TrackedState::Queued(_)))
+                    .map(|(id, _)| *id)
+                    .min();
+
+                let Some(task) = oldest.and_then(|id| tasks.get_mut(&id)) else {
+                    return Err("Event bus queue is full".into());
+                };
+
+                if let TrackedState::Queued(dropped) =
+                    std::mem::replace(&mut task.state, TrackedState::Dropped)
+                {
+                    let _ = dropped.send(());
+                }
+
+                self.dropped.fetch_add(1, Ordering::AcqRel);
+
+                Ok(true)
+            }
+            OverflowPolicy::Reject => Err("Event bus queue is full".into()),
+        }
+    }
+
+    fn spawn_tracked<T, F>(
+        self: &Arc<Self>,
+        tasks: &mut HashMap<u64, TrackedTask>,
+        id: u64,
+        partition: Option<String>,
+        task: T,
+    ) -> JoinHandle<F::Output>
+    where
+        T: FnOnce(Result<(), Error>) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (previous, partition) = match partition {
+            Some(key) => {
+                let (sender, receiver) = oneshot::channel();
+
+                let previous = self
+                    .lock_partitions()
+                    .insert(key.clone(), (id, receiver))
+                    .map(|(_, previous)| previous);
+
+                (previous, Some((key, sender)))
+            }
+            None => (None, None),
+        };
+
+        let guard = EventTrackerGuard {
+            id,
+            partition,
+            tracker: self.clone(),
+        };
+
+        let workers = self.limits.as_ref().map(|limits| limits.workers.clone());
+
+        let (dropped, dropped_receiver) = oneshot::channel();
+
+        let handle = tokio::spawn(async move {
+            let guard = guard;
+
+            let mut previous = previous;
+
+            // Waits for the previous event of the partition before taking a worker,
+            // so queued events of one key never hold every worker.
+            let turn = Box::pin(async {
+                if let Some(receiver) = previous.as_mut() {
+                    let _ = receiver.await;
+
+                    previous = None;
+                }
+
+                match workers {
+                    Some(workers) => workers.acquire_owned().await.ok(),
+                    None => None,
+                }
+            });
+
+            let permit = match future::select(turn, dropped_receiver).await {
+                Either::Left((permit, _)) => permit,
+                Either::Right(_) => None,
+            };
+
+            if !guard.start() {
+                // A dropped event still releases its successor only after its predecessor,
+                // so the key keeps its order.
+                if let Some(previous) = previous {
+                    let _ = previous.await;
+                }
+
+                return task(Err("Event was dropped from the full event bus queue".into())).await;
+            }
+
+            let _permit = permit;
+
+            EVENT_WORKER.scope((), task(Ok(()))).await
+        });
+
+        tasks.insert(
+            id,
+            TrackedTask {
+                abort: handle.abort_handle(),
+                state: TrackedState::Queued(dropped),
+            },
+        );
+
+        handle
+    }
+
+    fn lock_partitions(&self) -> MutexGuard<'_, HashMap<String, (u64, oneshot::Receiver<()>)>> {
+        self.partitions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_tasks(&self) -> MutexGuard<'_, HashMap<u64, TrackedTask>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
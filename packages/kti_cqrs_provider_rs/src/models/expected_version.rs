@@ -0,0 +1,16 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedVersion {
+    Any,
+    NoStream,
+    Exact(u64),
+}
+
+impl ExpectedVersion {
+    pub fn matches(&self, current: u64) -> bool {
+        match self {
+            Self::Any => true,
+            Self::NoStream => current == 0,
+            Self::Exact(version) => *version == current,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdempotencyStatus {
+    Started,
+    InProgress,
+    // The encoded outcome of the command.
+    Completed(String),
+}
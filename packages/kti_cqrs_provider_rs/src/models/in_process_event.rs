@@ -0,0 +1,34 @@
+use std::{fmt, sync::Arc};
+
+use super::dead_letter::SharedEvent;
+
+// A boxed event kept as is, it can't be encoded and is only replayed by the process
+// that stored it.
+#[derive(Clone)]
+pub struct InProcessEvent {
+    event: SharedEvent,
+}
+
+impl InProcessEvent {
+    pub fn new(event: SharedEvent) -> Self {
+        Self { event }
+    }
+
+    pub fn get_event(&self) -> SharedEvent {
+        self.event.clone()
+    }
+}
+
+impl fmt::Debug for InProcessEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InProcessEvent")
+    }
+}
+
+impl PartialEq for InProcessEvent {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.event, &other.event)
+    }
+}
+
+impl Eq for InProcessEvent {}
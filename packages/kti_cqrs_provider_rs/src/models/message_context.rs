@@ -0,0 +1,129 @@
+use std::{
+    any::Any,
+    sync::{Arc, Weak},
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::{
+    container::container::Container,
+    ports::{adapter_port::AdapterPort, context_port::ContextPort},
+};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::provider::cqrs_provider::CqrsProvider;
+
+use super::{
+    batch_scope::BatchScope, cancellation_token::CancellationToken, deadline::Deadline,
+    message_envelope::MessageEnvelope, unit_of_work::UnitOfWork,
+};
+
+// Creates the message context of every attempt of one message.
+pub type ContextFactory = Arc<dyn Fn() -> Arc<dyn ContextPort> + Send + Sync>;
+
+pub struct MessageContext {
+    this: Weak<MessageContext>,
+    context: Arc<dyn ContextPort>,
+    cancellation: CancellationToken,
+    unit_of_work: Option<Arc<UnitOfWork>>,
+    batches: Arc<BatchScope>,
+    envelope: MessageEnvelope,
+    type_name: Option<&'static str>,
+    deadline: Option<Deadline>,
+}
+
+impl MessageContext {
+    pub fn new(
+        context: Arc<dyn ContextPort>,
+        cancellation: CancellationToken,
+        unit_of_work: Option<Arc<UnitOfWork>>,
+        envelope: MessageEnvelope,
+        type_name: Option<&'static str>,
+        deadline: Option<Deadline>,
+    ) -> Arc<Self> {
+        let parent = Self::from_context(&context);
+
+        let unit_of_work =
+            unit_of_work.or_else(|| parent.and_then(|parent| parent.unit_of_work.clone()));
+
+        let batches = parent.map_or_else(Default::default, |parent| parent.batches.clone());
+
+        Arc::new_cyclic(|this| Self {
+            this: this.clone(),
+            context,
+            cancellation,
+            unit_of_work,
+            batches,
+            envelope,
+            type_name,
+            deadline,
+        })
+    }
+
+    pub fn from_context(context: &Arc<dyn ContextPort>) -> Option<&Self> {
+        context.as_any().downcast_ref::<Self>()
+    }
+
+    pub fn get_inner(&self) -> Arc<dyn ContextPort> {
+        self.context.clone()
+    }
+
+    pub fn get_cancellation(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    pub fn get_batch_scope(&self) -> Arc<BatchScope> {
+        self.batches.clone()
+    }
+
+    pub fn get_envelope(&self) -> &MessageEnvelope {
+        &self.envelope
+    }
+
+    pub fn get_deadline(&self) -> Option<Deadline> {
+        self.deadline
+    }
+
+    pub fn get_type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    pub fn get_unit_of_work(&self) -> Option<Arc<UnitOfWork>> {
+        self.unit_of_work
+            .clone()
+            .filter(|unit_of_work| unit_of_work.is_open())
+    }
+}
+
+#[async_trait]
+impl ContextPort for MessageContext {
+    async fn has_provider(&self, token: &'static str) -> bool {
+        self.context.has_provider(token).await
+    }
+
+    // Handlers resolving `CqrsProvider` get one scoped to this message,
+    // so nested messages and raised events see the same context.
+    async fn resolve_provider(&self, token: &'static str) -> Result<Box<dyn Any>, Error> {
+        let provider = self.context.resolve_provider(token).await?;
+
+        if token != CqrsProvider::token() {
+            return Ok(provider);
+        }
+
+        let provider = provider
+            .downcast::<CqrsProvider>()
+            .map_err(|_| format!("Cant resolve provider: {}", token))?;
+
+        let context: Arc<dyn ContextPort> =
+            self.this.upgrade().ok_or("Message context is dropped")?;
+
+        Ok(Box::new(provider.scoped(context)))
+    }
+
+    fn get_container(&self) -> Arc<Container> {
+        self.context.get_container()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
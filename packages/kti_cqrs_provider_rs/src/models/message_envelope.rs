@@ -0,0 +1,235 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::adapters::line_codec;
+
+use super::{message_context::MessageContext, message_id::MessageId, principal::Principal};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageEnvelope {
+    id: MessageId,
+    correlation_id: MessageId,
+    causation_id: Option<MessageId>,
+    timestamp: SystemTime,
+    principal: Option<Principal>,
+    headers: BTreeMap<String, String>,
+}
+
+impl Default for MessageEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageEnvelope {
+    // A message without a parent starts its own correlation.
+    pub fn new() -> Self {
+        let id = MessageId::new();
+
+        Self {
+            id,
+            correlation_id: id,
+            causation_id: None,
+            timestamp: SystemTime::now(),
+            principal: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_context(context: &Arc<dyn ContextPort>) -> Option<&Self> {
+        MessageContext::from_context(context).map(MessageContext::get_envelope)
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: MessageId) -> Self {
+        self.correlation_id = correlation_id;
+
+        self
+    }
+
+    pub fn with_principal(mut self, principal: Principal) -> Self {
+        self.principal = Some(principal);
+
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+
+        self
+    }
+
+    // Nested messages keep the correlation, principal and headers and are caused by this one.
+    pub fn create_child(&self) -> Self {
+        Self {
+            id: MessageId::new(),
+            correlation_id: self.correlation_id,
+            causation_id: Some(self.id),
+            timestamp: SystemTime::now(),
+            principal: self.principal.clone(),
+            headers: self.headers.clone(),
+        }
+    }
+
+    pub fn get_id(&self) -> MessageId {
+        self.id
+    }
+
+    pub fn get_correlation_id(&self) -> MessageId {
+        self.correlation_id
+    }
+
+    pub fn get_causation_id(&self) -> Option<MessageId> {
+        self.causation_id
+    }
+
+    pub fn get_timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn get_principal(&self) -> Option<&Principal> {
+        self.principal.as_ref()
+    }
+
+    pub fn get_headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    // One tab separated line, so stores can persist the envelope next to their records:
+    // `id  correlation  causation  timestamp_ns  user  tenant  (header value)*`, absent
+    // options are `-`, present ones are prefixed with `+`.
+    pub fn encode(&self) -> String {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let optional = |value: Option<&str>| match value {
+            Some(value) => format!("+{}", value),
+            None => "-".to_string(),
+        };
+
+        let mut fields = vec![
+            self.id.to_string(),
+            self.correlation_id.to_string(),
+            optional(self.causation_id.map(|id| id.to_string()).as_deref()),
+            timestamp.to_string(),
+            optional(self.principal.as_ref().map(Principal::get_user_id)),
+            optional(self.principal.as_ref().and_then(Principal::get_tenant_id)),
+        ];
+
+        for (name, value) in &self.headers {
+            fields.push(name.clone());
+            fields.push(value.clone());
+        }
+
+        fields
+            .iter()
+            .map(|field| line_codec::escape(field))
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+
+    pub fn decode(line: &str) -> Result<Self, Error> {
+        let invalid = || format!("Malformed message envelope: {}", line);
+
+        let fields = line
+            .split('\t')
+            .map(line_codec::unescape)
+            .collect::<Vec<_>>();
+
+        let [
+            id,
+            correlation_id,
+            causation_id,
+            timestamp,
+            user_id,
+            tenant_id,
+            headers @ ..,
+        ] = &fields[..]
+        else {
+            return Err(invalid().into());
+        };
+
+        if headers.len() % 2 != 0 {
+            return Err(invalid().into());
+        }
+
+        let optional = |value: &str| -> Result<Option<String>, Error> {
+            match (value, value.strip_prefix('+')) {
+                ("-", _) => Ok(None),
+                (_, Some(value)) => Ok(Some(value.to_string())),
+                _ => Err(invalid().into()),
+            }
+        };
+
+        let timestamp = timestamp.parse::<u128>()?;
+
+        let tenant_id = optional(tenant_id)?;
+
+        let principal = optional(user_id)?.map(|user_id| match tenant_id {
+            Some(tenant_id) => Principal::new(user_id).with_tenant(tenant_id),
+            None => Principal::new(user_id),
+        });
+
+        Ok(Self {
+            id: id.parse()?,
+            correlation_id: correlation_id.parse()?,
+            causation_id: optional(causation_id)?.map(|id| id.parse()).transpose()?,
+            timestamp: UNIX_EPOCH
+                + Duration::new(
+                    (timestamp / 1_000_000_000) as u64,
+                    (timestamp % 1_000_000_000) as u32,
+                ),
+            principal,
+            headers: headers
+                .chunks(2)
+                .map(|pair| (pair[0].clone(), pair[1].clone()))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::principal::Principal;
+
+    use super::MessageEnvelope;
+
+    #[test]
+    fn should_encode_and_decode_message_envelope() {
+        let request = MessageEnvelope::new()
+            .with_principal(Principal::new("ad\tmin").with_tenant("acme"))
+            .with_header("request-id", "r-5")
+            .with_header("note", "line\nbreak\\");
+
+        let child = request.create_child();
+
+        for envelope in [request, child, MessageEnvelope::new()] {
+            let decoded =
+                MessageEnvelope::decode(&envelope.encode()).expect("Cant decode envelope");
+
+            assert_eq!(decoded, envelope);
+        }
+
+        let without_tenant = MessageEnvelope::new().with_principal(Principal::new("admin"));
+
+        assert_eq!(
+            MessageEnvelope::decode(&without_tenant.encode()).expect("Cant decode envelope"),
+            without_tenant
+        );
+
+        assert!(MessageEnvelope::decode("").is_err());
+        assert!(MessageEnvelope::decode("not\tan\tenvelope").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use kti_cqrs_rs::errors::error::Error;
+
+use super::random::Random;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const LENGTH: usize = 26;
+
+// A ULID: 48 bits of unix milliseconds followed by 80 random bits, unique across
+// processes and sortable by creation time. Displayed as 26 Crockford base32 chars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MessageId(u128);
+
+impl Default for MessageId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageId {
+    pub fn new() -> Self {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        let random = ((Random::next_u64() as u128) << 64 | Random::next_u64() as u128) >> 48;
+
+        Self((millis & 0xFFFF_FFFF_FFFF) << 80 | random)
+    }
+
+    pub fn get_timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + std::time::Duration::from_millis((self.0 >> 80) as u64)
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = [0u8; LENGTH];
+
+        for (index, char) in chars.iter_mut().enumerate() {
+            let shift = (LENGTH - 1 - index) * 5;
+
+            *char = ALPHABET[(self.0 >> shift & 0x1F) as usize];
+        }
+
+        f.write_str(std::str::from_utf8(&chars).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl FromStr for MessageId {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid message id: {}", value);
+
+        // 26 chars hold 130 bits, the first one only carries 3 of them.
+        if value.len() != LENGTH || !value.starts_with(|char: char| ('0'..='7').contains(&char)) {
+            return Err(invalid().into());
+        }
+
+        let mut id = 0u128;
+
+        for char in value.bytes() {
+            let digit = ALPHABET
+                .iter()
+                .position(|known| *known == char.to_ascii_uppercase())
+                .ok_or_else(invalid)?;
+
+            id = id << 5 | digit as u128;
+        }
+
+        Ok(Self(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use super::MessageId;
+
+    #[test]
+    fn should_generate_unique_sortable_message_ids() {
+        let ids = (0..1000).map(|_| MessageId::new()).collect::<Vec<_>>();
+
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+
+        for id in &ids {
+            let text = id.to_string();
+
+            assert_eq!(text.len(), 26);
+            assert_eq!(text.parse::<MessageId>().expect("Cant parse id"), *id);
+            assert_eq!(
+                text.to_lowercase()
+                    .parse::<MessageId>()
+                    .expect("Cant parse id"),
+                *id
+            );
+        }
+
+        let earlier = MessageId::new();
+
+        std::thread::sleep(Duration::from_millis(2));
+
+        let later = MessageId::new();
+
+        assert!(earlier < later);
+        assert!(earlier.to_string() < later.to_string());
+        assert!(later.get_timestamp() > earlier.get_timestamp());
+
+        assert!("".parse::<MessageId>().is_err());
+        assert!("01ARZ3NDEKTSV4RRFFQ69G5FA".parse::<MessageId>().is_err());
+        assert!("81ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<MessageId>().is_err());
+        assert!("01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<MessageId>().is_err());
+    }
+}
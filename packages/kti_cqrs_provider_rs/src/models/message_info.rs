@@ -0,0 +1,83 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+
+use super::{
+    message_context::MessageContext, message_envelope::MessageEnvelope, message_id::MessageId,
+    message_kind::MessageKind,
+};
+
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_message_id() -> u64 {
+    NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageInfo {
+    kind: MessageKind,
+    started_at: Instant,
+    envelope: MessageEnvelope,
+    type_name: Option<&'static str>,
+}
+
+impl MessageInfo {
+    pub fn new(kind: MessageKind) -> Self {
+        Self::with_envelope(kind, MessageEnvelope::new())
+    }
+
+    pub fn with_envelope(kind: MessageKind, envelope: MessageEnvelope) -> Self {
+        Self {
+            kind,
+            started_at: Instant::now(),
+            envelope,
+            type_name: None,
+        }
+    }
+
+    pub fn with_type_name(mut self, type_name: &'static str) -> Self {
+        self.type_name = Some(type_name);
+
+        self
+    }
+
+    pub fn from_context(kind: MessageKind, context: &Arc<dyn ContextPort>) -> Self {
+        let Some(context) = MessageContext::from_context(context) else {
+            return Self::new(kind);
+        };
+
+        let message = Self::with_envelope(kind, context.get_envelope().clone());
+
+        match context.get_type_name() {
+            Some(type_name) => message.with_type_name(type_name),
+            None => message,
+        }
+    }
+
+    pub fn get_id(&self) -> MessageId {
+        self.envelope.get_id()
+    }
+
+    pub fn get_kind(&self) -> MessageKind {
+        self.kind
+    }
+
+    pub fn get_started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    pub fn get_envelope(&self) -> &MessageEnvelope {
+        &self.envelope
+    }
+
+    // Only typed dispatch knows the message type, boxed handlers report `None`.
+    pub fn get_type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+}
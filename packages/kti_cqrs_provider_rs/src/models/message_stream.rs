@@ -0,0 +1,116 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::Stream;
+use kti_cqrs_rs::errors::error::Error;
+use tokio::time::Sleep;
+
+use crate::{
+    errors::{cancelled_error::CancelledError, timeout_error::TimeoutError},
+    ports::stream_query_handler_port::QueryStream,
+    provider::middleware_provider::MiddlewareProvider,
+};
+
+use super::{cancellation_token::CancellationToken, message_info::MessageInfo};
+
+// Runs middleware `after` once the stream ends, fails or is dropped, a stream dropped before
+// its end is reported as cancelled. The query timeout bounds the wait for every next item.
+pub struct MessageStream<T> {
+    stream: QueryStream<T>,
+    pipeline: MiddlewareProvider,
+    message: MessageInfo,
+    timeout: Option<Duration>,
+    deadline: Option<Pin<Box<Sleep>>>,
+    cancellation: CancellationToken,
+    finished: bool,
+}
+
+impl<T> MessageStream<T> {
+    pub fn new(
+        stream: QueryStream<T>,
+        pipeline: MiddlewareProvider,
+        message: MessageInfo,
+        timeout: Option<Duration>,
+        cancellation: CancellationToken,
+    ) -> Self {
+        Self {
+            stream,
+            pipeline,
+            message,
+            timeout,
+            deadline: None,
+            cancellation,
+            finished: false,
+        }
+    }
+
+    pub fn get_message(&self) -> &MessageInfo {
+        &self.message
+    }
+
+    fn finish(&mut self, result: Result<(), Error>) -> Option<Result<T, Error>> {
+        self.finished = true;
+
+        self.deadline = None;
+
+        self.pipeline.leave(&self.message, result).err().map(Err)
+    }
+}
+
+impl<T> Stream for MessageStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        match this.stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) => {
+                this.deadline = None;
+
+                return Poll::Ready(Some(Ok(item)));
+            }
+            Poll::Ready(Some(Err(error))) => return Poll::Ready(this.finish(Err(error))),
+            Poll::Ready(None) => return Poll::Ready(this.finish(Ok(()))),
+            Poll::Pending => {}
+        }
+
+        let Some(timeout) = this.timeout else {
+            return Poll::Pending;
+        };
+
+        let deadline = this
+            .deadline
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+
+        if deadline.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        this.cancellation.cancel();
+
+        let error = TimeoutError::new(this.message.get_kind(), timeout).into();
+
+        Poll::Ready(this.finish(Err(error)))
+    }
+}
+
+impl<T> Drop for MessageStream<T> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.cancellation.cancel();
+
+        let error = CancelledError::new(self.message.get_kind()).into();
+
+        let _ = self.finish(Err(error));
+    }
+}
@@ -0,0 +1,6 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissedRunPolicy {
+    Skip,
+    #[default]
+    RunOnce,
+}
@@ -0,0 +1,48 @@
+pub mod aggregate_root;
+pub mod backoff;
+pub mod batch_scope;
+pub mod cache_policy;
+pub mod cancellation_token;
+pub mod cron_schedule;
+pub mod data_loader;
+pub mod dead_letter;
+pub mod deadline;
+pub mod deferred_message;
+pub mod event_data;
+pub mod event_failure;
+pub mod event_handle;
+pub mod event_streams;
+pub mod event_tracker;
+pub mod expected_version;
+pub mod idempotency_status;
+pub mod in_process_event;
+pub mod message_context;
+pub mod message_envelope;
+pub mod message_id;
+pub mod message_info;
+pub mod message_kind;
+pub mod message_stream;
+pub mod missed_run_policy;
+pub mod outbox_record;
+pub mod overflow_policy;
+pub mod page;
+pub mod page_cursor;
+pub mod page_position;
+pub mod page_request;
+pub mod page_sort;
+pub mod poison_event_policy;
+pub mod principal;
+pub mod random;
+pub mod recurring_job;
+pub mod recurring_job_stats;
+pub mod resolved_provider;
+pub mod retry_policy;
+pub mod saga_record;
+pub mod saga_status;
+pub mod scheduled_record;
+pub mod shutdown_report;
+pub mod skipped_event;
+pub mod sort_direction;
+pub mod sort_value;
+pub mod stored_event;
+pub mod unit_of_work;
@@ -0,0 +1,52 @@
+use std::time::SystemTime;
+
+use super::message_envelope::MessageEnvelope;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutboxRecord {
+    id: u64,
+    name: String,
+    payload: String,
+    created_at: SystemTime,
+    envelope: Option<MessageEnvelope>,
+}
+
+impl OutboxRecord {
+    pub fn new(id: u64, name: &str, payload: String, created_at: SystemTime) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            payload,
+            created_at,
+            envelope: None,
+        }
+    }
+
+    // The envelope the record is delivered with, so a relayed message keeps its id,
+    // correlation & principal.
+    pub fn with_envelope(mut self, envelope: Option<MessageEnvelope>) -> Self {
+        self.envelope = envelope;
+
+        self
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_payload(&self) -> &str {
+        &self.payload
+    }
+
+    pub fn get_created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    pub fn get_envelope(&self) -> Option<&MessageEnvelope> {
+        self.envelope.as_ref()
+    }
+}
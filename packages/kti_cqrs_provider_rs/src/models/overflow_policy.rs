@@ -0,0 +1,7 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    #[default]
+    Block,
+    Reject,
+    DropOldest,
+}
@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::ports::page_item_port::PageItemPort;
+
+use super::{
+    page_cursor::PageCursor, page_position::PagePosition, page_request::PageRequest,
+    page_sort::PageSort, sort_direction::SortDirection, sort_value::SortValue,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page<T> {
+    items: Vec<T>,
+    next_cursor: Option<String>,
+    total_estimate: Option<usize>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            items,
+            next_cursor: None,
+            total_estimate: None,
+        }
+    }
+
+    pub fn with_next_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.next_cursor = Some(cursor.into());
+
+        self
+    }
+
+    pub fn with_total_estimate(mut self, total: usize) -> Self {
+        self.total_estimate = Some(total);
+
+        self
+    }
+
+    pub fn get_items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn get_next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+
+    pub fn get_total_estimate(&self) -> Option<usize> {
+        self.total_estimate
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total_estimate: self.total_estimate,
+        }
+    }
+}
+
+impl<T: PageItemPort + Clone> Page<T> {
+    // Pages an in-memory collection, ties are ordered by cursor so pages stay stable.
+    pub fn from_slice(items: &[T], request: &PageRequest) -> Result<Self, Error> {
+        if request.get_limit() == 0 {
+            return Err("Page limit must be greater than zero".into());
+        }
+
+        let sort = request.get_sort();
+
+        let mut sorted = Vec::with_capacity(items.len());
+
+        for item in items {
+            let values = sort
+                .iter()
+                .map(|sort| {
+                    item.get_sort_value(sort.get_field())
+                        .ok_or_else(|| format!("Cant sort page by field: {}", sort.get_field()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            sorted.push((values, item.get_cursor(), item));
+        }
+
+        sorted.sort_by(|(left_values, left_id, _), (right_values, right_id, _)| {
+            compare(sort, (left_values, left_id), (right_values, right_id))
+        });
+
+        let start = match request.get_position() {
+            PagePosition::Offset(offset) => *offset,
+            PagePosition::Cursor(cursor) => {
+                let cursor = PageCursor::decode(cursor)?;
+
+                if cursor.get_sort() != sort {
+                    return Err("Page cursor was created for another sort".into());
+                }
+
+                // Seeks past the cursor, so it stays valid when its item was removed.
+                sorted.partition_point(|(values, id, _)| {
+                    compare(sort, (values, id), (cursor.get_values(), cursor.get_id())).is_le()
+                })
+            }
+        };
+
+        let start = start.min(sorted.len());
+
+        let end = start.saturating_add(request.get_limit()).min(sorted.len());
+
+        let mut page = Self::new(
+            sorted[start..end]
+                .iter()
+                .map(|(_, _, item)| (*item).clone())
+                .collect(),
+        )
+        .with_total_estimate(sorted.len());
+
+        if end < sorted.len() {
+            let (values, id, _) = &sorted[end - 1];
+
+            let cursor = PageCursor::new(sort.to_vec(), values.clone(), id.clone());
+
+            page = page.with_next_cursor(cursor.encode());
+        }
+
+        Ok(page)
+    }
+}
+
+fn compare(
+    sort: &[PageSort],
+    (left_values, left_id): (&[SortValue], &str),
+    (right_values, right_id): (&[SortValue], &str),
+) -> Ordering {
+    for ((sort, left), right) in sort.iter().zip(left_values).zip(right_values) {
+        let ordering = match sort.get_direction() {
+            SortDirection::Ascending => left.cmp(right),
+            SortDirection::Descending => left.cmp(right).reverse(),
+        };
+
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+
+    left_id.cmp(right_id)
+}
@@ -0,0 +1,171 @@
+use kti_cqrs_rs::errors::error::Error;
+
+use super::{page_sort::PageSort, sort_direction::SortDirection, sort_value::SortValue};
+
+// Points right after an item by its sort values & id, so the next page is found by
+// comparison even when that item is gone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageCursor {
+    sort: Vec<PageSort>,
+    values: Vec<SortValue>,
+    id: String,
+}
+
+impl PageCursor {
+    pub fn new(sort: Vec<PageSort>, values: Vec<SortValue>, id: impl Into<String>) -> Self {
+        Self {
+            sort,
+            values,
+            id: id.into(),
+        }
+    }
+
+    pub fn get_sort(&self) -> &[PageSort] {
+        &self.sort
+    }
+
+    pub fn get_values(&self) -> &[SortValue] {
+        &self.values
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    // `field:direction:value|...|id`, every part is escaped.
+    pub fn encode(&self) -> String {
+        let mut parts = self
+            .sort
+            .iter()
+            .zip(&self.values)
+            .map(|(sort, value)| {
+                let direction = match sort.get_direction() {
+                    SortDirection::Ascending => "asc",
+                    SortDirection::Descending => "desc",
+                };
+
+                format!(
+                    "{}:{}:{}",
+                    escape(sort.get_field()),
+                    direction,
+                    escape(&value.encode())
+                )
+            })
+            .collect::<Vec<_>>();
+
+        parts.push(escape(&self.id));
+
+        parts.join("|")
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, Error> {
+        let invalid = || format!("Invalid page cursor: {}", cursor);
+
+        let mut parts = cursor.split('|').collect::<Vec<_>>();
+
+        let id = unescape(parts.pop().ok_or_else(invalid)?).ok_or_else(invalid)?;
+
+        let mut sort = Vec::with_capacity(parts.len());
+
+        let mut values = Vec::with_capacity(parts.len());
+
+        for part in parts {
+            let [field, direction, value] = part.split(':').collect::<Vec<_>>()[..] else {
+                return Err(invalid().into());
+            };
+
+            let direction = match direction {
+                "asc" => SortDirection::Ascending,
+                "desc" => SortDirection::Descending,
+                _ => return Err(invalid().into()),
+            };
+
+            sort.push(PageSort::new(
+                unescape(field).ok_or_else(invalid)?,
+                direction,
+            ));
+
+            values.push(SortValue::decode(&unescape(value).ok_or_else(invalid)?)?);
+        }
+
+        Ok(Self::new(sort, values, id))
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '%' => escaped.push_str("%25"),
+            ':' => escaped.push_str("%3A"),
+            '|' => escaped.push_str("%7C"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            unescaped.push(char);
+
+            continue;
+        }
+
+        let code = [chars.next()?, chars.next()?];
+
+        unescaped.push(match code {
+            ['2', '5'] => '%',
+            ['3', 'A'] => ':',
+            ['7', 'C'] => '|',
+            _ => return None,
+        });
+    }
+
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{page_sort::PageSort, sort_value::SortValue};
+
+    use super::PageCursor;
+
+    #[test]
+    fn should_encode_and_decode_page_cursor() {
+        let cursor = PageCursor::new(
+            vec![PageSort::descending("na|me:"), PageSort::ascending("age")],
+            vec![
+                SortValue::Text("50%|a:b".to_string()),
+                SortValue::Integer(-3),
+            ],
+            "id|1",
+        );
+
+        let encoded = cursor.encode();
+
+        assert_eq!(
+            PageCursor::decode(&encoded).expect("Cant decode cursor"),
+            cursor
+        );
+
+        let without_sort = PageCursor::new(Vec::new(), Vec::new(), "1");
+
+        assert_eq!(
+            PageCursor::decode(&without_sort.encode()).expect("Cant decode cursor"),
+            without_sort
+        );
+
+        assert!(PageCursor::decode("name:up:tA|1").is_err());
+        assert!(PageCursor::decode("name:asc|1").is_err());
+        assert!(PageCursor::decode("name:asc:x1|1").is_err());
+        assert!(PageCursor::decode("name:asc:tA|%2").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PagePosition {
+    Offset(usize),
+    Cursor(String),
+}
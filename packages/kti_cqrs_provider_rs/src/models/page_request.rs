@@ -0,0 +1,48 @@
+use super::{page_position::PagePosition, page_sort::PageSort};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PageRequest {
+    position: PagePosition,
+    limit: usize,
+    sort: Vec<PageSort>,
+}
+
+impl PageRequest {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            position: PagePosition::Offset(0),
+            limit,
+            sort: Vec::new(),
+        }
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.position = PagePosition::Offset(offset);
+
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.position = PagePosition::Cursor(cursor.into());
+
+        self
+    }
+
+    pub fn with_sort(mut self, sort: PageSort) -> Self {
+        self.sort.push(sort);
+
+        self
+    }
+
+    pub fn get_position(&self) -> &PagePosition {
+        &self.position
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn get_sort(&self) -> &[PageSort] {
+        &self.sort
+    }
+}
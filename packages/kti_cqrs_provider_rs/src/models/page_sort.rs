@@ -0,0 +1,32 @@
+use super::sort_direction::SortDirection;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PageSort {
+    field: String,
+    direction: SortDirection,
+}
+
+impl PageSort {
+    pub fn new(field: impl Into<String>, direction: SortDirection) -> Self {
+        Self {
+            field: field.into(),
+            direction,
+        }
+    }
+
+    pub fn ascending(field: impl Into<String>) -> Self {
+        Self::new(field, SortDirection::Ascending)
+    }
+
+    pub fn descending(field: impl Into<String>) -> Self {
+        Self::new(field, SortDirection::Descending)
+    }
+
+    pub fn get_field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn get_direction(&self) -> SortDirection {
+        self.direction
+    }
+}
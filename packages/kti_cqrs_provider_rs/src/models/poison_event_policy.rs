@@ -0,0 +1,12 @@
+// What a projection does with an event it fails to apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoisonEventPolicy {
+    // The checkpoint stays at the event, every catch-up retries it.
+    #[default]
+    Block,
+    // After failing in as many catch-ups, the event is recorded as a `SkippedEvent`,
+    // reported & the checkpoint moves past it.
+    Skip {
+        attempts: u32,
+    },
+}
@@ -0,0 +1,61 @@
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Principal {
+    user_id: String,
+    tenant_id: Option<String>,
+}
+
+impl Principal {
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            tenant_id: None,
+        }
+    }
+
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+
+        self
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+
+    // Prefixes `key` with the tenant & user id, so shared results don't leak between principals.
+    // Both are length prefixed, so no two principals scope a key alike, `-` is no tenant.
+    pub fn scope(&self, key: &str) -> String {
+        let tenant = match self.get_tenant_id() {
+            Some(tenant_id) => format!("{}:{}", tenant_id.len(), tenant_id),
+            None => "-".to_string(),
+        };
+
+        format!("{}{}:{}/{}", tenant, self.user_id.len(), self.user_id, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Principal;
+
+    #[test]
+    fn should_scope_keys_by_principal_unambiguously() {
+        let scopes = [
+            Principal::new("admin").scope("key"),
+            Principal::new("admin").with_tenant("").scope("key"),
+            Principal::new("b/admin").with_tenant("a").scope("key"),
+            Principal::new("admin").with_tenant("a/b").scope("key"),
+            Principal::new("admin").with_tenant("a").scope("b/key"),
+        ];
+
+        let unique = scopes.iter().collect::<HashSet<_>>();
+
+        assert_eq!(unique.len(), scopes.len());
+    }
+}
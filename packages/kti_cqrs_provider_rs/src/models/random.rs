@@ -0,0 +1,42 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+// SplitMix64 sequence seeded once per process, spreads retries & ticks and fills the
+// random part of message ids. Not meant for secrets.
+static STATE: LazyLock<AtomicU64> =
+    LazyLock::new(|| AtomicU64::new(RandomState::new().build_hasher().finish()));
+
+pub struct Random;
+
+impl Random {
+    pub fn next_u64() -> u64 {
+        let mut value = STATE
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        value ^ (value >> 31)
+    }
+
+    // Returns a value in `[0, 1)`.
+    pub fn next_f64() -> f64 {
+        (Self::next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn duration_up_to(max: Duration) -> Duration {
+        if max.is_zero() {
+            return Duration::ZERO;
+        }
+
+        max.mul_f64(Self::next_f64())
+    }
+}
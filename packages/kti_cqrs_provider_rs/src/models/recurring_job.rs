@@ -0,0 +1,81 @@
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::{errors::error::Error, ports::handler::command_handler_port::CommandHandlerPort};
+
+use crate::provider::cqrs_provider::CqrsProvider;
+
+use super::{cron_schedule::CronSchedule, missed_run_policy::MissedRunPolicy};
+
+pub type RecurringCommand = Arc<
+    dyn Fn(CqrsProvider) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> + Send + Sync,
+>;
+
+#[derive(Clone)]
+pub struct RecurringJob {
+    name: &'static str,
+    schedule: CronSchedule,
+    command: RecurringCommand,
+    jitter: Duration,
+    missed: MissedRunPolicy,
+}
+
+impl RecurringJob {
+    pub fn new<C>(
+        name: &'static str,
+        schedule: CronSchedule,
+        factory: impl Fn() -> Box<C> + Send + Sync + 'static,
+    ) -> Self
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: Send,
+    {
+        let factory = Arc::new(factory);
+
+        let command: RecurringCommand = Arc::new(move |provider| {
+            let command = factory();
+
+            Box::pin(async move { provider.send_command(command).await.map(|_| ()) })
+        });
+
+        Self {
+            name,
+            schedule,
+            command,
+            jitter: Duration::ZERO,
+            missed: MissedRunPolicy::default(),
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+
+        self
+    }
+
+    pub fn with_missed_run_policy(mut self, missed: MissedRunPolicy) -> Self {
+        self.missed = missed;
+
+        self
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_schedule(&self) -> &CronSchedule {
+        &self.schedule
+    }
+
+    pub fn get_command(&self) -> RecurringCommand {
+        self.command.clone()
+    }
+
+    pub fn get_jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    pub fn get_missed_run_policy(&self) -> MissedRunPolicy {
+        self.missed
+    }
+}
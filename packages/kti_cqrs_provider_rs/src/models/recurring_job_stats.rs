@@ -0,0 +1,73 @@
+use std::{
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+#[derive(Default)]
+pub struct RecurringJobStats {
+    running: AtomicBool,
+    runs: AtomicU64,
+    skipped: AtomicU64,
+    missed: AtomicU64,
+    next_run: Mutex<Option<SystemTime>>,
+}
+
+impl RecurringJobStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    pub fn get_runs(&self) -> u64 {
+        self.runs.load(Ordering::Acquire)
+    }
+
+    pub fn get_skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Acquire)
+    }
+
+    pub fn get_missed(&self) -> u64 {
+        self.missed.load(Ordering::Acquire)
+    }
+
+    pub fn get_next_run(&self) -> Option<SystemTime> {
+        *self.next_run.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn try_start(&self) -> bool {
+        let started = self
+            .running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+
+        if !started {
+            self.skipped.fetch_add(1, Ordering::AcqRel);
+        }
+
+        started
+    }
+
+    pub(crate) fn finish(&self) {
+        self.runs.fetch_add(1, Ordering::AcqRel);
+
+        self.running.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn add_missed(&self, missed: u64) {
+        self.missed.fetch_add(missed, Ordering::AcqRel);
+    }
+
+    pub(crate) fn set_next_run(&self, next_run: Option<SystemTime>) {
+        *self.next_run.lock().unwrap_or_else(PoisonError::into_inner) = next_run;
+    }
+}
@@ -0,0 +1,28 @@
+use std::future::Future;
+
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::OnceCell;
+
+// Caches an optional provider on first use, a default included. The container isn't asked
+// again, so a token injected after the first dispatch is ignored.
+pub struct ResolvedProvider<T> {
+    cell: OnceCell<T>,
+}
+
+impl<T> Default for ResolvedProvider<T> {
+    fn default() -> Self {
+        Self {
+            cell: OnceCell::new(),
+        }
+    }
+}
+
+impl<T> ResolvedProvider<T> {
+    pub async fn get_or_resolve<F, R>(&self, resolve: F) -> Result<&T, Error>
+    where
+        F: FnOnce() -> R,
+        R: Future<Output = Result<T, Error>>,
+    {
+        self.cell.get_or_try_init(resolve).await
+    }
+}
@@ -0,0 +1,119 @@
+use std::{sync::Arc, time::Duration};
+
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::errors::transient_error::TransientError;
+
+use super::{backoff::Backoff, random::Random};
+
+type RetryablePredicate = Arc<dyn Fn(&Error) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: f64,
+    retryable: RetryablePredicate,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::None,
+            jitter: 0.0,
+            retryable: Arc::new(TransientError::is_transient),
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_fixed_backoff(mut self, delay: Duration) -> Self {
+        self.backoff = Backoff::Fixed(delay);
+
+        self
+    }
+
+    pub fn with_exponential_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = Backoff::Exponential { initial, max };
+
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+
+        self
+    }
+
+    // Replaces the default, which only retries errors wrapped in `TransientError`.
+    pub fn with_retryable<F>(mut self, retryable: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(retryable);
+
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn get_backoff(&self) -> Backoff {
+        self.backoff
+    }
+
+    pub fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && (self.retryable)(error)
+    }
+
+    pub fn get_delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff.get_delay(attempt);
+
+        if self.jitter == 0.0 {
+            return delay;
+        }
+
+        delay.mul_f64(1.0 - self.jitter * Random::next_f64())
+    }
+
+    pub async fn run<F, R, O>(&self, operation: F) -> Result<O, Error>
+    where
+        F: FnMut() -> R,
+        R: Future<Output = Result<O, Error>>,
+    {
+        self.run_counted(operation).await.0
+    }
+
+    pub async fn run_counted<F, R, O>(&self, mut operation: F) -> (Result<O, Error>, u32)
+    where
+        F: FnMut() -> R,
+        R: Future<Output = Result<O, Error>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            let error = match operation().await {
+                Ok(output) => return (Ok(output), attempt),
+                Err(error) => error,
+            };
+
+            if !self.should_retry(attempt, &error) {
+                return (Err(error), attempt);
+            }
+
+            tokio::time::sleep(self.get_delay(attempt)).await;
+
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::SystemTime;
+
+use super::saga_status::SagaStatus;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SagaRecord {
+    saga: String,
+    correlation_id: String,
+    status: SagaStatus,
+    state: String,
+    error: Option<String>,
+    updated_at: SystemTime,
+}
+
+impl SagaRecord {
+    pub fn new(saga: &str, correlation_id: &str, status: SagaStatus, state: String) -> Self {
+        Self {
+            saga: saga.to_string(),
+            correlation_id: correlation_id.to_string(),
+            status,
+            state,
+            error: None,
+            updated_at: SystemTime::now(),
+        }
+    }
+
+    pub fn with_error(mut self, error: String) -> Self {
+        self.error = Some(error);
+
+        self
+    }
+
+    pub fn get_saga(&self) -> &str {
+        &self.saga
+    }
+
+    pub fn get_correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+
+    pub fn get_status(&self) -> SagaStatus {
+        self.status
+    }
+
+    pub fn get_state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn get_updated_at(&self) -> SystemTime {
+        self.updated_at
+    }
+}
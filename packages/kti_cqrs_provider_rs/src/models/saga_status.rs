@@ -0,0 +1,13 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SagaStatus {
+    Running,
+    Completed,
+    Compensating,
+    Compensated,
+}
+
+impl SagaStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Compensated)
+    }
+}
@@ -0,0 +1,58 @@
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownReport {
+    aborted: Vec<u64>,
+    cancelled_schedules: Vec<u64>,
+    aborted_schedules: Vec<u64>,
+    aborted_jobs: Vec<&'static str>,
+}
+
+impl ShutdownReport {
+    pub fn new(aborted: Vec<u64>) -> Self {
+        Self {
+            aborted,
+            cancelled_schedules: Vec::new(),
+            aborted_schedules: Vec::new(),
+            aborted_jobs: Vec::new(),
+        }
+    }
+
+    // Schedules that were not due yet, persisted ones can be restored.
+    pub fn with_cancelled_schedules(mut self, cancelled: Vec<u64>) -> Self {
+        self.cancelled_schedules = cancelled;
+
+        self
+    }
+
+    pub fn with_aborted_schedules(mut self, aborted: Vec<u64>) -> Self {
+        self.aborted_schedules = aborted;
+
+        self
+    }
+
+    // Recurring jobs whose run was still in progress.
+    pub fn with_aborted_jobs(mut self, aborted: Vec<&'static str>) -> Self {
+        self.aborted_jobs = aborted;
+
+        self
+    }
+
+    pub fn get_aborted(&self) -> &[u64] {
+        &self.aborted
+    }
+
+    pub fn get_cancelled_schedules(&self) -> &[u64] {
+        &self.cancelled_schedules
+    }
+
+    pub fn get_aborted_schedules(&self) -> &[u64] {
+        &self.aborted_schedules
+    }
+
+    pub fn get_aborted_jobs(&self) -> &[&'static str] {
+        &self.aborted_jobs
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.aborted.is_empty() && self.aborted_schedules.is_empty() && self.aborted_jobs.is_empty()
+    }
+}
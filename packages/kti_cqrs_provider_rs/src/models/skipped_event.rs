@@ -0,0 +1,44 @@
+use std::time::SystemTime;
+
+use super::stored_event::StoredEvent;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedEvent {
+    projection: &'static str,
+    event: StoredEvent,
+    error: String,
+    attempts: u32,
+    skipped_at: SystemTime,
+}
+
+impl SkippedEvent {
+    pub fn new(projection: &'static str, event: StoredEvent, error: String, attempts: u32) -> Self {
+        Self {
+            projection,
+            event,
+            error,
+            attempts,
+            skipped_at: SystemTime::now(),
+        }
+    }
+
+    pub fn get_projection(&self) -> &'static str {
+        self.projection
+    }
+
+    pub fn get_event(&self) -> &StoredEvent {
+        &self.event
+    }
+
+    pub fn get_error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn get_skipped_at(&self) -> SystemTime {
+        self.skipped_at
+    }
+}
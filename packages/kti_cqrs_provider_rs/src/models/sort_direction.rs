@@ -0,0 +1,6 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
@@ -0,0 +1,31 @@
+use kti_cqrs_rs::errors::error::Error;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SortValue {
+    Bool(bool),
+    Integer(i64),
+    Text(String),
+}
+
+impl SortValue {
+    pub fn encode(&self) -> String {
+        match self {
+            Self::Bool(value) => format!("b{}", value),
+            Self::Integer(value) => format!("i{}", value),
+            Self::Text(value) => format!("t{}", value),
+        }
+    }
+
+    pub fn decode(value: &str) -> Result<Self, Error> {
+        let invalid = || format!("Invalid sort value: {}", value);
+
+        let (tag, rest) = value.split_at_checked(1).ok_or_else(invalid)?;
+
+        match tag {
+            "b" => Ok(Self::Bool(rest.parse().map_err(|_| invalid())?)),
+            "i" => Ok(Self::Integer(rest.parse().map_err(|_| invalid())?)),
+            "t" => Ok(Self::Text(rest.to_string())),
+            _ => Err(invalid().into()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::SystemTime;
+
+use super::{event_data::EventData, message_id::MessageId};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredEvent {
+    position: u64,
+    stream_id: String,
+    version: u64,
+    data: EventData,
+    recorded_at: SystemTime,
+}
+
+impl StoredEvent {
+    pub fn new(
+        position: u64,
+        stream_id: &str,
+        version: u64,
+        data: EventData,
+        recorded_at: SystemTime,
+    ) -> Self {
+        Self {
+            position,
+            stream_id: stream_id.to_string(),
+            version,
+            data,
+            recorded_at,
+        }
+    }
+
+    pub fn get_position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn get_stream_id(&self) -> &str {
+        &self.stream_id
+    }
+
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn get_name(&self) -> &str {
+        self.data.get_name()
+    }
+
+    pub fn get_payload(&self) -> &str {
+        self.data.get_payload()
+    }
+
+    pub fn get_message_id(&self) -> Option<MessageId> {
+        self.data.get_message_id()
+    }
+
+    pub fn get_recorded_at(&self) -> SystemTime {
+        self.recorded_at
+    }
+}
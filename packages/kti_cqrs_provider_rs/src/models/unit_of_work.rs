@@ -0,0 +1,98 @@
+use std::{
+    pin::Pin,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::provider::cqrs_provider::CqrsProvider;
+
+use super::deferred_message::DeferredMessage;
+
+pub type DeferredEvent =
+    Box<dyn FnOnce(CqrsProvider) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> + Send>;
+
+// A staged unit of work stores outbox records as they are raised, for commands whose output
+// can't be held while the records are stored. They are removed again if the command fails.
+pub struct UnitOfWork {
+    messages: Mutex<Option<Vec<DeferredMessage>>>,
+    staged: Option<Mutex<Vec<u64>>>,
+}
+
+impl Default for UnitOfWork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnitOfWork {
+    pub fn new() -> Self {
+        Self {
+            messages: Mutex::new(Some(Vec::new())),
+            staged: None,
+        }
+    }
+
+    pub fn staged() -> Self {
+        Self {
+            staged: Some(Mutex::new(Vec::new())),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_staged(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.lock_messages().is_some()
+    }
+
+    pub fn defer(&self, message: DeferredMessage) -> Result<(), DeferredMessage> {
+        match self.lock_messages().as_mut() {
+            Some(messages) => {
+                messages.push(message);
+
+                Ok(())
+            }
+            None => Err(message),
+        }
+    }
+
+    // Defers the delivery of an outbox record that is already stored.
+    pub fn stage(&self, id: Option<u64>, event: DeferredEvent) -> Result<(), DeferredEvent> {
+        let mut messages = self.lock_messages();
+
+        let Some(messages) = messages.as_mut() else {
+            return Err(event);
+        };
+
+        messages.push(DeferredMessage::Event(event));
+
+        if let (Some(id), Some(staged)) = (id, &self.staged) {
+            staged
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(id);
+        }
+
+        Ok(())
+    }
+
+    pub fn take_staged(&self) -> Vec<u64> {
+        self.staged
+            .as_ref()
+            .map(|staged| {
+                std::mem::take(&mut *staged.lock().unwrap_or_else(PoisonError::into_inner))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn close(&self) -> Vec<DeferredMessage> {
+        self.lock_messages().take().unwrap_or_default()
+    }
+
+    fn lock_messages(&self) -> MutexGuard<'_, Option<Vec<DeferredMessage>>> {
+        self.messages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,9 @@
+use kti_cqrs_rs::errors::error::Error;
+
+pub trait AggregateEventPort: Sized + Send + Sync + 'static {
+    fn get_name(&self) -> &'static str;
+
+    fn encode(&self) -> Result<String, Error>;
+
+    fn decode(name: &str, payload: &str) -> Result<Self, Error>;
+}
@@ -0,0 +1,9 @@
+use super::aggregate_event_port::AggregateEventPort;
+
+pub trait AggregatePort: Default + Send + Sync + 'static {
+    type Event: AggregateEventPort;
+
+    fn get_name() -> &'static str;
+
+    fn apply(&mut self, event: &Self::Event);
+}
@@ -0,0 +1,18 @@
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+#[async_trait]
+pub trait BatchLoaderPort: Send + Sync + 'static {
+    type Key: Clone + Eq + Hash + Send + Sync + 'static;
+    type Value: Clone + Send + Sync + 'static;
+
+    // Keys missing from the result resolve to `None`.
+    async fn load(
+        &self,
+        keys: Vec<Self::Key>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<HashMap<Self::Key, Self::Value>, Error>;
+}
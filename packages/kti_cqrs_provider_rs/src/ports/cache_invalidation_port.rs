@@ -0,0 +1,3 @@
+pub trait CacheInvalidationPort {
+    fn get_invalidated_tags(&self) -> Vec<String>;
+}
@@ -0,0 +1,7 @@
+use crate::models::cache_policy::CachePolicy;
+
+use super::query_message_port::QueryMessagePort;
+
+pub trait CacheableQueryPort: QueryMessagePort<Output: Clone + Sync> {
+    fn get_cache_policy(&self) -> CachePolicy;
+}
@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+#[async_trait]
+pub trait CheckpointStorePort: Send + Sync {
+    async fn load(&self, projection: &str) -> Result<u64, Error>;
+
+    async fn save(&self, projection: &str, position: u64) -> Result<(), Error>;
+}
@@ -0,0 +1,10 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ClockPort: Send + Sync {
+    fn now(&self) -> SystemTime;
+
+    async fn sleep_until(&self, at: SystemTime);
+}
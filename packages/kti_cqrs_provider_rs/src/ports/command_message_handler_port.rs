@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use super::command_message_port::CommandMessagePort;
+
+#[async_trait]
+pub trait CommandMessageHandlerPort<M: CommandMessagePort>: Send + Sync {
+    async fn handle(&self, command: &M, context: Arc<dyn ContextPort>) -> Result<M::Output, Error>;
+}
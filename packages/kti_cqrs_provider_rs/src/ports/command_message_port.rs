@@ -0,0 +1,7 @@
+pub trait CommandMessagePort: Send + Sync + 'static {
+    type Output: Send + 'static;
+
+    fn get_invalidated_tags(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
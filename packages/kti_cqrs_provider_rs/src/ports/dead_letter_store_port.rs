@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::{dead_letter::DeadLetter, message_id::MessageId};
+
+// Letters with an event are kept in process, a persistent store may skip them.
+#[async_trait]
+pub trait DeadLetterStorePort: Send + Sync {
+    async fn push(&self, letter: DeadLetter) -> Result<(), Error>;
+
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error>;
+
+    async fn get(&self, id: MessageId) -> Result<Option<DeadLetter>, Error>;
+
+    async fn remove(&self, id: MessageId) -> Result<Option<DeadLetter>, Error>;
+
+    async fn purge(&self) -> Result<usize, Error>;
+}
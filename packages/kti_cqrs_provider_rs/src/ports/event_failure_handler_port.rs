@@ -0,0 +1,8 @@
+use async_trait::async_trait;
+
+use crate::models::event_failure::EventFailure;
+
+#[async_trait]
+pub trait EventFailureHandlerPort: Send + Sync {
+    async fn handle(&self, failure: EventFailure);
+}
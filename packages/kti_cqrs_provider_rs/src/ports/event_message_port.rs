@@ -0,0 +1,5 @@
+pub trait EventMessagePort: Send + Sync + 'static {
+    fn get_partition_key(&self) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::{
+    event_data::EventData, expected_version::ExpectedVersion, stored_event::StoredEvent,
+};
+
+#[async_trait]
+pub trait EventStorePort: Send + Sync {
+    async fn append(
+        &self,
+        stream_id: &str,
+        expected: ExpectedVersion,
+        events: Vec<EventData>,
+    ) -> Result<u64, Error>;
+
+    async fn read_stream(
+        &self,
+        stream_id: &str,
+        after_version: u64,
+    ) -> Result<Vec<StoredEvent>, Error>;
+
+    async fn read_all(&self, after_position: u64, limit: usize) -> Result<Vec<StoredEvent>, Error>;
+}
@@ -0,0 +1,16 @@
+use std::{any::type_name, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use super::event_message_port::EventMessagePort;
+
+#[async_trait]
+pub trait EventSubscriberPort<E: EventMessagePort>: Send + Sync {
+    fn get_name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
+    async fn handle(&self, event: &E, context: Arc<dyn ContextPort>) -> Result<(), Error>;
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::idempotency_status::IdempotencyStatus;
+
+// Leases belong to the `owner` that began them, renewing, completing and releasing a key
+// compare it, so an owner whose lease expired can't touch the key of the next one.
+#[async_trait]
+pub trait IdempotencyStorePort: Send + Sync {
+    // Atomically marks an unknown or expired key as in progress for `lease` and returns `Started`.
+    async fn begin(
+        &self,
+        key: &str,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<IdempotencyStatus, Error>;
+
+    async fn renew(&self, key: &str, owner: &str, lease: Duration) -> Result<bool, Error>;
+
+    async fn complete(
+        &self,
+        key: &str,
+        owner: &str,
+        value: String,
+        ttl: Duration,
+    ) -> Result<bool, Error>;
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool, Error>;
+}
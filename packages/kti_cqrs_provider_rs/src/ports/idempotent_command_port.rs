@@ -0,0 +1,7 @@
+use super::{
+    command_message_port::CommandMessagePort, idempotent_output_port::IdempotentOutputPort,
+};
+
+pub trait IdempotentCommandPort: CommandMessagePort<Output: IdempotentOutputPort> {
+    fn get_idempotency_key(&self) -> String;
+}
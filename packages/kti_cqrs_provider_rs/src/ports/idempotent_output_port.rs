@@ -0,0 +1,77 @@
+use std::str::FromStr;
+
+use kti_cqrs_rs::errors::error::Error;
+
+// Outcomes are stored encoded, so idempotency stores may persist them.
+pub trait IdempotentOutputPort: Clone + Send + Sync + Sized + 'static {
+    fn encode(&self) -> Result<String, Error>;
+
+    fn decode(payload: &str) -> Result<Self, Error>;
+}
+
+impl IdempotentOutputPort for () {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    fn decode(_: &str) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+impl IdempotentOutputPort for String {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.clone())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        Ok(payload.to_string())
+    }
+}
+
+impl IdempotentOutputPort for bool {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.to_string())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        parse(payload)
+    }
+}
+
+impl IdempotentOutputPort for u32 {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.to_string())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        parse(payload)
+    }
+}
+
+impl IdempotentOutputPort for u64 {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.to_string())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        parse(payload)
+    }
+}
+
+impl IdempotentOutputPort for i64 {
+    fn encode(&self) -> Result<String, Error> {
+        Ok(self.to_string())
+    }
+
+    fn decode(payload: &str) -> Result<Self, Error> {
+        parse(payload)
+    }
+}
+
+fn parse<T>(payload: &str) -> Result<T, Error>
+where
+    T: FromStr<Err: std::error::Error + Send + Sync + 'static>,
+{
+    Ok(payload.parse()?)
+}
@@ -0,0 +1,26 @@
+use std::{future::Future, pin::Pin};
+
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::message_info::MessageInfo;
+
+pub type MiddlewareNext<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+#[async_trait]
+pub trait MiddlewarePort: Send + Sync {
+    async fn before(&self, _message: &MessageInfo) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // Wraps the inner middlewares and the handler, `next` resolves once the handler finished.
+    // Needs a `Send` output, so the generic `ServiceBusPort` and bus `send` skip it.
+    async fn around(&self, _message: &MessageInfo, next: MiddlewareNext<'_>) -> Result<(), Error> {
+        next.await
+    }
+
+    // Sync on purpose: bus outputs have no `Send` bound, so they can't be held across `.await`.
+    fn after(&self, _message: &MessageInfo, _result: Result<(), &Error>) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+pub mod aggregate_event_port;
+pub mod aggregate_port;
+pub mod batch_loader_port;
+pub mod cache_invalidation_port;
+pub mod cacheable_query_port;
+pub mod checkpoint_store_port;
+pub mod clock_port;
+pub mod command_message_handler_port;
+pub mod command_message_port;
+pub mod dead_letter_store_port;
+pub mod event_failure_handler_port;
+pub mod event_message_port;
+pub mod event_store_port;
+pub mod event_subscriber_port;
+pub mod idempotency_store_port;
+pub mod idempotent_command_port;
+pub mod idempotent_output_port;
+pub mod middleware_port;
+pub mod outbox_message_port;
+pub mod outbox_store_port;
+pub mod page_item_port;
+pub mod persistent_command_port;
+pub mod projection_port;
+pub mod query_cache_port;
+pub mod query_message_handler_port;
+pub mod query_message_port;
+pub mod saga_handler_port;
+pub mod saga_port;
+pub mod saga_store_port;
+pub mod schedule_store_port;
+pub mod single_flight_query_port;
+pub mod stream_query_handler_port;
@@ -0,0 +1,11 @@
+use kti_cqrs_rs::errors::error::Error;
+
+use super::event_message_port::EventMessagePort;
+
+pub trait OutboxMessagePort: EventMessagePort + Sized {
+    fn get_name() -> &'static str;
+
+    fn encode(&self) -> Result<String, Error>;
+
+    fn decode(payload: &str) -> Result<Self, Error>;
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::{message_envelope::MessageEnvelope, outbox_record::OutboxRecord};
+
+#[async_trait]
+pub trait OutboxStorePort: Send + Sync {
+    async fn append(
+        &self,
+        name: &str,
+        payload: String,
+        envelope: Option<MessageEnvelope>,
+    ) -> Result<OutboxRecord, Error>;
+
+    // Stores the records of a committed command at once, either all of them or none.
+    async fn append_all(
+        &self,
+        records: Vec<(&str, String, Option<MessageEnvelope>)>,
+    ) -> Result<Vec<OutboxRecord>, Error>;
+
+    async fn list(&self) -> Result<Vec<OutboxRecord>, Error>;
+
+    async fn remove(&self, id: u64) -> Result<bool, Error>;
+}
@@ -0,0 +1,9 @@
+use crate::models::sort_value::SortValue;
+
+pub trait PageItemPort {
+    // Unique and stable, breaks ties between items with equal sort values.
+    fn get_cursor(&self) -> String;
+
+    // `None` when the item can't be sorted by the field.
+    fn get_sort_value(&self, field: &str) -> Option<SortValue>;
+}
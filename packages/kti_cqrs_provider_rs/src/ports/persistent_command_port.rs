@@ -0,0 +1,11 @@
+use kti_cqrs_rs::errors::error::Error;
+
+use super::command_message_port::CommandMessagePort;
+
+pub trait PersistentCommandPort: CommandMessagePort + Sized {
+    fn get_name() -> &'static str;
+
+    fn encode(&self) -> Result<String, Error>;
+
+    fn decode(payload: &str) -> Result<Self, Error>;
+}
@@ -0,0 +1,18 @@
+use std::{any::type_name, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::stored_event::StoredEvent;
+
+#[async_trait]
+pub trait ProjectionPort: Send + Sync {
+    fn get_name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
+    async fn apply(&self, event: &StoredEvent, context: Arc<dyn ContextPort>) -> Result<(), Error>;
+
+    async fn reset(&self) -> Result<(), Error>;
+}
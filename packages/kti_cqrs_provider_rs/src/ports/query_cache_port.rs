@@ -0,0 +1,15 @@
+use std::{any::Any, sync::Arc, time::Duration};
+
+pub type CachedValue = Arc<dyn Any + Send + Sync>;
+
+// Sync on purpose: values are in-process `Any`s and invalidation runs right after
+// a command returns, where awaiting would require the command output to be `Send`.
+pub trait QueryCachePort: Send + Sync {
+    fn get(&self, key: &str) -> Option<CachedValue>;
+
+    fn insert(&self, key: &str, value: CachedValue, ttl: Duration, tags: &[String]);
+
+    fn invalidate(&self, tags: &[String]) -> usize;
+
+    fn clear(&self);
+}
@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use super::query_message_port::QueryMessagePort;
+
+#[async_trait]
+pub trait QueryMessageHandlerPort<M: QueryMessagePort>: Send + Sync {
+    async fn handle(&self, query: &M, context: Arc<dyn ContextPort>) -> Result<M::Output, Error>;
+}
@@ -0,0 +1,3 @@
+pub trait QueryMessagePort: Send + Sync + 'static {
+    type Output: Send + 'static;
+}
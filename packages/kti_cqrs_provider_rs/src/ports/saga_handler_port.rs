@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{models::saga_status::SagaStatus, provider::cqrs_provider::CqrsProvider};
+
+use super::{event_message_port::EventMessagePort, saga_port::SagaPort};
+
+#[async_trait]
+pub trait SagaHandlerPort<E: EventMessagePort>: SagaPort {
+    fn get_correlation_id(&self, event: &E) -> String;
+
+    // Moves the state under the saga lock, it is saved before `dispatch` runs.
+    async fn apply(&self, event: &E, state: &mut Self::State) -> Result<SagaStatus, Error>;
+
+    // Issues the follow-up messages of the step outside the saga lock, so they may
+    // route events back to the same saga. They are sent at least once: an event
+    // redelivered after a crash is applied again to the saved state.
+    async fn dispatch(
+        &self,
+        event: &E,
+        state: &Self::State,
+        provider: &CqrsProvider,
+    ) -> Result<(), Error>;
+}
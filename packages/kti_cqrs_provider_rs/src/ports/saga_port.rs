@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::provider::cqrs_provider::CqrsProvider;
+
+#[async_trait]
+pub trait SagaPort: Send + Sync + 'static {
+    type State: Default + Send + Sync;
+
+    fn get_name(&self) -> &'static str;
+
+    fn encode(state: &Self::State) -> Result<String, Error>;
+
+    fn decode(state: &str) -> Result<Self::State, Error>;
+
+    // Undoes the steps recorded in the state after a step failed. Runs under the saga
+    // lock, so it must not wait for events of the same saga instance.
+    async fn compensate(
+        &self,
+        correlation_id: &str,
+        state: &Self::State,
+        provider: &CqrsProvider,
+    ) -> Result<(), Error>;
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::saga_record::SagaRecord;
+
+#[async_trait]
+pub trait SagaStorePort: Send + Sync {
+    async fn load(&self, saga: &str, correlation_id: &str) -> Result<Option<SagaRecord>, Error>;
+
+    async fn save(&self, record: SagaRecord) -> Result<(), Error>;
+
+    async fn list(&self, saga: &str) -> Result<Vec<SagaRecord>, Error>;
+}
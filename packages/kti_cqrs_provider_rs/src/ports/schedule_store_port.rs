@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::scheduled_record::ScheduledRecord;
+
+#[async_trait]
+pub trait ScheduleStorePort: Send + Sync {
+    async fn save(&self, record: ScheduledRecord) -> Result<(), Error>;
+
+    async fn list(&self) -> Result<Vec<ScheduledRecord>, Error>;
+
+    async fn remove(&self, id: u64) -> Result<bool, Error>;
+}
@@ -0,0 +1,5 @@
+use super::query_message_port::QueryMessagePort;
+
+pub trait SingleFlightQueryPort: QueryMessagePort<Output: Clone + Sync> {
+    fn get_single_flight_key(&self) -> String;
+}
@@ -0,0 +1,19 @@
+use std::{pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use futures::Stream;
+use ioc_container_rs::ports::context_port::ContextPort;
+use kti_cqrs_rs::errors::error::Error;
+
+pub type QueryStream<T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>;
+
+#[async_trait]
+pub trait StreamQueryHandlerPort: Send + Sync {
+    type Item: Send + 'static;
+
+    // Items are pulled on demand, a slow consumer holds the handler back.
+    async fn execute(
+        &self,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<QueryStream<Self::Item>, Error>;
+}
@@ -0,0 +1,72 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::ports::batch_loader_port::BatchLoaderPort;
+
+#[derive(Clone)]
+pub struct BatchLoaderProvider {
+    loaders: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    window: Duration,
+}
+
+#[async_trait]
+impl AdapterPort<BatchLoaderProvider> for BatchLoaderProvider {
+    fn token() -> &'static str {
+        "BATCH_LOADER_PROVIDER"
+    }
+}
+
+impl Default for BatchLoaderProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchLoaderProvider {
+    pub fn new() -> Self {
+        Self {
+            loaders: HashMap::new(),
+            window: Duration::from_millis(1),
+        }
+    }
+
+    pub fn with_loader<L: BatchLoaderPort>(mut self, loader: Arc<L>) -> Self {
+        self.loaders.insert(TypeId::of::<L>(), loader);
+
+        self
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn get_loader<L: BatchLoaderPort>(&self) -> Result<Arc<L>, Error> {
+        self.loaders
+            .get(&TypeId::of::<L>())
+            .cloned()
+            .and_then(|loader| loader.downcast::<L>().ok())
+            .ok_or_else(|| format!("Batch loader {} is not registered", type_name::<L>()).into())
+    }
+}
@@ -1,5 +1,9 @@
 use std::sync::Arc;
 
+use crate::models::{
+    deadline::Deadline, message_context::ContextFactory, message_info::MessageInfo,
+    message_kind::MessageKind, resolved_provider::ResolvedProvider, retry_policy::RetryPolicy,
+};
 use async_trait::async_trait;
 use ioc_container_rs::{
     errors::error::Error,
@@ -9,8 +13,11 @@ use kti_cqrs_rs::ports::{
     bus::command_bus_port::CommandBusPort, handler::command_handler_port::CommandHandlerPort,
 };
 
+use super::middleware_provider::MiddlewareProvider;
+
 pub struct CommandBusProvider {
     context: Arc<dyn ContextPort>,
+    pipeline: ResolvedProvider<MiddlewareProvider>,
 }
 
 #[async_trait]
@@ -27,16 +34,80 @@ impl CommandBusPort for CommandBusProvider {
         command: Box<dyn CommandHandlerPort<Context = C, Output = O>>,
         context: C,
     ) -> Result<O, Error> {
-        command.execute(context).await
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::new(MessageKind::Command);
+
+        pipeline
+            .run_without_around(&message, command.execute(context))
+            .await
     }
 }
 
 impl CommandBusProvider {
     pub fn new(context: Arc<dyn ContextPort>) -> Self {
-        Self { context }
+        Self {
+            context,
+            pipeline: ResolvedProvider::default(),
+        }
     }
 
     pub fn get_context(&self) -> Arc<dyn ContextPort> {
         self.context.clone()
     }
+
+    pub async fn get_pipeline(&self) -> Result<&MiddlewareProvider, Error> {
+        self.pipeline
+            .get_or_resolve(|| MiddlewareProvider::resolve(&self.context))
+            .await
+    }
+
+    // Every attempt runs in a fresh context, so a failed attempt leaves nothing behind.
+    pub async fn send_with_retry<O: Send>(
+        &self,
+        command: Box<dyn CommandHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        create_context: ContextFactory,
+        policy: &RetryPolicy,
+    ) -> Result<O, Error> {
+        let pipeline = self.get_pipeline().await?;
+
+        let command = &command;
+        let create_context = &create_context;
+
+        policy
+            .run(|| async move {
+                let context = create_context();
+
+                let message = MessageInfo::from_context(MessageKind::Command, &context);
+
+                let handler = Deadline::enforce(&context, command.execute(context.clone()));
+
+                pipeline.run(&message, handler).await
+            })
+            .await
+    }
+
+    pub async fn send_with_retry_without_around<O>(
+        &self,
+        command: Box<dyn CommandHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        create_context: ContextFactory,
+        policy: &RetryPolicy,
+    ) -> Result<O, Error> {
+        let pipeline = self.get_pipeline().await?;
+
+        let command = &command;
+        let create_context = &create_context;
+
+        policy
+            .run(|| async move {
+                let context = create_context();
+
+                let message = MessageInfo::from_context(MessageKind::Command, &context);
+
+                let handler = Deadline::enforce(&context, command.execute(context.clone()));
+
+                pipeline.run_without_around(&message, handler).await
+            })
+            .await
+    }
 }
@@ -1,29 +1,104 @@
-use std::sync::Arc;
+use std::{
+    any::type_name,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant, SystemTime},
+};
 
 use async_trait::async_trait;
+use futures::future::Either;
 use ioc_container_rs::{
     errors::error::Error,
     ports::{adapter_port::AdapterPort, context_port::ContextPort},
 };
 use kti_cqrs_rs::ports::{
-    bus::{
-        command_bus_port::CommandBusPort, event_bus_port::EventBusPort,
-        query_bus_port::QueryBusPort, service_bus_port::ServiceBusPort,
-    },
+    bus::service_bus_port::ServiceBusPort,
     handler::{
         command_handler_port::CommandHandlerPort, event_handler_port::EventHandlerPort,
         query_handler_port::QueryHandlerPort,
     },
 };
+use tokio::sync::OnceCell;
+
+use crate::{
+    adapters::{
+        boxed_handler_adapter::BoxedHandlerAdapter,
+        projection_event_adapter::ProjectionEventAdapter,
+        registered_command_adapter::RegisteredCommandAdapter,
+        registered_query_adapter::RegisteredQueryAdapter,
+    },
+    models::{
+        aggregate_root::AggregateRoot,
+        cache_policy::CachePolicy,
+        cancellation_token::CancellationToken,
+        data_loader::DataLoader,
+        dead_letter::{DeadLetter, SharedEvent},
+        deadline::Deadline,
+        deferred_message::{DeferredDelivery, DeferredMessage},
+        event_failure::EventFailure,
+        event_handle::EventHandle,
+        message_context::{ContextFactory, MessageContext},
+        message_envelope::MessageEnvelope,
+        message_id::MessageId,
+        message_info::next_message_id,
+        message_kind::MessageKind,
+        outbox_record::OutboxRecord,
+        resolved_provider::ResolvedProvider,
+        retry_policy::RetryPolicy,
+        saga_record::SagaRecord,
+        scheduled_record::ScheduledRecord,
+        shutdown_report::ShutdownReport,
+        unit_of_work::{DeferredEvent, UnitOfWork},
+    },
+    ports::{
+        aggregate_port::AggregatePort,
+        batch_loader_port::BatchLoaderPort,
+        cache_invalidation_port::CacheInvalidationPort,
+        cacheable_query_port::CacheableQueryPort,
+        command_message_port::CommandMessagePort,
+        event_message_port::EventMessagePort,
+        idempotent_command_port::IdempotentCommandPort,
+        idempotent_output_port::IdempotentOutputPort,
+        outbox_message_port::OutboxMessagePort,
+        persistent_command_port::PersistentCommandPort,
+        query_message_port::QueryMessagePort,
+        single_flight_query_port::SingleFlightQueryPort,
+        stream_query_handler_port::{QueryStream, StreamQueryHandlerPort},
+    },
+};
 
 use super::{
-    command_bus_provider::CommandBusProvider, event_bus_provider::EventBusProvider,
-    query_bus_provider::QueryBusProvider,
+    batch_loader_provider::BatchLoaderProvider, command_bus_provider::CommandBusProvider,
+    dead_letter_provider::DeadLetterProvider, event_bus_provider::EventBusProvider,
+    event_failure_provider::EventFailureProvider, event_store_provider::EventStoreProvider,
+    event_subscriptions_provider::EventSubscriptionsProvider,
+    handler_registry_provider::HandlerRegistryProvider, idempotency_provider::IdempotencyProvider,
+    outbox_provider::OutboxProvider, projection_provider::ProjectionProvider,
+    query_bus_provider::QueryBusProvider, query_cache_provider::QueryCacheProvider,
+    recurring_jobs_provider::RecurringJobsProvider, retry_provider::RetryProvider,
+    saga_provider::SagaProvider, scheduler_provider::SchedulerProvider,
+    single_flight_provider::SingleFlightProvider, timeout_provider::TimeoutProvider,
 };
 
+#[derive(Default)]
+struct CqrsBuses {
+    command: OnceCell<Box<CommandBusProvider>>,
+    query: OnceCell<Box<QueryBusProvider>>,
+    event: OnceCell<Box<EventBusProvider>>,
+    registry: ResolvedProvider<HandlerRegistryProvider>,
+    subscriptions: ResolvedProvider<EventSubscriptionsProvider>,
+    retry: ResolvedProvider<RetryProvider>,
+    timeout: ResolvedProvider<TimeoutProvider>,
+    outbox: ResolvedProvider<Option<OutboxProvider>>,
+    projections: ResolvedProvider<Option<ProjectionProvider>>,
+}
+
 #[derive(Clone)]
 pub struct CqrsProvider {
     context: Arc<dyn ContextPort>,
+    buses: Arc<CqrsBuses>,
+    type_name: Option<&'static str>,
+    resumed: Option<MessageEnvelope>,
 }
 
 #[async_trait]
@@ -35,12 +110,1544 @@ impl AdapterPort<CqrsProvider> for CqrsProvider {
 
 impl CqrsProvider {
     pub fn new(context: Arc<dyn ContextPort>) -> Self {
-        Self { context }
+        Self {
+            context,
+            buses: Arc::new(CqrsBuses::default()),
+            type_name: None,
+            resumed: None,
+        }
+    }
+
+    pub fn scoped(&self, context: Arc<dyn ContextPort>) -> Self {
+        Self {
+            context,
+            buses: self.buses.clone(),
+            type_name: None,
+            resumed: None,
+        }
+    }
+
+    // Messages dispatched through the returned provider are caused by the envelope.
+    pub fn with_envelope(&self, envelope: MessageEnvelope) -> Self {
+        let context = MessageContext::new(
+            self.get_context(),
+            self.create_cancellation(),
+            None,
+            envelope,
+            None,
+            None,
+        );
+
+        self.scoped(context)
+    }
+
+    // Messages dispatched through the returned provider are cancelled with the token,
+    // next to their own timeouts.
+    pub fn with_cancellation(&self, cancellation: CancellationToken) -> Self {
+        let envelope = MessageEnvelope::from_context(&self.context)
+            .cloned()
+            .unwrap_or_else(MessageEnvelope::new);
+
+        let context =
+            MessageContext::new(self.get_context(), cancellation, None, envelope, None, None);
+
+        self.scoped(context)
     }
 
     pub fn get_context(&self) -> Arc<dyn ContextPort> {
         self.context.clone()
     }
+
+    pub async fn get_command_bus(&self) -> Result<&CommandBusProvider, Error> {
+        let bus = self
+            .buses
+            .command
+            .get_or_try_init(|| CommandBusProvider::get_adapter(&self.context))
+            .await?;
+
+        Ok(bus)
+    }
+
+    pub async fn get_query_bus(&self) -> Result<&QueryBusProvider, Error> {
+        let bus = self
+            .buses
+            .query
+            .get_or_try_init(|| QueryBusProvider::get_adapter(&self.context))
+            .await?;
+
+        Ok(bus)
+    }
+
+    pub async fn get_event_bus(&self) -> Result<&EventBusProvider, Error> {
+        let bus = self
+            .buses
+            .event
+            .get_or_try_init(|| EventBusProvider::get_adapter(&self.context))
+            .await?;
+
+        Ok(bus)
+    }
+
+    pub async fn get_handler_registry(&self) -> Result<&HandlerRegistryProvider, Error> {
+        self.buses
+            .registry
+            .get_or_resolve(|| HandlerRegistryProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn get_retry_provider(&self) -> Result<&RetryProvider, Error> {
+        self.buses
+            .retry
+            .get_or_resolve(|| RetryProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn get_timeout_provider(&self) -> Result<&TimeoutProvider, Error> {
+        self.buses
+            .timeout
+            .get_or_resolve(|| TimeoutProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn get_outbox_provider(&self) -> Result<Option<&OutboxProvider>, Error> {
+        let outbox = self
+            .buses
+            .outbox
+            .get_or_resolve(|| OutboxProvider::resolve(&self.context))
+            .await?;
+
+        Ok(outbox.as_ref())
+    }
+
+    pub async fn get_projections(&self) -> Result<Option<&ProjectionProvider>, Error> {
+        let projections = self
+            .buses
+            .projections
+            .get_or_resolve(|| ProjectionProvider::resolve(&self.context))
+            .await?;
+
+        Ok(projections.as_ref())
+    }
+
+    // Unlike `ServiceBusPort::command`, a boxed handler of a known type gets its retry
+    // override and the `around` middleware.
+    pub async fn send_command<C>(&self, command: Box<C>) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: Send,
+    {
+        let timeout = self.get_timeout_provider().await?.get_command_timeout();
+
+        self.command_with_deadline(command, timeout, Vec::new())
+            .await
+    }
+
+    // Unlike `ServiceBusPort::query`, runs the `around` middleware.
+    pub async fn send_query<O: Send>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+    ) -> Result<O, Error> {
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        self.query_with_deadline(query, timeout).await
+    }
+
+    // Unlike `ServiceBusPort::event`, applies the retry override of the event type.
+    pub async fn send_event<E>(&self, event: Box<E>) -> Result<(), Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        let policy = self.get_retry_provider().await?.get_policy::<E>().clone();
+
+        let event: SharedEvent = Arc::new(BoxedHandlerAdapter::new(event));
+
+        self.raise(Box::new(move |provider| {
+            Box::pin(async move {
+                provider
+                    .named::<E>()
+                    .publish_event(event, None, policy)
+                    .await
+            })
+        }))
+        .await
+    }
+
+    pub async fn command_with_timeout<C>(
+        &self,
+        command: Box<C>,
+        timeout: Duration,
+    ) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: Send,
+    {
+        self.command_with_deadline(command, Some(timeout), Vec::new())
+            .await
+    }
+
+    pub async fn command_with_invalidation<C>(&self, command: Box<C>) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>>
+            + CacheInvalidationPort
+            + ?Sized
+            + 'static,
+        C::Output: Send,
+    {
+        let tags = command.get_invalidated_tags();
+
+        let timeout = self.get_timeout_provider().await?.get_command_timeout();
+
+        self.command_with_deadline(command, timeout, tags).await
+    }
+
+    pub async fn cached_query<O: Clone + Send + Sync + 'static>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        policy: CachePolicy,
+    ) -> Result<O, Error> {
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        let bus = self.get_query_bus().await?;
+
+        let deadline = self.create_deadline(MessageKind::Query, timeout).await?;
+
+        let context = self.create_message_context(None, deadline);
+
+        bus.send_cached(query, context, &policy).await
+    }
+
+    pub async fn single_flight_query<O: Clone + Send + Sync + 'static>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        key: &str,
+    ) -> Result<O, Error> {
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        let bus = self.get_query_bus().await?;
+
+        let deadline = self.create_deadline(MessageKind::Query, timeout).await?;
+
+        let context = self.create_message_context(None, deadline);
+
+        bus.send_single_flight(query, context, key).await
+    }
+
+    pub async fn stream_query<T: Send + 'static>(
+        &self,
+        query: Box<dyn StreamQueryHandlerPort<Item = T>>,
+    ) -> Result<QueryStream<T>, Error> {
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        let bus = self.get_query_bus().await?;
+
+        let deadline = self.create_deadline(MessageKind::Query, timeout).await?;
+
+        let context = self.create_message_context(None, deadline);
+
+        bus.send_stream(query, context).await
+    }
+
+    pub async fn get_single_flight(&self) -> Result<&SingleFlightProvider, Error> {
+        self.get_query_bus().await?.get_single_flight().await
+    }
+
+    pub async fn get_query_cache(&self) -> Result<&QueryCacheProvider, Error> {
+        self.get_query_bus().await?.get_cache().await
+    }
+
+    pub async fn query_with_timeout<O: Send>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        timeout: Duration,
+    ) -> Result<O, Error> {
+        self.query_with_deadline(query, Some(timeout)).await
+    }
+
+    pub async fn dispatch_command<M: CommandMessagePort>(
+        &self,
+        command: M,
+    ) -> Result<M::Output, Error> {
+        let handler = self
+            .get_handler_registry()
+            .await?
+            .get_command_handler::<M>()?;
+
+        let policy = self.get_retry_provider().await?.get_policy::<M>();
+
+        let timeout = self.get_timeout_provider().await?.get_command_timeout();
+
+        let tags = command.get_invalidated_tags();
+
+        let bus = self.get_command_bus().await?;
+
+        let command = Box::new(RegisteredCommandAdapter::new(command, handler));
+
+        self.named::<M>()
+            .execute_command(timeout, tags, |create_context| {
+                bus.send_with_retry(command, create_context, policy)
+            })
+            .await
+    }
+
+    // Keys are scoped by the command type, boxed trait objects share a type and need a scope
+    // of their own, see `command_with_idempotency_scope`.
+    pub async fn command_with_idempotency_key<C>(
+        &self,
+        command: Box<C>,
+        key: &str,
+    ) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + 'static,
+        C::Output: IdempotentOutputPort,
+    {
+        self.command_with_idempotency_scope(command, type_name::<C>(), key)
+            .await
+    }
+
+    pub async fn command_with_idempotency_scope<C>(
+        &self,
+        command: Box<C>,
+        scope: &str,
+        key: &str,
+    ) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: IdempotentOutputPort,
+    {
+        let idempotency = IdempotencyProvider::resolve(&self.context).await?;
+
+        let timeout = self.get_timeout_provider().await?.get_command_timeout();
+
+        idempotency
+            .run(
+                scope,
+                key,
+                self.command_with_deadline(command, timeout, Vec::new()),
+            )
+            .await
+    }
+
+    // Keys are scoped by the message type, so two commands may share a client key.
+    pub async fn dispatch_idempotent_command<M: IdempotentCommandPort>(
+        &self,
+        command: M,
+    ) -> Result<M::Output, Error> {
+        let idempotency = IdempotencyProvider::resolve(&self.context).await?;
+
+        let key = command.get_idempotency_key();
+
+        idempotency
+            .run(type_name::<M>(), &key, self.dispatch_command(command))
+            .await
+    }
+
+    pub async fn dispatch_query<M: QueryMessagePort>(&self, query: M) -> Result<M::Output, Error> {
+        let handler = self
+            .get_handler_registry()
+            .await?
+            .get_query_handler::<M>()?;
+
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        let bus = self.get_query_bus().await?;
+
+        let query = Box::new(RegisteredQueryAdapter::new(query, handler));
+
+        self.named::<M>()
+            .execute_query(timeout, |context| bus.send_message(query, context))
+            .await
+    }
+
+    pub async fn dispatch_cached_query<M: CacheableQueryPort>(
+        &self,
+        query: M,
+    ) -> Result<M::Output, Error> {
+        let handler = self
+            .get_handler_registry()
+            .await?
+            .get_query_handler::<M>()?;
+
+        let policy = query.get_cache_policy();
+
+        self.named::<M>()
+            .cached_query(
+                Box::new(RegisteredQueryAdapter::new(query, handler)),
+                policy,
+            )
+            .await
+    }
+
+    pub async fn dispatch_single_flight_query<M: SingleFlightQueryPort>(
+        &self,
+        query: M,
+    ) -> Result<M::Output, Error> {
+        let handler = self
+            .get_handler_registry()
+            .await?
+            .get_query_handler::<M>()?;
+
+        let key = format!("{}:{}", type_name::<M>(), query.get_single_flight_key());
+
+        self.named::<M>()
+            .single_flight_query(Box::new(RegisteredQueryAdapter::new(query, handler)), &key)
+            .await
+    }
+
+    pub async fn load<L: BatchLoaderPort>(&self, key: L::Key) -> Result<Option<L::Value>, Error> {
+        let mut values = self.load_many::<L>(vec![key]).await?;
+
+        Ok(values.pop().flatten())
+    }
+
+    // Loads of one request are batched, outside a message every call is its own batch.
+    pub async fn load_many<L: BatchLoaderPort>(
+        &self,
+        keys: Vec<L::Key>,
+    ) -> Result<Vec<Option<L::Value>>, Error> {
+        let loaders = BatchLoaderProvider::resolve(&self.context).await?;
+
+        let create = || -> Result<DataLoader<L>, Error> {
+            Ok(DataLoader::new(
+                loaders.get_loader::<L>()?,
+                loaders.get_window(),
+            ))
+        };
+
+        let scope =
+            MessageContext::from_context(&self.context).map(MessageContext::get_batch_scope);
+
+        let loader = match scope {
+            Some(scope) => scope.get_or_try_insert_with(create)?,
+            None => Arc::new(create()?),
+        };
+
+        loader.load_many(keys, self.get_context()).await
+    }
+
+    pub async fn get_event_subscriptions(&self) -> Result<&EventSubscriptionsProvider, Error> {
+        self.buses
+            .subscriptions
+            .get_or_resolve(|| EventSubscriptionsProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn publish<E: EventMessagePort>(&self, event: E) -> Result<(), Error> {
+        self.raise(Box::new(move |provider| {
+            Box::pin(async move { provider.broadcast(event).await })
+        }))
+        .await
+    }
+
+    pub async fn publish_with_outbox<E: OutboxMessagePort>(&self, event: E) -> Result<(), Error> {
+        let payload = event.encode()?;
+
+        let deliver: DeferredDelivery = Box::new(move |provider, id| {
+            Box::pin(async move { provider.deliver_with_outbox(event, id).await })
+        });
+
+        self.raise_outbox(E::get_name(), payload, deliver).await
+    }
+
+    pub async fn relay_outbox(&self) -> Result<usize, Error> {
+        let outbox = self
+            .get_outbox_provider()
+            .await?
+            .ok_or("Outbox provider is not registered")?;
+
+        let store = outbox.get_store();
+
+        let mut relayed = 0;
+        let mut result = Ok(());
+
+        for record in store.list().await? {
+            if !outbox.has_message(record.get_name()) {
+                if result.is_ok() {
+                    result = Err(
+                        format!("Outbox message {} is not registered", record.get_name()).into(),
+                    );
+                }
+
+                continue;
+            }
+
+            let provider = match record.get_envelope() {
+                Some(envelope) => self.resumed(envelope.clone()),
+                None => self.clone(),
+            };
+
+            let outcome = outbox
+                .relay(
+                    provider,
+                    record.get_name(),
+                    record.get_payload().to_string(),
+                )
+                .await;
+
+            // A failed relay keeps the record for the next one.
+            if let Err(error) = outcome {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+
+                continue;
+            }
+
+            store.remove(record.get_id()).await?;
+
+            relayed += 1;
+        }
+
+        result.map(|_| relayed)
+    }
+
+    async fn broadcast<E: EventMessagePort>(&self, event: E) -> Result<(), Error> {
+        let subscribers = self.get_event_subscriptions().await?.get_subscribers::<E>();
+
+        let policy = self.get_retry_provider().await?.get_policy::<E>().clone();
+
+        let bus = self.get_event_bus().await?;
+
+        bus.broadcast(
+            Arc::new(event),
+            subscribers,
+            self.create_event_context(),
+            policy,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn publish_and_wait<E: EventMessagePort>(&self, event: E) -> Result<(), Error> {
+        let subscribers = self.get_event_subscriptions().await?.get_subscribers::<E>();
+
+        let policy = self.get_retry_provider().await?.get_policy::<E>().clone();
+
+        let bus = self.get_event_bus().await?;
+
+        let handles = bus
+            .broadcast_with_handles(
+                Arc::new(event),
+                subscribers,
+                self.create_event_context(),
+                policy,
+            )
+            .await?;
+
+        let mut result = Ok(());
+
+        for handle in handles {
+            let outcome = handle.await;
+
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+
+        result
+    }
+
+    pub async fn event_with_partition<E>(
+        &self,
+        event: Box<E>,
+        partition: impl Into<String>,
+    ) -> Result<(), Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        let policy = self.get_retry_provider().await?.get_policy::<E>().clone();
+
+        let event: SharedEvent = Arc::new(BoxedHandlerAdapter::new(event));
+        let partition = partition.into();
+
+        self.raise(Box::new(move |provider| {
+            Box::pin(async move {
+                provider
+                    .named::<E>()
+                    .publish_event(event, Some(partition), policy)
+                    .await
+            })
+        }))
+        .await
+    }
+
+    pub async fn event_with_handle<E>(&self, event: Box<E>) -> Result<EventHandle, Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        self.dispatch_event(event, None).await
+    }
+
+    pub async fn event_with_handle_and_partition<E>(
+        &self,
+        event: Box<E>,
+        partition: impl Into<String>,
+    ) -> Result<EventHandle, Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        self.dispatch_event(event, Some(partition.into())).await
+    }
+
+    pub async fn event_and_wait<E>(&self, event: Box<E>) -> Result<(), Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        self.event_with_handle(event).await?.await
+    }
+
+    pub async fn get_dead_letter_provider(&self) -> Result<DeadLetterProvider, Error> {
+        let provider = DeadLetterProvider::resolve(&self.context)
+            .await?
+            .ok_or("Dead letter provider is not registered")?;
+
+        Ok(provider)
+    }
+
+    pub async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>, Error> {
+        let provider = self.get_dead_letter_provider().await?;
+
+        provider.get_store().list().await
+    }
+
+    pub async fn get_dead_letter(&self, id: MessageId) -> Result<Option<DeadLetter>, Error> {
+        let provider = self.get_dead_letter_provider().await?;
+
+        provider.get_store().get(id).await
+    }
+
+    // The letter stays stored until a replay succeeds. Replays keep the envelope of the
+    // failed event, letters of boxed events replay the event they keep.
+    pub async fn replay_dead_letter(&self, id: MessageId) -> Result<(), Error> {
+        let provider = self.get_dead_letter_provider().await?;
+
+        let store = provider.get_store();
+
+        let letter = store
+            .get(id)
+            .await?
+            .ok_or_else(|| format!("Dead letter {} does not exist", id))?;
+
+        let policy = self.get_retry_provider().await?.get_default();
+
+        let origin = match letter.get_envelope() {
+            Some(envelope) => self.resumed(envelope.clone()),
+            None => self.clone(),
+        };
+
+        let (result, attempts) = policy
+            .run_counted(|| async {
+                match letter.get_event() {
+                    Some(event) => event.execute(origin.create_event_context()).await,
+                    None => {
+                        provider
+                            .replay(
+                                origin.clone(),
+                                letter.get_name(),
+                                letter.get_payload().to_string(),
+                                letter.get_subscriber().to_string(),
+                            )
+                            .await
+                    }
+                }
+            })
+            .await;
+
+        match &result {
+            Ok(()) => {
+                store.remove(id).await?;
+            }
+            Err(error) => {
+                store
+                    .push(letter.with_failed_attempts(error.to_string(), attempts))
+                    .await?;
+            }
+        }
+
+        result
+    }
+
+    pub async fn purge_dead_letters(&self) -> Result<usize, Error> {
+        let provider = self.get_dead_letter_provider().await?;
+
+        provider.get_store().purge().await
+    }
+
+    pub(crate) async fn handle_dead_letter<E: EventMessagePort>(
+        &self,
+        event: E,
+        subscriber: &str,
+    ) -> Result<(), Error> {
+        let handler = self
+            .get_event_subscriptions()
+            .await?
+            .get_subscribers::<E>()
+            .into_iter()
+            .find(|handler| handler.get_name() == subscriber)
+            .ok_or_else(|| format!("Subscriber {} is not registered", subscriber))?;
+
+        handler.handle(&event, self.create_event_context()).await
+    }
+
+    pub async fn get_event_store(&self) -> Result<Box<EventStoreProvider>, Error> {
+        EventStoreProvider::get_adapter(&self.context).await
+    }
+
+    pub async fn load_aggregate<A: AggregatePort>(
+        &self,
+        id: &str,
+    ) -> Result<AggregateRoot<A>, Error> {
+        self.get_event_store().await?.load::<A>(id).await
+    }
+
+    pub async fn save_aggregate<A: AggregatePort>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: Vec<A::Event>,
+    ) -> Result<u64, Error> {
+        let store = self.get_event_store().await?;
+
+        let version = store.save::<A>(aggregate, events).await?;
+
+        if let Some(projections) = self.get_projections().await? {
+            let event: SharedEvent = Arc::new(ProjectionEventAdapter::new(
+                projections.clone(),
+                store.get_store(),
+            ));
+
+            let policy = self.get_retry_provider().await?.get_default().clone();
+
+            // One partition keeps catch-up runs from queueing up on every worker.
+            self.raise(Box::new(move |provider| {
+                Box::pin(async move {
+                    provider
+                        .publish_event(event, Some(ProjectionProvider::token().to_string()), policy)
+                        .await
+                })
+            }))
+            .await?;
+        }
+
+        Ok(version)
+    }
+
+    // Feeds an event of the bus to projections through the event store. The event is
+    // stored once per envelope id, so retries, relays & replays only catch up again.
+    pub async fn project_event<E: OutboxMessagePort>(&self, event: &E) -> Result<usize, Error> {
+        let projections = self
+            .get_projections()
+            .await?
+            .ok_or("Projection provider is not registered")?;
+
+        let store = self.get_event_store().await?;
+
+        let message_id = MessageEnvelope::from_context(&self.context).map(MessageEnvelope::get_id);
+
+        store.append_event(event, message_id).await?;
+
+        projections
+            .catch_up(store.get_store().as_ref(), self.get_context())
+            .await
+    }
+
+    pub async fn catch_up_projections(&self) -> Result<usize, Error> {
+        let projections = self
+            .get_projections()
+            .await?
+            .ok_or("Projection provider is not registered")?;
+
+        let store = self.get_event_store().await?.get_store();
+
+        projections
+            .catch_up(store.as_ref(), self.get_context())
+            .await
+    }
+
+    pub async fn rebuild_projection(&self, name: &str) -> Result<usize, Error> {
+        let projections = self
+            .get_projections()
+            .await?
+            .ok_or("Projection provider is not registered")?;
+
+        let store = self.get_event_store().await?.get_store();
+
+        projections
+            .rebuild(name, store.as_ref(), self.get_context())
+            .await
+    }
+
+    pub async fn get_saga(
+        &self,
+        saga: &str,
+        correlation_id: &str,
+    ) -> Result<Option<SagaRecord>, Error> {
+        let sagas = SagaProvider::resolve(&self.context).await?;
+
+        sagas.get_store().load(saga, correlation_id).await
+    }
+
+    pub async fn get_scheduler(&self) -> Result<SchedulerProvider, Error> {
+        SchedulerProvider::resolve(&self.context).await
+    }
+
+    pub async fn schedule_command<C>(&self, at: SystemTime, command: Box<C>) -> Result<u64, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: Send,
+    {
+        let scheduler = self.get_scheduler().await?;
+
+        let id = scheduler.next_id().await?;
+
+        scheduler.schedule(
+            id,
+            at,
+            self.detach(),
+            Box::new(move |provider| {
+                Box::pin(async move { provider.send_command(command).await.map(|_| ()) })
+            }),
+        );
+
+        Ok(id)
+    }
+
+    pub async fn schedule_event<E>(&self, delay: Duration, event: Box<E>) -> Result<u64, Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        let scheduler = self.get_scheduler().await?;
+
+        let id = scheduler.next_id().await?;
+
+        let at = scheduler.get_clock().now() + delay;
+
+        scheduler.schedule(
+            id,
+            at,
+            self.detach(),
+            Box::new(move |provider| Box::pin(async move { provider.send_event(event).await })),
+        );
+
+        Ok(id)
+    }
+
+    pub async fn schedule_persistent_event<E: OutboxMessagePort>(
+        &self,
+        at: SystemTime,
+        event: E,
+    ) -> Result<u64, Error> {
+        let payload = event.encode()?;
+
+        let job: DeferredEvent = Box::new(move |provider| {
+            Box::pin(async move { provider.publish_and_wait(event).await })
+        });
+
+        self.schedule_persistent(E::get_name(), payload, at, job)
+            .await
+    }
+
+    pub async fn schedule_persistent_command<C: PersistentCommandPort>(
+        &self,
+        at: SystemTime,
+        command: C,
+    ) -> Result<u64, Error> {
+        let payload = command.encode()?;
+
+        let job: DeferredEvent = Box::new(move |provider| {
+            Box::pin(async move { provider.dispatch_command(command).await.map(|_| ()) })
+        });
+
+        self.schedule_persistent(C::get_name(), payload, at, job)
+            .await
+    }
+
+    pub async fn cancel_schedule(&self, id: u64) -> Result<bool, Error> {
+        let scheduler = self.get_scheduler().await?;
+
+        let cancelled = scheduler.cancel(id);
+
+        // A running job keeps its record, which is removed once the job succeeded.
+        if !cancelled && scheduler.is_running(id) {
+            return Ok(false);
+        }
+
+        let removed = match scheduler.get_store() {
+            Some(store) => store.remove(id).await?,
+            None => false,
+        };
+
+        Ok(cancelled || removed)
+    }
+
+    pub async fn restore_schedules(&self) -> Result<usize, Error> {
+        let scheduler = self.get_scheduler().await?;
+
+        let Some(store) = scheduler.get_store() else {
+            return Ok(0);
+        };
+
+        let pending = scheduler.get_pending();
+
+        let mut restored = 0;
+
+        for record in store.list().await? {
+            scheduler.reserve_id(record.get_id());
+
+            if pending.contains(&record.get_id()) {
+                continue;
+            }
+
+            let job = scheduler.decode(record.get_name(), record.get_payload())?;
+
+            let provider = match record.get_envelope() {
+                Some(envelope) => self.with_envelope(envelope.clone()),
+                None => self.clone(),
+            };
+
+            scheduler.schedule(record.get_id(), record.get_due_at(), provider, job);
+
+            restored += 1;
+        }
+
+        Ok(restored)
+    }
+
+    pub async fn get_recurring_jobs(&self) -> Result<Option<RecurringJobsProvider>, Error> {
+        RecurringJobsProvider::resolve(&self.context).await
+    }
+
+    pub async fn start_recurring_jobs(&self) -> Result<usize, Error> {
+        let jobs = self
+            .get_recurring_jobs()
+            .await?
+            .ok_or("Recurring jobs provider is not registered")?;
+
+        let clock = self.get_scheduler().await?.get_clock();
+
+        Ok(jobs.start(self.clone(), clock))
+    }
+
+    pub async fn stop_recurring_jobs(&self) -> Result<usize, Error> {
+        let stopped = match self.get_recurring_jobs().await? {
+            Some(jobs) => jobs.stop(),
+            None => 0,
+        };
+
+        Ok(stopped)
+    }
+
+    // Stops recurring jobs & schedules that are not due yet, then waits for running
+    // jobs before the event bus.
+    pub async fn drain(&self) -> Result<(), Error> {
+        let jobs = self.get_recurring_jobs().await?;
+
+        let scheduler = self.get_scheduler().await?;
+
+        if let Some(jobs) = &jobs {
+            jobs.stop();
+        }
+
+        scheduler.stop();
+
+        if let Some(jobs) = &jobs {
+            jobs.drain().await;
+        }
+
+        scheduler.drain().await;
+
+        let bus = self.get_event_bus().await?;
+
+        bus.drain().await;
+
+        Ok(())
+    }
+
+    pub async fn shutdown(&self, timeout: Duration) -> Result<ShutdownReport, Error> {
+        let started = Instant::now();
+
+        let jobs = self.get_recurring_jobs().await?;
+
+        let scheduler = self.get_scheduler().await?;
+
+        if let Some(jobs) = &jobs {
+            jobs.stop();
+        }
+
+        let cancelled = scheduler.stop();
+
+        let running = async {
+            if let Some(jobs) = &jobs {
+                jobs.drain().await;
+            }
+
+            scheduler.drain().await;
+        };
+
+        let (aborted_jobs, aborted) = match tokio::time::timeout(timeout, running).await {
+            Ok(()) => (Vec::new(), Vec::new()),
+            Err(_) => (
+                jobs.as_ref()
+                    .map(RecurringJobsProvider::abort)
+                    .unwrap_or_default(),
+                scheduler.abort(),
+            ),
+        };
+
+        let bus = self.get_event_bus().await?;
+
+        let report = bus
+            .shutdown(timeout.saturating_sub(started.elapsed()))
+            .await;
+
+        Ok(report
+            .with_cancelled_schedules(cancelled)
+            .with_aborted_schedules(aborted)
+            .with_aborted_jobs(aborted_jobs))
+    }
+
+    async fn schedule_persistent(
+        &self,
+        name: &'static str,
+        payload: String,
+        at: SystemTime,
+        job: DeferredEvent,
+    ) -> Result<u64, Error> {
+        let scheduler = self.get_scheduler().await?;
+
+        let store = scheduler
+            .get_store()
+            .ok_or("Scheduler has no schedule store")?;
+
+        if !scheduler.has_message(name) {
+            return Err(format!("Scheduled message {} is not registered", name).into());
+        }
+
+        let id = scheduler.next_id().await?;
+
+        let envelope = MessageEnvelope::from_context(&self.context).cloned();
+
+        store
+            .save(ScheduledRecord::new(id, name, payload, at).with_envelope(envelope))
+            .await?;
+
+        scheduler.schedule(id, at, self.detach(), job);
+
+        Ok(id)
+    }
+
+    async fn command_with_deadline<C>(
+        &self,
+        command: Box<C>,
+        timeout: Option<Duration>,
+        tags: Vec<String>,
+    ) -> Result<C::Output, Error>
+    where
+        C: CommandHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+        C::Output: Send,
+    {
+        let policy = self.get_retry_provider().await?.get_policy::<C>();
+
+        let bus = self.get_command_bus().await?;
+
+        let command = Box::new(BoxedHandlerAdapter::new(command));
+
+        self.execute_command(timeout, tags, |create_context| {
+            bus.send_with_retry(command, create_context, policy)
+        })
+        .await
+    }
+
+    async fn query_with_deadline<O: Send>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        timeout: Option<Duration>,
+    ) -> Result<O, Error> {
+        let bus = self.get_query_bus().await?;
+
+        self.execute_query(timeout, |context| bus.send_message(query, context))
+            .await
+    }
+
+    async fn execute_command<O: Send, F>(
+        &self,
+        timeout: Option<Duration>,
+        tags: Vec<String>,
+        send: impl FnOnce(ContextFactory) -> F,
+    ) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        let (result, unit_of_work, _) = self.run_command(timeout, tags, false, send).await?;
+
+        let Some(unit_of_work) = unit_of_work else {
+            return result;
+        };
+
+        let committed = self.commit(&unit_of_work, result.is_ok()).await;
+
+        result.and_then(|output| committed.map(|_| output))
+    }
+
+    // For outputs without `Send`, which can't be held while outbox records are stored. The
+    // records are staged as they are raised, so they are stored once the command returns.
+    async fn execute_command_detached<O, F>(
+        &self,
+        timeout: Option<Duration>,
+        send: impl FnOnce(ContextFactory) -> F,
+    ) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        // The output can't be held across `.await`, so only the error leaves this block.
+        let (error, unit_of_work, discarded) = {
+            let (result, unit_of_work, discarded) =
+                self.run_command(timeout, Vec::new(), true, send).await?;
+
+            let Some(unit_of_work) = unit_of_work else {
+                return result;
+            };
+
+            match result {
+                Ok(output) => {
+                    let provider = self.clone();
+
+                    // The records are stored already, what is left only dispatches them.
+                    tokio::spawn(async move {
+                        let committed = match provider.unstage(discarded).await {
+                            Ok(()) => provider.commit(&unit_of_work, true).await,
+                            Err(error) => Err(error),
+                        };
+
+                        if let Err(error) = committed {
+                            let failure = EventFailure::new(next_message_id(), error);
+
+                            EventFailureProvider::report(&provider.get_context(), failure).await;
+                        }
+                    });
+
+                    return Ok(output);
+                }
+                Err(error) => (error, unit_of_work, discarded),
+            }
+        };
+
+        unit_of_work.close();
+
+        let mut staged = discarded;
+
+        staged.extend(unit_of_work.take_staged());
+
+        self.unstage(staged).await?;
+
+        Err(error)
+    }
+
+    // Returns the unit of work of the last attempt, which the caller commits, and the records
+    // staged by the failed attempts.
+    async fn run_command<O, F>(
+        &self,
+        timeout: Option<Duration>,
+        tags: Vec<String>,
+        staged: bool,
+        send: impl FnOnce(ContextFactory) -> F,
+    ) -> Result<(Result<O, Error>, Option<Arc<UnitOfWork>>, Vec<u64>), Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        let cache = self.get_query_cache().await?;
+
+        let outbox = self.get_outbox_provider().await?.is_some();
+
+        let deadline = self.create_deadline(MessageKind::Command, timeout).await?;
+
+        let envelope = self.create_envelope();
+
+        let attempt = Arc::new(Mutex::new((None, Vec::new())));
+
+        let provider = self.clone();
+        let current = attempt.clone();
+
+        let create_context: ContextFactory = Arc::new(move || {
+            let unit_of_work = outbox.then(|| match staged {
+                true => Arc::new(UnitOfWork::staged()),
+                false => Arc::new(UnitOfWork::new()),
+            });
+
+            let mut current = current.lock().unwrap_or_else(PoisonError::into_inner);
+
+            let (current, discarded) = &mut *current;
+
+            // Events raised by a failed attempt are dropped with its unit of work.
+            if let Some(previous) = std::mem::replace(current, unit_of_work.clone()) {
+                previous.close();
+
+                discarded.extend(previous.take_staged());
+            }
+
+            provider.create_scope(envelope.clone(), unit_of_work, deadline)
+        });
+
+        let result = send(create_context).await;
+
+        // Invalidates even on errors, a failed command may still have changed state.
+        cache.invalidate(&tags);
+
+        let (unit_of_work, discarded) =
+            std::mem::take(&mut *attempt.lock().unwrap_or_else(PoisonError::into_inner));
+
+        Ok((result, unit_of_work, discarded))
+    }
+
+    async fn execute_query<O, F>(
+        &self,
+        timeout: Option<Duration>,
+        send: impl FnOnce(Arc<dyn ContextPort>) -> F,
+    ) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        let deadline = self.create_deadline(MessageKind::Query, timeout).await?;
+
+        let context = self.create_message_context(None, deadline);
+
+        send(context).await
+    }
+
+    async fn create_deadline(
+        &self,
+        kind: MessageKind,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Deadline>, Error> {
+        let Some(timeout) = timeout else {
+            return Ok(None);
+        };
+
+        let grace = self.get_timeout_provider().await?.get_grace_period();
+
+        Ok(Some(Deadline::new(kind, timeout, grace)))
+    }
+
+    fn create_message_context(
+        &self,
+        unit_of_work: Option<Arc<UnitOfWork>>,
+        deadline: Option<Deadline>,
+    ) -> Arc<dyn ContextPort> {
+        self.create_scope(self.create_envelope(), unit_of_work, deadline)
+    }
+
+    fn create_envelope(&self) -> MessageEnvelope {
+        if let Some(envelope) = &self.resumed {
+            return envelope.clone();
+        }
+
+        MessageEnvelope::from_context(&self.context)
+            .map_or_else(MessageEnvelope::new, MessageEnvelope::create_child)
+    }
+
+    fn create_scope(
+        &self,
+        envelope: MessageEnvelope,
+        unit_of_work: Option<Arc<UnitOfWork>>,
+        deadline: Option<Deadline>,
+    ) -> Arc<dyn ContextPort> {
+        MessageContext::new(
+            self.get_context(),
+            self.create_cancellation(),
+            unit_of_work,
+            envelope,
+            self.type_name,
+            deadline,
+        )
+    }
+
+    // Nested messages are cancelled with the message that dispatched them.
+    fn create_cancellation(&self) -> CancellationToken {
+        MessageContext::from_context(&self.context).map_or_else(CancellationToken::new, |context| {
+            context.get_cancellation().child()
+        })
+    }
+
+    // The next message context of the returned provider carries the type name for middleware.
+    fn named<M: ?Sized>(&self) -> Self {
+        Self {
+            type_name: Some(type_name::<M>()),
+            ..self.clone()
+        }
+    }
+
+    // The next message context of the returned provider keeps the envelope as is, so a
+    // redelivered message has the id of its first delivery.
+    fn resumed(&self, envelope: MessageEnvelope) -> Self {
+        Self {
+            resumed: Some(envelope),
+            ..self.clone()
+        }
+    }
+
+    // Event handlers are cancelled with the message that raised the event.
+    fn create_event_context(&self) -> Arc<dyn ContextPort> {
+        self.create_message_context(None, None)
+    }
+
+    fn get_unit_of_work(&self) -> Option<Arc<UnitOfWork>> {
+        MessageContext::from_context(&self.context).and_then(MessageContext::get_unit_of_work)
+    }
+
+    async fn raise(&self, event: DeferredEvent) -> Result<(), Error> {
+        let event = DeferredMessage::Event(self.bind(event));
+
+        self.raise_message(event).await
+    }
+
+    // Outside a command the outbox record is stored right before delivery.
+    async fn raise_outbox(
+        &self,
+        name: &'static str,
+        payload: String,
+        deliver: DeferredDelivery,
+    ) -> Result<(), Error> {
+        let envelope = self.create_envelope();
+
+        let origin = self.detach().resumed(envelope.clone());
+
+        let message = DeferredMessage::Outbox {
+            name,
+            payload,
+            envelope: Box::new(envelope),
+            deliver: Box::new(move |_, id| deliver(origin, id)),
+        };
+
+        self.raise_message(message).await
+    }
+
+    // Deferred messages keep the envelope of the message that raised them.
+    async fn raise_message(&self, message: DeferredMessage) -> Result<(), Error> {
+        let message = match self.get_unit_of_work() {
+            Some(unit_of_work) if unit_of_work.is_staged() => {
+                return self.stage(&unit_of_work, vec![message]).await;
+            }
+            Some(unit_of_work) => match unit_of_work.defer(message) {
+                Ok(()) => return Ok(()),
+                Err(message) => message,
+            },
+            None => message,
+        };
+
+        for (_, event) in self.prepare(vec![message]).await? {
+            event(self.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    fn bind(&self, event: DeferredEvent) -> DeferredEvent {
+        let origin = self.detach();
+
+        Box::new(move |_| event(origin))
+    }
+
+    // Deferred messages run after the message that raised them finished, so they keep
+    // its envelope but not its cancellation, deadline or unit of work.
+    fn detach(&self) -> Self {
+        let Some(envelope) = MessageEnvelope::from_context(&self.context) else {
+            return self.clone();
+        };
+
+        let context = MessageContext::new(
+            self.get_context(),
+            CancellationToken::new(),
+            None,
+            envelope.clone(),
+            None,
+            None,
+        );
+
+        self.scoped(context)
+    }
+
+    // Stores the outbox records of the messages at once and returns them in the order raised,
+    // with the id of their record.
+    async fn prepare(
+        &self,
+        messages: Vec<DeferredMessage>,
+    ) -> Result<Vec<(Option<u64>, DeferredEvent)>, Error> {
+        let mut records = Vec::new();
+        let mut events = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            match message {
+                DeferredMessage::Event(event) => events.push(Either::Left(event)),
+                DeferredMessage::Outbox {
+                    name,
+                    payload,
+                    envelope,
+                    deliver,
+                } => {
+                    records.push((name, payload, Some(*envelope)));
+
+                    events.push(Either::Right(deliver));
+                }
+            }
+        }
+
+        let mut ids = Vec::with_capacity(records.len()).into_iter();
+
+        if !records.is_empty() {
+            let store = self
+                .get_outbox_provider()
+                .await?
+                .ok_or("Outbox provider is not registered")?
+                .get_store();
+
+            ids = store
+                .append_all(records)
+                .await?
+                .iter()
+                .map(OutboxRecord::get_id)
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+
+        events
+            .into_iter()
+            .map(|event| match event {
+                Either::Left(event) => Ok((None, event)),
+                Either::Right(deliver) => {
+                    let id = ids.next().ok_or("Outbox store returned fewer records")?;
+
+                    let event: DeferredEvent = Box::new(move |provider| deliver(provider, id));
+
+                    Ok((Some(id), event))
+                }
+            })
+            .collect()
+    }
+
+    // Stores outbox records right away and defers their delivery to a staged unit of work.
+    async fn stage(
+        &self,
+        unit_of_work: &UnitOfWork,
+        messages: Vec<DeferredMessage>,
+    ) -> Result<(), Error> {
+        for (id, event) in self.prepare(messages).await? {
+            if let Err(event) = unit_of_work.stage(id, event) {
+                event(self.clone()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn unstage(&self, ids: Vec<u64>) -> Result<(), Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let store = self
+            .get_outbox_provider()
+            .await?
+            .ok_or("Outbox provider is not registered")?
+            .get_store();
+
+        for id in ids {
+            store.remove(id).await?;
+        }
+
+        Ok(())
+    }
+
+    // Messages of a nested command move to the parent unit of work. The outermost command
+    // stores all its outbox records before it returns and dispatches in the order raised.
+    async fn commit(&self, unit_of_work: &UnitOfWork, committed: bool) -> Result<(), Error> {
+        let messages = unit_of_work.close();
+
+        if !committed || messages.is_empty() {
+            return Ok(());
+        }
+
+        let messages = match self.get_unit_of_work() {
+            Some(parent) if parent.is_staged() => return self.stage(&parent, messages).await,
+            Some(parent) => messages
+                .into_iter()
+                .filter_map(|message| parent.defer(message).err())
+                .collect(),
+            None => messages,
+        };
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let events = self.prepare(messages).await?;
+
+        let provider = self.clone();
+
+        tokio::spawn(async move {
+            for (_, event) in events {
+                if let Err(error) = event(provider.clone()).await {
+                    let failure = EventFailure::new(next_message_id(), error);
+
+                    EventFailureProvider::report(&provider.get_context(), failure).await;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn dispatch_event<E>(
+        &self,
+        event: Box<E>,
+        partition: Option<String>,
+    ) -> Result<EventHandle, Error>
+    where
+        E: EventHandlerPort<Context = Arc<dyn ContextPort>> + ?Sized + 'static,
+    {
+        let policy = self.get_retry_provider().await?.get_policy::<E>().clone();
+
+        let bus = self.get_event_bus().await?;
+
+        bus.dispatch(
+            Arc::new(BoxedHandlerAdapter::new(event)),
+            partition,
+            self.named::<E>().create_event_context(),
+            policy,
+        )
+        .await
+    }
+
+    async fn publish_event(
+        &self,
+        event: SharedEvent,
+        partition: Option<String>,
+        policy: RetryPolicy,
+    ) -> Result<(), Error> {
+        let bus = self.get_event_bus().await?;
+
+        bus.publish(event, partition, self.create_event_context(), policy)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn deliver_with_outbox<E: OutboxMessagePort>(
+        &self,
+        event: E,
+        id: u64,
+    ) -> Result<(), Error> {
+        let store = self
+            .get_outbox_provider()
+            .await?
+            .ok_or("Outbox provider is not registered")?
+            .get_store();
+
+        // A failed delivery keeps the record for `relay_outbox`.
+        self.publish_and_wait(event).await?;
+
+        store.remove(id).await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -51,28 +1658,36 @@ impl ServiceBusPort for CqrsProvider {
         &self,
         event: Box<dyn EventHandlerPort<Context = Self::Context>>,
     ) -> Result<(), Error> {
-        let bus = EventBusProvider::get_adapter(&self.get_context()).await?;
-
-        bus.send(event, self.get_context());
-
-        Ok(())
+        self.send_event(event).await
     }
 
     async fn command<O>(
         &self,
         command: Box<dyn CommandHandlerPort<Context = Self::Context, Output = O>>,
     ) -> Result<O, Error> {
-        let bus = CommandBusProvider::get_adapter(&self.get_context()).await?;
+        let policy = self.get_retry_provider().await?.get_default();
 
-        bus.send(command, self.get_context()).await
+        let timeout = self.get_timeout_provider().await?.get_command_timeout();
+
+        let bus = self.get_command_bus().await?;
+
+        self.execute_command_detached(timeout, |create_context| {
+            bus.send_with_retry_without_around(command, create_context, policy)
+        })
+        .await
     }
 
     async fn query<O>(
         &self,
         query: Box<dyn QueryHandlerPort<Context = Self::Context, Output = O>>,
     ) -> Result<O, Error> {
-        let bus = QueryBusProvider::get_adapter(&self.get_context()).await?;
+        let timeout = self.get_timeout_provider().await?.get_query_timeout();
+
+        let bus = self.get_query_bus().await?;
 
-        bus.send(query, self.get_context()).await
+        self.execute_query(timeout, |context| {
+            bus.send_message_without_around(query, context)
+        })
+        .await
     }
 }
@@ -0,0 +1,115 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::ports::{
+    dead_letter_store_port::DeadLetterStorePort, event_message_port::EventMessagePort,
+    outbox_message_port::OutboxMessagePort,
+};
+
+use super::cqrs_provider::CqrsProvider;
+
+type DeadLetterEncoder = Arc<dyn Fn(&dyn Any) -> Result<String, Error> + Send + Sync>;
+
+type DeadLetterDecoder = Arc<
+    dyn Fn(CqrsProvider, String, String) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+// Optional, failed events of unregistered messages are only reported to `EventFailureProvider`.
+#[derive(Clone)]
+pub struct DeadLetterProvider {
+    store: Arc<dyn DeadLetterStorePort>,
+    encoders: HashMap<TypeId, (&'static str, DeadLetterEncoder)>,
+    decoders: HashMap<&'static str, DeadLetterDecoder>,
+}
+
+#[async_trait]
+impl AdapterPort<DeadLetterProvider> for DeadLetterProvider {
+    fn token() -> &'static str {
+        "DEAD_LETTER_PROVIDER"
+    }
+}
+
+impl DeadLetterProvider {
+    pub fn new(store: Arc<dyn DeadLetterStorePort>) -> Self {
+        Self {
+            store,
+            encoders: HashMap::new(),
+            decoders: HashMap::new(),
+        }
+    }
+
+    pub fn with_message<E: OutboxMessagePort>(mut self) -> Self {
+        let encoder: DeadLetterEncoder = Arc::new(|event| {
+            event
+                .downcast_ref::<E>()
+                .ok_or_else(|| format!("Dead letter message {} has another type", E::get_name()))?
+                .encode()
+        });
+
+        let decoder: DeadLetterDecoder = Arc::new(|provider, payload, subscriber| {
+            Box::pin(async move {
+                provider
+                    .handle_dead_letter(E::decode(&payload)?, &subscriber)
+                    .await
+            })
+        });
+
+        self.encoders
+            .insert(TypeId::of::<E>(), (E::get_name(), encoder));
+
+        self.decoders.insert(E::get_name(), decoder);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Option<Self>, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(None);
+        }
+
+        Ok(Some(*Self::get_adapter(context).await?))
+    }
+
+    pub fn get_store(&self) -> Arc<dyn DeadLetterStorePort> {
+        self.store.clone()
+    }
+
+    pub fn has_message(&self, name: &str) -> bool {
+        self.decoders.contains_key(name)
+    }
+
+    // `None` when the message is not registered and can't be stored.
+    pub fn encode<E: EventMessagePort>(
+        &self,
+        event: &E,
+    ) -> Option<Result<(&'static str, String), Error>> {
+        let (name, encoder) = self.encoders.get(&TypeId::of::<E>())?;
+
+        Some(encoder(event).map(|payload| (*name, payload)))
+    }
+
+    pub async fn replay(
+        &self,
+        provider: CqrsProvider,
+        name: &str,
+        payload: String,
+        subscriber: String,
+    ) -> Result<(), Error> {
+        let decoder = self
+            .decoders
+            .get(name)
+            .ok_or_else(|| format!("Dead letter message {} is not registered", name))?;
+
+        decoder(provider, payload, subscriber).await
+    }
+}
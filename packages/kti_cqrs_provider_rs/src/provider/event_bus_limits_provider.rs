@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use ioc_container_rs::ports::adapter_port::AdapterPort;
+
+use crate::models::overflow_policy::OverflowPolicy;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EventBusLimitsProvider {
+    max_workers: usize,
+    queue_capacity: usize,
+    overflow: OverflowPolicy,
+}
+
+#[async_trait]
+impl AdapterPort<EventBusLimitsProvider> for EventBusLimitsProvider {
+    fn token() -> &'static str {
+        "EVENT_BUS_LIMITS_PROVIDER"
+    }
+}
+
+impl EventBusLimitsProvider {
+    pub fn new(max_workers: usize, queue_capacity: usize) -> Self {
+        Self {
+            max_workers: max_workers.max(1),
+            queue_capacity,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+
+        self
+    }
+
+    pub fn get_max_workers(&self) -> usize {
+        self.max_workers
+    }
+
+    pub fn get_queue_capacity(&self) -> usize {
+        self.queue_capacity
+    }
+
+    pub fn get_overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+}
@@ -1,13 +1,46 @@
-use std::sync::Arc;
+use std::{
+    any::Any,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use async_trait::async_trait;
 use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
-use kti_cqrs_rs::ports::{
-    bus::event_bus_port::EventBusPort, handler::event_handler_port::EventHandlerPort,
+use kti_cqrs_rs::{
+    errors::error::Error,
+    ports::{bus::event_bus_port::EventBusPort, handler::event_handler_port::EventHandlerPort},
+};
+
+use crate::{
+    errors::shared_error::SharedError,
+    models::{
+        dead_letter::{DeadLetter, SharedEvent},
+        event_failure::EventFailure,
+        event_handle::EventHandle,
+        event_tracker::EventTracker,
+        message_context::MessageContext,
+        message_envelope::MessageEnvelope,
+        message_id::MessageId,
+        message_info::next_message_id,
+        retry_policy::RetryPolicy,
+        shutdown_report::ShutdownReport,
+    },
+    ports::{event_message_port::EventMessagePort, event_subscriber_port::EventSubscriberPort},
 };
 
+use super::{
+    dead_letter_provider::DeadLetterProvider, event_failure_provider::EventFailureProvider,
+    retry_provider::RetryProvider,
+};
+
+type ContextEvent = (
+    Box<dyn EventHandlerPort<Context = Arc<dyn ContextPort>>>,
+    Arc<dyn ContextPort>,
+);
+
 pub struct EventBusProvider {
     context: Arc<dyn ContextPort>,
+    tracker: Arc<EventTracker>,
 }
 
 #[async_trait]
@@ -19,19 +52,400 @@ impl AdapterPort<EventBusProvider> for EventBusProvider {
 
 #[async_trait]
 impl EventBusPort for EventBusProvider {
+    // Events in a provider context are retried and dead lettered like `publish`, other
+    // contexts can't be cloned for another attempt.
     fn send<C: Send + 'static>(&self, event: Box<dyn EventHandlerPort<Context = C>>, context: C) {
-        tokio::spawn(async move {
-            std::mem::drop(event.execute(context).await);
+        let mut sent = Some((event, context));
+
+        let shared = (&mut sent as &mut dyn Any)
+            .downcast_mut::<Option<ContextEvent>>()
+            .and_then(Option::take);
+
+        if let Some((event, context)) = shared {
+            return self.send_shared(Arc::from(event), context);
+        }
+
+        let Some((event, context)) = sent else {
+            return;
+        };
+
+        let id = next_message_id();
+        let provider_context = self.get_context();
+
+        let spawned = self.tracker.spawn(id, None, move |admission| async move {
+            let result = match admission {
+                Ok(()) => event.execute(context).await,
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = result {
+                EventFailureProvider::report(&provider_context, EventFailure::new(id, error)).await;
+            }
         });
+
+        if let Err(error) = spawned {
+            self.report_rejected(id, error);
+        }
     }
 }
 
 impl EventBusProvider {
-    pub fn new(context: Arc<dyn ContextPort>) -> Self {
-        Self { context }
+    pub fn new(context: Arc<dyn ContextPort>, tracker: Arc<EventTracker>) -> Self {
+        Self { context, tracker }
     }
 
     pub fn get_context(&self) -> Arc<dyn ContextPort> {
         self.context.clone()
     }
+
+    pub fn is_accepting(&self) -> bool {
+        self.tracker.is_accepting()
+    }
+
+    pub fn get_in_flight(&self) -> usize {
+        self.tracker.get_in_flight()
+    }
+
+    pub fn get_active_workers(&self) -> usize {
+        self.tracker.get_active_workers()
+    }
+
+    pub fn get_queue_depth(&self) -> usize {
+        self.tracker.get_queue_depth()
+    }
+
+    pub fn get_dropped(&self) -> u64 {
+        self.tracker.get_dropped()
+    }
+
+    pub async fn publish(
+        &self,
+        event: SharedEvent,
+        partition: Option<String>,
+        context: Arc<dyn ContextPort>,
+        policy: RetryPolicy,
+    ) -> Result<u64, Error> {
+        let id = next_message_id();
+        let provider_context = self.get_context();
+
+        self.tracker
+            .spawn_or_wait(id, partition, move |admission| async move {
+                let _ =
+                    Self::deliver(&provider_context, id, event, context, policy, admission).await;
+            })
+            .await?;
+
+        Ok(id)
+    }
+
+    pub async fn dispatch(
+        &self,
+        event: SharedEvent,
+        partition: Option<String>,
+        context: Arc<dyn ContextPort>,
+        policy: RetryPolicy,
+    ) -> Result<EventHandle, Error> {
+        let id = next_message_id();
+        let provider_context = self.get_context();
+
+        let handle = self
+            .tracker
+            .spawn_or_wait(id, partition, move |admission| async move {
+                Self::deliver(&provider_context, id, event, context, policy, admission).await
+            })
+            .await?;
+
+        Ok(EventHandle::new(id, handle))
+    }
+
+    pub async fn broadcast<E: EventMessagePort>(
+        &self,
+        event: Arc<E>,
+        subscribers: Vec<Arc<dyn EventSubscriberPort<E>>>,
+        context: Arc<dyn ContextPort>,
+        policy: RetryPolicy,
+    ) -> Result<Vec<u64>, Error> {
+        let key = event.get_partition_key();
+
+        let mut ids = Vec::with_capacity(subscribers.len());
+
+        for subscriber in subscribers {
+            let id = next_message_id();
+            let event = event.clone();
+            let context = context.clone();
+            let policy = policy.clone();
+            let provider_context = self.get_context();
+
+            let partition = Self::get_partition(key.as_deref(), subscriber.get_name());
+
+            self.tracker
+                .spawn_or_wait(id, partition, move |admission| async move {
+                    let first_attempt_at = SystemTime::now();
+
+                    // Events dropped from a full queue are dead lettered without an attempt.
+                    let (result, attempts) = match admission {
+                        Ok(()) => {
+                            policy
+                                .run_counted(|| subscriber.handle(&event, context.clone()))
+                                .await
+                        }
+                        Err(error) => (Err(error), 0),
+                    };
+
+                    if let Err(error) = result {
+                        let failure =
+                            EventFailure::new(id, error).with_subscriber(subscriber.get_name());
+
+                        let stored = Self::store_letter(
+                            &provider_context,
+                            event.as_ref(),
+                            &context,
+                            &failure,
+                            attempts,
+                            first_attempt_at,
+                        )
+                        .await;
+
+                        Self::dead_letter(&provider_context, failure, stored).await;
+                    }
+                })
+                .await?;
+
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    pub async fn broadcast_with_handles<E: EventMessagePort>(
+        &self,
+        event: Arc<E>,
+        subscribers: Vec<Arc<dyn EventSubscriberPort<E>>>,
+        context: Arc<dyn ContextPort>,
+        policy: RetryPolicy,
+    ) -> Result<Vec<EventHandle>, Error> {
+        let key = event.get_partition_key();
+
+        let mut handles = Vec::with_capacity(subscribers.len());
+
+        for subscriber in subscribers {
+            let id = next_message_id();
+            let event = event.clone();
+            let context = context.clone();
+            let policy = policy.clone();
+            let provider_context = self.get_context();
+
+            let partition = Self::get_partition(key.as_deref(), subscriber.get_name());
+
+            let handle = self
+                .tracker
+                .spawn_or_wait(id, partition, move |admission| async move {
+                    let first_attempt_at = SystemTime::now();
+
+                    let (result, attempts) = match admission {
+                        Ok(()) => {
+                            policy
+                                .run_counted(|| subscriber.handle(&event, context.clone()))
+                                .await
+                        }
+                        Err(error) => (Err(error), 0),
+                    };
+
+                    let Err(error) = result else {
+                        return Ok(());
+                    };
+
+                    // The caller gets the same error the letter was stored with.
+                    let error = SharedError::new(error);
+
+                    let failure = EventFailure::new(id, Box::new(error.clone()))
+                        .with_subscriber(subscriber.get_name());
+
+                    let stored = Self::store_letter(
+                        &provider_context,
+                        event.as_ref(),
+                        &context,
+                        &failure,
+                        attempts,
+                        first_attempt_at,
+                    )
+                    .await;
+
+                    Self::dead_letter(&provider_context, failure, stored).await;
+
+                    Err(error.into())
+                })
+                .await?;
+
+            handles.push(EventHandle::new(id, handle));
+        }
+
+        Ok(handles)
+    }
+
+    pub async fn drain(&self) {
+        self.tracker.drain().await
+    }
+
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        self.tracker.shutdown(timeout).await
+    }
+
+    fn get_partition(key: Option<&str>, subscriber: &str) -> Option<String> {
+        key.map(|key| format!("{}:{}", subscriber, key))
+    }
+
+    fn send_shared(&self, event: SharedEvent, context: Arc<dyn ContextPort>) {
+        let id = next_message_id();
+        let provider_context = self.get_context();
+
+        let spawned = self.tracker.spawn(id, None, move |admission| async move {
+            let policy = match RetryProvider::resolve(&provider_context).await {
+                Ok(retry) => retry.get_default().clone(),
+                Err(error) => {
+                    let failure = EventFailure::new(id, error);
+
+                    return EventFailureProvider::report(&provider_context, failure).await;
+                }
+            };
+
+            let _ = Self::deliver(&provider_context, id, event, context, policy, admission).await;
+        });
+
+        if let Err(error) = spawned {
+            self.report_rejected(id, error);
+        }
+    }
+
+    fn report_rejected(&self, id: u64, error: Error) {
+        let provider_context = self.get_context();
+
+        tokio::spawn(async move {
+            EventFailureProvider::report(&provider_context, EventFailure::new(id, error)).await;
+        });
+    }
+
+    // Boxed events can't be encoded, so their letters keep the event for an in-process replay.
+    // Awaited events get the same error the letter was stored with.
+    async fn deliver(
+        provider_context: &Arc<dyn ContextPort>,
+        id: u64,
+        event: SharedEvent,
+        context: Arc<dyn ContextPort>,
+        policy: RetryPolicy,
+        admission: Result<(), Error>,
+    ) -> Result<(), Error> {
+        let first_attempt_at = SystemTime::now();
+
+        // Events dropped from a full queue are dead lettered without an attempt.
+        let (result, attempts) = match admission {
+            Ok(()) => policy.run_counted(|| event.execute(context.clone())).await,
+            Err(error) => (Err(error), 0),
+        };
+
+        let Err(error) = result else {
+            return Ok(());
+        };
+
+        let error = SharedError::new(error);
+
+        let failure = EventFailure::new(id, Box::new(error.clone()));
+
+        let stored = match DeadLetterProvider::resolve(provider_context).await {
+            Ok(Some(provider)) => {
+                let name = MessageContext::from_context(&context)
+                    .and_then(MessageContext::get_type_name)
+                    .unwrap_or("BoxedEvent");
+
+                let letter = Self::create_letter(
+                    name,
+                    String::new(),
+                    &context,
+                    &failure,
+                    attempts,
+                    first_attempt_at,
+                )
+                .with_event(event);
+
+                provider.get_store().push(letter).await
+            }
+            Ok(None) => Ok(()),
+            Err(error) => Err(error),
+        };
+
+        Self::dead_letter(provider_context, failure, stored).await;
+
+        Err(error.into())
+    }
+
+    // A letter that can't be encoded or stored is reported as a failure of its own.
+    async fn dead_letter(
+        context: &Arc<dyn ContextPort>,
+        failure: EventFailure,
+        stored: Result<(), Error>,
+    ) {
+        let id = failure.get_id();
+        let subscriber = failure.get_subscriber();
+
+        EventFailureProvider::report(context, failure).await;
+
+        if let Err(error) = stored {
+            let failure = EventFailure::new(id, error);
+
+            let failure = match subscriber {
+                Some(subscriber) => failure.with_subscriber(subscriber),
+                None => failure,
+            };
+
+            EventFailureProvider::report(context, failure).await;
+        }
+    }
+
+    // Only messages registered with `DeadLetterProvider` are stored.
+    async fn store_letter<E: EventMessagePort>(
+        provider_context: &Arc<dyn ContextPort>,
+        event: &E,
+        context: &Arc<dyn ContextPort>,
+        failure: &EventFailure,
+        attempts: u32,
+        first_attempt_at: SystemTime,
+    ) -> Result<(), Error> {
+        let Some(provider) = DeadLetterProvider::resolve(provider_context).await? else {
+            return Ok(());
+        };
+
+        let Some(message) = provider.encode(event) else {
+            return Ok(());
+        };
+
+        let (name, payload) = message?;
+
+        let letter =
+            Self::create_letter(name, payload, context, failure, attempts, first_attempt_at);
+
+        provider.get_store().push(letter).await
+    }
+
+    // Every letter gets an id of its own, an event may fail for several subscribers.
+    fn create_letter(
+        name: &str,
+        payload: String,
+        context: &Arc<dyn ContextPort>,
+        failure: &EventFailure,
+        attempts: u32,
+        first_attempt_at: SystemTime,
+    ) -> DeadLetter {
+        let envelope = MessageEnvelope::from_context(context).cloned();
+
+        DeadLetter::new(
+            MessageId::new(),
+            name,
+            payload,
+            failure.get_subscriber().unwrap_or_default(),
+            failure.get_error().to_string(),
+            attempts,
+            first_attempt_at,
+        )
+        .with_envelope(envelope)
+    }
 }
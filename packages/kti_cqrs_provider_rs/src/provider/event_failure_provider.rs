@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+
+use crate::{
+    models::event_failure::EventFailure, ports::event_failure_handler_port::EventFailureHandlerPort,
+};
+
+pub struct EventFailureProvider {
+    handler: Arc<dyn EventFailureHandlerPort>,
+}
+
+#[async_trait]
+impl AdapterPort<EventFailureProvider> for EventFailureProvider {
+    fn token() -> &'static str {
+        "EVENT_FAILURE_PROVIDER"
+    }
+}
+
+impl EventFailureProvider {
+    pub fn new(handler: Arc<dyn EventFailureHandlerPort>) -> Self {
+        Self { handler }
+    }
+
+    pub async fn handle(&self, failure: EventFailure) {
+        self.handler.handle(failure).await
+    }
+
+    // Without a registered handler the failure is dropped, the library never logs on its own.
+    pub async fn report(context: &Arc<dyn ContextPort>, failure: EventFailure) {
+        if !context.has_provider(Self::token()).await {
+            return;
+        }
+
+        if let Ok(provider) = Self::get_adapter(context).await {
+            provider.handle(failure).await
+        }
+    }
+}
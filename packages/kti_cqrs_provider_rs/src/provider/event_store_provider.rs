@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::adapter_port::AdapterPort;
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    models::{
+        aggregate_root::AggregateRoot, event_data::EventData, expected_version::ExpectedVersion,
+        message_id::MessageId,
+    },
+    ports::{
+        aggregate_event_port::AggregateEventPort, aggregate_port::AggregatePort,
+        event_store_port::EventStorePort, outbox_message_port::OutboxMessagePort,
+    },
+};
+
+#[derive(Clone)]
+pub struct EventStoreProvider {
+    store: Arc<dyn EventStorePort>,
+}
+
+#[async_trait]
+impl AdapterPort<EventStoreProvider> for EventStoreProvider {
+    fn token() -> &'static str {
+        "EVENT_STORE_PROVIDER"
+    }
+}
+
+impl EventStoreProvider {
+    pub fn new(store: Arc<dyn EventStorePort>) -> Self {
+        Self { store }
+    }
+
+    pub fn get_store(&self) -> Arc<dyn EventStorePort> {
+        self.store.clone()
+    }
+
+    pub async fn load<A: AggregatePort>(&self, id: &str) -> Result<AggregateRoot<A>, Error> {
+        let mut aggregate = AggregateRoot::<A>::new(id);
+
+        let events = self
+            .store
+            .read_stream(&aggregate.get_stream_id(), 0)
+            .await?;
+
+        for event in events {
+            aggregate.apply(&A::Event::decode(event.get_name(), event.get_payload())?);
+        }
+
+        Ok(aggregate)
+    }
+
+    pub async fn save<A: AggregatePort>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: Vec<A::Event>,
+    ) -> Result<u64, Error> {
+        if events.is_empty() {
+            return Ok(aggregate.get_version());
+        }
+
+        let data = events
+            .iter()
+            .map(|event| Ok(EventData::new(event.get_name(), event.encode()?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let expected = match aggregate.get_version() {
+            0 => ExpectedVersion::NoStream,
+            version => ExpectedVersion::Exact(version),
+        };
+
+        let version = self
+            .store
+            .append(&aggregate.get_stream_id(), expected, data)
+            .await?;
+
+        for event in &events {
+            aggregate.apply(event);
+        }
+
+        Ok(version)
+    }
+
+    // Events of the bus are stored in one stream per message name, an event with a
+    // message id is stored once however often it's delivered.
+    pub async fn append_event<E: OutboxMessagePort>(
+        &self,
+        event: &E,
+        message_id: Option<MessageId>,
+    ) -> Result<u64, Error> {
+        let data = EventData::new(E::get_name(), event.encode()?);
+
+        let data = match message_id {
+            Some(message_id) => data.with_message_id(message_id),
+            None => data,
+        };
+
+        self.store
+            .append(E::get_name(), ExpectedVersion::Any, vec![data])
+            .await
+    }
+}
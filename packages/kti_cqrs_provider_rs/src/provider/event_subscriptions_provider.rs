@@ -0,0 +1,84 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    adapters::{
+        projection_subscriber_adapter::ProjectionSubscriberAdapter,
+        saga_subscriber_adapter::SagaSubscriberAdapter,
+    },
+    ports::{
+        event_message_port::EventMessagePort, event_subscriber_port::EventSubscriberPort,
+        outbox_message_port::OutboxMessagePort, saga_handler_port::SagaHandlerPort,
+    },
+};
+
+type AnySubscriber = Arc<dyn Any + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct EventSubscriptionsProvider {
+    subscribers: HashMap<TypeId, Vec<AnySubscriber>>,
+}
+
+#[async_trait]
+impl AdapterPort<EventSubscriptionsProvider> for EventSubscriptionsProvider {
+    fn token() -> &'static str {
+        "EVENT_SUBSCRIPTIONS_PROVIDER"
+    }
+}
+
+impl EventSubscriptionsProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_subscriber<E: EventMessagePort>(
+        mut self,
+        subscriber: Arc<dyn EventSubscriberPort<E>>,
+    ) -> Self {
+        self.subscribers
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(Arc::new(subscriber));
+
+        self
+    }
+
+    pub fn with_saga<E: EventMessagePort, S: SagaHandlerPort<E>>(self, saga: Arc<S>) -> Self {
+        self.with_subscriber::<E>(Arc::new(SagaSubscriberAdapter::new(saga)))
+    }
+
+    // Stores published `E` events in the event store & catches projections up with them.
+    pub fn with_projection<E: OutboxMessagePort>(self) -> Self {
+        self.with_subscriber::<E>(Arc::new(ProjectionSubscriberAdapter::default()))
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_subscribers<E: EventMessagePort>(&self) -> Vec<Arc<dyn EventSubscriberPort<E>>> {
+        self.subscribers
+            .get(&TypeId::of::<E>())
+            .map(|subscribers| {
+                subscribers
+                    .iter()
+                    .filter_map(|subscriber| {
+                        subscriber.downcast_ref::<Arc<dyn EventSubscriberPort<E>>>()
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::ports::{
+    command_message_handler_port::CommandMessageHandlerPort,
+    command_message_port::CommandMessagePort, query_message_handler_port::QueryMessageHandlerPort,
+    query_message_port::QueryMessagePort,
+};
+
+type AnyHandler = Arc<dyn Any + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct HandlerRegistryProvider {
+    commands: HashMap<TypeId, AnyHandler>,
+    queries: HashMap<TypeId, AnyHandler>,
+}
+
+#[async_trait]
+impl AdapterPort<HandlerRegistryProvider> for HandlerRegistryProvider {
+    fn token() -> &'static str {
+        "HANDLER_REGISTRY_PROVIDER"
+    }
+}
+
+impl HandlerRegistryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_command<M: CommandMessagePort>(
+        mut self,
+        handler: Arc<dyn CommandMessageHandlerPort<M>>,
+    ) -> Self {
+        self.commands.insert(TypeId::of::<M>(), Arc::new(handler));
+
+        self
+    }
+
+    pub fn with_query<M: QueryMessagePort>(
+        mut self,
+        handler: Arc<dyn QueryMessageHandlerPort<M>>,
+    ) -> Self {
+        self.queries.insert(TypeId::of::<M>(), Arc::new(handler));
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_command_handler<M: CommandMessagePort>(
+        &self,
+    ) -> Result<Arc<dyn CommandMessageHandlerPort<M>>, Error> {
+        self.commands
+            .get(&TypeId::of::<M>())
+            .and_then(|handler| handler.downcast_ref::<Arc<dyn CommandMessageHandlerPort<M>>>())
+            .cloned()
+            .ok_or_else(|| {
+                format!("Command handler for {} is not registered", type_name::<M>()).into()
+            })
+    }
+
+    pub fn get_query_handler<M: QueryMessagePort>(
+        &self,
+    ) -> Result<Arc<dyn QueryMessageHandlerPort<M>>, Error> {
+        self.queries
+            .get(&TypeId::of::<M>())
+            .and_then(|handler| handler.downcast_ref::<Arc<dyn QueryMessageHandlerPort<M>>>())
+            .cloned()
+            .ok_or_else(|| {
+                format!("Query handler for {} is not registered", type_name::<M>()).into()
+            })
+    }
+}
@@ -0,0 +1,172 @@
+use std::{pin::pin, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    adapters::in_memory_idempotency_store_adapter::InMemoryIdempotencyStoreAdapter,
+    errors::idempotency_conflict_error::IdempotencyConflictError,
+    models::{idempotency_status::IdempotencyStatus, message_id::MessageId},
+    ports::{
+        idempotency_store_port::IdempotencyStorePort, idempotent_output_port::IdempotentOutputPort,
+    },
+};
+
+// Releases the key when the command future is dropped before it was completed.
+struct IdempotencyLease {
+    store: Arc<dyn IdempotencyStorePort>,
+    key: String,
+    owner: String,
+    completed: bool,
+}
+
+impl Drop for IdempotencyLease {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let store = self.store.clone();
+
+        let key = std::mem::take(&mut self.key);
+        let owner = std::mem::take(&mut self.owner);
+
+        runtime.spawn(async move {
+            let _ = store.release(&key, &owner).await;
+        });
+    }
+}
+
+#[derive(Clone)]
+pub struct IdempotencyProvider {
+    store: Arc<dyn IdempotencyStorePort>,
+    ttl: Duration,
+    lease: Duration,
+}
+
+#[async_trait]
+impl AdapterPort<IdempotencyProvider> for IdempotencyProvider {
+    fn token() -> &'static str {
+        "IDEMPOTENCY_PROVIDER"
+    }
+}
+
+impl Default for IdempotencyProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryIdempotencyStoreAdapter::new()))
+    }
+}
+
+impl IdempotencyProvider {
+    pub fn new(store: Arc<dyn IdempotencyStorePort>) -> Self {
+        Self {
+            store,
+            ttl: Duration::from_secs(24 * 60 * 60),
+            lease: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+
+        self
+    }
+
+    // How long a key stays in progress when its owner crashed, a running command renews it
+    // every half lease.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_store(&self) -> Arc<dyn IdempotencyStorePort> {
+        self.store.clone()
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get_lease(&self) -> Duration {
+        self.lease
+    }
+
+    // Keys are stored under `scope`, failed or dropped commands release them, so the client
+    // can retry.
+    pub async fn run<O: IdempotentOutputPort>(
+        &self,
+        scope: &str,
+        key: &str,
+        future: impl Future<Output = Result<O, Error>>,
+    ) -> Result<O, Error> {
+        let scoped = format!("{}:{}", scope, key);
+
+        let owner = MessageId::new().to_string();
+
+        match self.store.begin(&scoped, &owner, self.lease).await? {
+            IdempotencyStatus::Completed(value) => O::decode(&value),
+            IdempotencyStatus::InProgress => Err(IdempotencyConflictError::new(key).into()),
+            IdempotencyStatus::Started => {
+                let mut lease = IdempotencyLease {
+                    store: self.store.clone(),
+                    key: scoped,
+                    owner,
+                    completed: false,
+                };
+
+                let value = match self.renew_while(&lease, future).await {
+                    Ok(value) => value,
+                    Err(error) => {
+                        lease.completed = true;
+
+                        self.store.release(&lease.key, &lease.owner).await?;
+
+                        return Err(error);
+                    }
+                };
+
+                // The command already ran, a store failure only releases the key.
+                if let Ok(encoded) = value.encode() {
+                    lease.completed = self
+                        .store
+                        .complete(&lease.key, &lease.owner, encoded, self.ttl)
+                        .await
+                        .unwrap_or(false);
+                }
+
+                Ok(value)
+            }
+        }
+    }
+
+    // A failed renewal leaves the command running, its lease may expire then.
+    async fn renew_while<O>(
+        &self,
+        lease: &IdempotencyLease,
+        future: impl Future<Output = Result<O, Error>>,
+    ) -> Result<O, Error> {
+        let mut future = pin!(future);
+
+        loop {
+            if let Ok(result) = tokio::time::timeout(self.lease / 2, future.as_mut()).await {
+                return result;
+            }
+
+            let _ = self.store.renew(&lease.key, &lease.owner, self.lease).await;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    models::message_info::MessageInfo,
+    ports::middleware_port::{MiddlewareNext, MiddlewarePort},
+};
+
+#[derive(Clone, Default)]
+pub struct MiddlewareProvider {
+    middlewares: Vec<Arc<dyn MiddlewarePort>>,
+}
+
+#[async_trait]
+impl AdapterPort<MiddlewareProvider> for MiddlewareProvider {
+    fn token() -> &'static str {
+        "MIDDLEWARE_PROVIDER"
+    }
+}
+
+impl MiddlewareProvider {
+    pub fn new(middlewares: Vec<Arc<dyn MiddlewarePort>>) -> Self {
+        Self { middlewares }
+    }
+
+    pub fn with(mut self, middleware: Arc<dyn MiddlewarePort>) -> Self {
+        self.middlewares.push(middleware);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub async fn run<F, O>(&self, message: &MessageInfo, handler: F) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>> + Send,
+        O: Send,
+    {
+        self.enter(message).await?;
+
+        let mut output = None;
+
+        let mut next: MiddlewareNext<'_> = Box::pin(async {
+            output = Some(handler.await?);
+
+            Ok(())
+        });
+
+        for middleware in self.middlewares.iter().rev() {
+            next = Box::pin(middleware.around(message, next));
+        }
+
+        let result = next.await;
+
+        let result = match (result, output) {
+            (Ok(()), Some(output)) => Ok(output),
+            (Ok(()), None) => Err("Middleware completed without running the handler".into()),
+            (Err(error), _) => Err(error),
+        };
+
+        self.leave(message, result)
+    }
+
+    // For outputs without `Send`, which can't cross the `around` hooks.
+    pub async fn run_without_around<F, O>(
+        &self,
+        message: &MessageInfo,
+        handler: F,
+    ) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+    {
+        self.enter(message).await?;
+
+        let result = handler.await;
+
+        self.leave(message, result)
+    }
+
+    // Runs `before` of every middleware, unwinding the entered ones on rejection.
+    pub async fn enter(&self, message: &MessageInfo) -> Result<(), Error> {
+        for (entered, middleware) in self.middlewares.iter().enumerate() {
+            if let Err(error) = middleware.before(message).await {
+                return self.unwind(entered, message, Err(error));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn leave<O>(&self, message: &MessageInfo, result: Result<O, Error>) -> Result<O, Error> {
+        self.unwind(self.middlewares.len(), message, result)
+    }
+
+    fn unwind<O>(
+        &self,
+        entered: usize,
+        message: &MessageInfo,
+        mut result: Result<O, Error>,
+    ) -> Result<O, Error> {
+        for middleware in self.middlewares[..entered].iter().rev() {
+            if let Err(error) = middleware.after(message, result.as_ref().map(|_| ())) {
+                result = Err(error);
+            }
+        }
+
+        result
+    }
+}
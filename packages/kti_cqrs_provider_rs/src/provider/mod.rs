@@ -1,4 +1,22 @@
+pub mod batch_loader_provider;
 pub mod command_bus_provider;
 pub mod cqrs_provider;
+pub mod dead_letter_provider;
+pub mod event_bus_limits_provider;
 pub mod event_bus_provider;
+pub mod event_failure_provider;
+pub mod event_store_provider;
+pub mod event_subscriptions_provider;
+pub mod handler_registry_provider;
+pub mod idempotency_provider;
+pub mod middleware_provider;
+pub mod outbox_provider;
+pub mod projection_provider;
 pub mod query_bus_provider;
+pub mod query_cache_provider;
+pub mod recurring_jobs_provider;
+pub mod retry_provider;
+pub mod saga_provider;
+pub mod scheduler_provider;
+pub mod single_flight_provider;
+pub mod timeout_provider;
@@ -0,0 +1,77 @@
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::ports::{outbox_message_port::OutboxMessagePort, outbox_store_port::OutboxStorePort};
+
+use super::cqrs_provider::CqrsProvider;
+
+type OutboxDecoder = Arc<
+    dyn Fn(CqrsProvider, String) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+pub struct OutboxProvider {
+    store: Arc<dyn OutboxStorePort>,
+    decoders: HashMap<&'static str, OutboxDecoder>,
+}
+
+#[async_trait]
+impl AdapterPort<OutboxProvider> for OutboxProvider {
+    fn token() -> &'static str {
+        "OUTBOX_PROVIDER"
+    }
+}
+
+impl OutboxProvider {
+    pub fn new(store: Arc<dyn OutboxStorePort>) -> Self {
+        Self {
+            store,
+            decoders: HashMap::new(),
+        }
+    }
+
+    pub fn with_message<E: OutboxMessagePort>(mut self) -> Self {
+        let decoder: OutboxDecoder = Arc::new(|provider, payload| {
+            Box::pin(async move { provider.publish_and_wait(E::decode(&payload)?).await })
+        });
+
+        self.decoders.insert(E::get_name(), decoder);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Option<Self>, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(None);
+        }
+
+        Ok(Some(*Self::get_adapter(context).await?))
+    }
+
+    pub fn get_store(&self) -> Arc<dyn OutboxStorePort> {
+        self.store.clone()
+    }
+
+    pub fn has_message(&self, name: &str) -> bool {
+        self.decoders.contains_key(name)
+    }
+
+    pub async fn relay(
+        &self,
+        provider: CqrsProvider,
+        name: &str,
+        payload: String,
+    ) -> Result<(), Error> {
+        let decoder = self
+            .decoders
+            .get(name)
+            .ok_or_else(|| format!("Outbox message {} is not registered", name))?;
+
+        decoder(provider, payload).await
+    }
+}
@@ -0,0 +1,251 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    errors::projection_error::ProjectionError,
+    models::{
+        event_failure::EventFailure, poison_event_policy::PoisonEventPolicy,
+        skipped_event::SkippedEvent, stored_event::StoredEvent,
+    },
+    ports::{
+        checkpoint_store_port::CheckpointStorePort, event_store_port::EventStorePort,
+        projection_port::ProjectionPort,
+    },
+};
+
+use super::event_failure_provider::EventFailureProvider;
+
+struct RegisteredProjection {
+    projection: Arc<dyn ProjectionPort>,
+    lock: tokio::sync::Mutex<()>,
+    // The position that failed last & how many catch-ups it failed in.
+    failed: Mutex<(u64, u32)>,
+    skipped: Mutex<Vec<SkippedEvent>>,
+}
+
+impl RegisteredProjection {
+    fn fail(&self, position: u64) -> u32 {
+        let mut failed = self.failed.lock().unwrap_or_else(PoisonError::into_inner);
+
+        *failed = match *failed {
+            (failed, attempts) if failed == position => (position, attempts + 1),
+            _ => (position, 1),
+        };
+
+        failed.1
+    }
+
+    fn reset(&self) {
+        *self.failed.lock().unwrap_or_else(PoisonError::into_inner) = (0, 0);
+
+        self.lock_skipped().clear();
+    }
+
+    fn lock_skipped(&self) -> MutexGuard<'_, Vec<SkippedEvent>> {
+        self.skipped.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Clone)]
+pub struct ProjectionProvider {
+    checkpoints: Arc<dyn CheckpointStorePort>,
+    projections: Vec<Arc<RegisteredProjection>>,
+    batch_size: usize,
+    poison_policy: PoisonEventPolicy,
+}
+
+#[async_trait]
+impl AdapterPort<ProjectionProvider> for ProjectionProvider {
+    fn token() -> &'static str {
+        "PROJECTION_PROVIDER"
+    }
+}
+
+impl ProjectionProvider {
+    pub fn new(checkpoints: Arc<dyn CheckpointStorePort>) -> Self {
+        Self {
+            checkpoints,
+            projections: Vec::new(),
+            batch_size: 256,
+            poison_policy: PoisonEventPolicy::default(),
+        }
+    }
+
+    pub fn with_projection(mut self, projection: Arc<dyn ProjectionPort>) -> Self {
+        self.projections.push(Arc::new(RegisteredProjection {
+            projection,
+            lock: tokio::sync::Mutex::new(()),
+            failed: Mutex::new((0, 0)),
+            skipped: Mutex::new(Vec::new()),
+        }));
+
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+
+        self
+    }
+
+    pub fn with_poison_policy(mut self, poison_policy: PoisonEventPolicy) -> Self {
+        self.poison_policy = poison_policy;
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Option<Self>, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(None);
+        }
+
+        Ok(Some(*Self::get_adapter(context).await?))
+    }
+
+    pub fn get_checkpoints(&self) -> Arc<dyn CheckpointStorePort> {
+        self.checkpoints.clone()
+    }
+
+    pub fn get_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn get_poison_policy(&self) -> PoisonEventPolicy {
+        self.poison_policy
+    }
+
+    pub fn get_skipped_events(&self) -> Vec<SkippedEvent> {
+        self.projections
+            .iter()
+            .flat_map(|registered| registered.lock_skipped().clone())
+            .collect()
+    }
+
+    pub fn get_names(&self) -> Vec<&'static str> {
+        self.projections
+            .iter()
+            .map(|registered| registered.projection.get_name())
+            .collect()
+    }
+
+    pub async fn get_checkpoint(&self, name: &str) -> Result<u64, Error> {
+        self.checkpoints.load(name).await
+    }
+
+    // Returns the number of events applied across all projections, a failing
+    // projection does not hold back the others and is reported in `ProjectionError`.
+    pub async fn catch_up(
+        &self,
+        store: &dyn EventStorePort,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<usize, Error> {
+        let mut applied = 0;
+        let mut failures = Vec::new();
+
+        for registered in &self.projections {
+            let _lock = registered.lock.lock().await;
+
+            match self.run(registered, store, context.clone()).await {
+                Ok(count) => applied += count,
+                Err(error) => failures.push((registered.projection.get_name(), error)),
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(ProjectionError::new(applied, failures).into());
+        }
+
+        Ok(applied)
+    }
+
+    pub async fn rebuild(
+        &self,
+        name: &str,
+        store: &dyn EventStorePort,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<usize, Error> {
+        let registered = self
+            .projections
+            .iter()
+            .find(|registered| registered.projection.get_name() == name)
+            .ok_or_else(|| format!("Projection {} is not registered", name))?;
+
+        let _lock = registered.lock.lock().await;
+
+        registered.projection.reset().await?;
+
+        registered.reset();
+
+        self.checkpoints.save(name, 0).await?;
+
+        self.run(registered, store, context).await
+    }
+
+    // The checkpoint moves after every applied or skipped event, so a failure resumes
+    // from the first event that was not applied.
+    async fn run(
+        &self,
+        registered: &RegisteredProjection,
+        store: &dyn EventStorePort,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<usize, Error> {
+        let name = registered.projection.get_name();
+
+        let mut position = self.checkpoints.load(name).await?;
+
+        let mut applied = 0;
+
+        loop {
+            let events = store.read_all(position, self.batch_size).await?;
+
+            if events.is_empty() {
+                return Ok(applied);
+            }
+
+            for event in &events {
+                match registered.projection.apply(event, context.clone()).await {
+                    Ok(()) => applied += 1,
+                    Err(error) => self.skip(registered, event, error, &context).await?,
+                }
+
+                position = event.get_position();
+
+                self.checkpoints.save(name, position).await?;
+            }
+        }
+    }
+
+    // Hands the error back while the event may still block the projection.
+    async fn skip(
+        &self,
+        registered: &RegisteredProjection,
+        event: &StoredEvent,
+        error: Error,
+        context: &Arc<dyn ContextPort>,
+    ) -> Result<(), Error> {
+        let attempts = registered.fail(event.get_position());
+
+        match self.poison_policy {
+            PoisonEventPolicy::Skip { attempts: limit } if attempts >= limit => {}
+            _ => return Err(error),
+        }
+
+        let name = registered.projection.get_name();
+
+        registered.lock_skipped().push(SkippedEvent::new(
+            name,
+            event.clone(),
+            error.to_string(),
+            attempts,
+        ));
+
+        let failure = EventFailure::new(event.get_position(), error).with_subscriber(name);
+
+        EventFailureProvider::report(context, failure).await;
+
+        Ok(())
+    }
+}
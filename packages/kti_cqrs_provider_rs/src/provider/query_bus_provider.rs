@@ -7,8 +7,25 @@ use kti_cqrs_rs::{
     ports::{bus::query_bus_port::QueryBusPort, handler::query_handler_port::QueryHandlerPort},
 };
 
+use crate::{
+    models::{
+        cache_policy::CachePolicy, deadline::Deadline, message_context::MessageContext,
+        message_info::MessageInfo, message_kind::MessageKind, message_stream::MessageStream,
+        resolved_provider::ResolvedProvider,
+    },
+    ports::stream_query_handler_port::{QueryStream, StreamQueryHandlerPort},
+};
+
+use super::{
+    middleware_provider::MiddlewareProvider, query_cache_provider::QueryCacheProvider,
+    single_flight_provider::SingleFlightProvider,
+};
+
 pub struct QueryBusProvider {
     context: Arc<dyn ContextPort>,
+    pipeline: ResolvedProvider<MiddlewareProvider>,
+    cache: ResolvedProvider<QueryCacheProvider>,
+    flights: ResolvedProvider<SingleFlightProvider>,
 }
 
 #[async_trait]
@@ -25,16 +42,168 @@ impl QueryBusPort for QueryBusProvider {
         query: Box<dyn QueryHandlerPort<Context = C, Output = O>>,
         context: C,
     ) -> Result<O, Error> {
-        query.execute(context).await
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::new(MessageKind::Query);
+
+        pipeline
+            .run_without_around(&message, query.execute(context))
+            .await
     }
 }
 
 impl QueryBusProvider {
     pub fn new(context: Arc<dyn ContextPort>) -> Self {
-        Self { context }
+        Self {
+            context,
+            pipeline: ResolvedProvider::default(),
+            cache: ResolvedProvider::default(),
+            flights: ResolvedProvider::default(),
+        }
     }
 
     pub fn get_context(&self) -> Arc<dyn ContextPort> {
         self.context.clone()
     }
+
+    pub async fn get_pipeline(&self) -> Result<&MiddlewareProvider, Error> {
+        self.pipeline
+            .get_or_resolve(|| MiddlewareProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn get_cache(&self) -> Result<&QueryCacheProvider, Error> {
+        self.cache
+            .get_or_resolve(|| QueryCacheProvider::resolve(&self.context))
+            .await
+    }
+
+    // Like `send`, but middleware sees the envelope of the message context.
+    pub async fn send_message<O: Send>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<O, Error> {
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::from_context(MessageKind::Query, &context);
+
+        let handler = Deadline::enforce(&context, query.execute(context.clone()));
+
+        pipeline.run(&message, handler).await
+    }
+
+    pub async fn send_message_without_around<O>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<O, Error> {
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::from_context(MessageKind::Query, &context);
+
+        let handler = Deadline::enforce(&context, query.execute(context.clone()));
+
+        pipeline.run_without_around(&message, handler).await
+    }
+
+    pub async fn send_cached<O: Clone + Send + Sync + 'static>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        context: Arc<dyn ContextPort>,
+        policy: &CachePolicy,
+    ) -> Result<O, Error> {
+        let cache = self.get_cache().await?;
+
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::from_context(MessageKind::Query, &context);
+
+        let policy = QueryCacheProvider::scope(policy, Some(message.get_envelope()));
+
+        let handler = async {
+            let generation = cache.get_generation(&policy);
+
+            if let Some(value) = cache.get::<O>(policy.get_key()) {
+                return Ok(value);
+            }
+
+            let value = Deadline::enforce(&context, query.execute(context.clone())).await?;
+
+            cache.insert(&policy, generation, value.clone());
+
+            Ok(value)
+        };
+
+        pipeline.run(&message, handler).await
+    }
+
+    pub async fn get_single_flight(&self) -> Result<&SingleFlightProvider, Error> {
+        self.flights
+            .get_or_resolve(|| SingleFlightProvider::resolve(&self.context))
+            .await
+    }
+
+    pub async fn send_single_flight<O: Clone + Send + Sync + 'static>(
+        &self,
+        query: Box<dyn QueryHandlerPort<Context = Arc<dyn ContextPort>, Output = O>>,
+        context: Arc<dyn ContextPort>,
+        key: &str,
+    ) -> Result<O, Error> {
+        let flights = self.get_single_flight().await?;
+
+        let pipeline = self.get_pipeline().await?;
+
+        let message = MessageInfo::from_context(MessageKind::Query, &context);
+
+        // Every caller passes its own middleware, only the executing one runs the handler.
+        let key = match message.get_envelope().get_principal() {
+            Some(principal) => principal.scope(key),
+            None => key.to_string(),
+        };
+
+        let handler = flights.run(
+            &key,
+            Deadline::enforce(&context, query.execute(context.clone())),
+        );
+
+        pipeline.run(&message, handler).await
+    }
+
+    pub async fn send_stream<T: Send + 'static>(
+        &self,
+        query: Box<dyn StreamQueryHandlerPort<Item = T>>,
+        context: Arc<dyn ContextPort>,
+    ) -> Result<QueryStream<T>, Error> {
+        let pipeline = self.get_pipeline().await?.clone();
+
+        let message = MessageInfo::from_context(MessageKind::Query, &context);
+
+        let scope = MessageContext::from_context(&context);
+
+        let timeout = scope
+            .and_then(MessageContext::get_deadline)
+            .map(|deadline| deadline.get_timeout());
+
+        let cancellation = scope
+            .map(MessageContext::get_cancellation)
+            .unwrap_or_default();
+
+        pipeline.enter(&message).await?;
+
+        let handler = Deadline::enforce(&context, query.execute(context.clone()));
+
+        let stream = match handler.await {
+            Ok(stream) => stream,
+            Err(error) => return pipeline.leave(&message, Err(error)),
+        };
+
+        Ok(Box::pin(MessageStream::new(
+            stream,
+            pipeline,
+            message,
+            timeout,
+            cancellation,
+        )))
+    }
 }
@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::{
+    adapters::in_memory_query_cache_adapter::InMemoryQueryCacheAdapter,
+    models::{cache_policy::CachePolicy, message_envelope::MessageEnvelope},
+    ports::query_cache_port::QueryCachePort,
+};
+
+#[derive(Clone)]
+pub struct QueryCacheProvider {
+    store: Arc<dyn QueryCachePort>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    generations: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+#[async_trait]
+impl AdapterPort<QueryCacheProvider> for QueryCacheProvider {
+    fn token() -> &'static str {
+        "QUERY_CACHE_PROVIDER"
+    }
+}
+
+impl Default for QueryCacheProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryQueryCacheAdapter::default()))
+    }
+}
+
+impl QueryCacheProvider {
+    pub fn new(store: Arc<dyn QueryCachePort>) -> Self {
+        Self {
+            store,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+            generations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_store(&self) -> Arc<dyn QueryCachePort> {
+        self.store.clone()
+    }
+
+    pub fn get<O: Clone + 'static>(&self, key: &str) -> Option<O> {
+        let value = self
+            .store
+            .get(key)
+            .and_then(|value| value.downcast_ref::<O>().cloned());
+
+        let counter = match value {
+            Some(_) => &self.hits,
+            None => &self.misses,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        value
+    }
+
+    // Keys of queries sent on behalf of a principal are scoped to its tenant & user id.
+    pub fn scope(policy: &CachePolicy, envelope: Option<&MessageEnvelope>) -> CachePolicy {
+        let Some(principal) = envelope.and_then(MessageEnvelope::get_principal) else {
+            return policy.clone();
+        };
+
+        policy.clone().with_key(principal.scope(policy.get_key()))
+    }
+
+    // Read before running a query and passed to `insert`, so a result computed while one
+    // of its tags was invalidated is not stored. Generations only grow, so their sum does too.
+    pub fn get_generation(&self, policy: &CachePolicy) -> u64 {
+        Self::sum_generations(&self.lock_generations(), policy)
+    }
+
+    pub fn insert<O: Send + Sync + 'static>(
+        &self,
+        policy: &CachePolicy,
+        generation: u64,
+        value: O,
+    ) -> bool {
+        let generations = self.lock_generations();
+
+        if Self::sum_generations(&generations, policy) != generation {
+            return false;
+        }
+
+        self.store.insert(
+            policy.get_key(),
+            Arc::new(value),
+            policy.get_ttl(),
+            policy.get_tags(),
+        );
+
+        true
+    }
+
+    pub fn invalidate(&self, tags: &[String]) -> usize {
+        if tags.is_empty() {
+            return 0;
+        }
+
+        let mut generations = self.lock_generations();
+
+        for tag in tags {
+            *generations.entry(tag.clone()).or_default() += 1;
+        }
+
+        self.store.invalidate(tags)
+    }
+
+    pub fn get_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn get_misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn sum_generations(generations: &HashMap<String, u64>, policy: &CachePolicy) -> u64 {
+        policy
+            .get_tags()
+            .iter()
+            .filter_map(|tag| generations.get(tag))
+            .sum()
+    }
+
+    fn lock_generations(&self) -> MutexGuard<'_, HashMap<String, u64>> {
+        self.generations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
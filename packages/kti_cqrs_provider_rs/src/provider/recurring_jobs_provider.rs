@@ -0,0 +1,253 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+use tokio::{sync::Notify, task::AbortHandle};
+
+use crate::{
+    models::{
+        event_failure::EventFailure, message_info::next_message_id,
+        missed_run_policy::MissedRunPolicy, random::Random, recurring_job::RecurringJob,
+        recurring_job_stats::RecurringJobStats,
+    },
+    ports::clock_port::ClockPort,
+};
+
+use super::{cqrs_provider::CqrsProvider, event_failure_provider::EventFailureProvider};
+
+#[derive(Clone, Default)]
+pub struct RecurringJobsProvider {
+    jobs: Vec<RecurringJob>,
+    stats: HashMap<&'static str, Arc<RecurringJobStats>>,
+    tasks: Arc<Mutex<Vec<AbortHandle>>>,
+    runs: Arc<Mutex<HashMap<&'static str, AbortHandle>>>,
+    idle: Arc<Notify>,
+}
+
+#[async_trait]
+impl AdapterPort<RecurringJobsProvider> for RecurringJobsProvider {
+    fn token() -> &'static str {
+        "RECURRING_JOBS_PROVIDER"
+    }
+}
+
+impl RecurringJobsProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_job(mut self, job: RecurringJob) -> Self {
+        self.stats
+            .insert(job.get_name(), Arc::new(RecurringJobStats::new()));
+
+        self.jobs.push(job);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Option<Self>, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(None);
+        }
+
+        Ok(Some(*Self::get_adapter(context).await?))
+    }
+
+    pub fn get_names(&self) -> Vec<&'static str> {
+        self.jobs.iter().map(RecurringJob::get_name).collect()
+    }
+
+    pub fn get_stats(&self, name: &str) -> Option<Arc<RecurringJobStats>> {
+        self.stats.get(name).cloned()
+    }
+
+    pub fn is_started(&self) -> bool {
+        !self.lock_tasks().is_empty()
+    }
+
+    // Returns the number of started jobs, zero when they are already running.
+    pub fn start(&self, provider: CqrsProvider, clock: Arc<dyn ClockPort>) -> usize {
+        let mut tasks = self.lock_tasks();
+
+        if !tasks.is_empty() {
+            return 0;
+        }
+
+        for job in &self.jobs {
+            let stats = self.stats[job.get_name()].clone();
+
+            let task = tokio::spawn(self.clone().run_job(
+                job.clone(),
+                stats,
+                provider.clone(),
+                clock.clone(),
+            ));
+
+            tasks.push(task.abort_handle());
+        }
+
+        tasks.len()
+    }
+
+    // Stops scheduling new runs, call `drain` or `abort` for the runs already in progress.
+    pub fn stop(&self) -> usize {
+        let tasks = std::mem::take(&mut *self.lock_tasks());
+
+        for task in &tasks {
+            task.abort();
+        }
+
+        for stats in self.stats.values() {
+            stats.set_next_run(None);
+        }
+
+        tasks.len()
+    }
+
+    pub fn get_running(&self) -> Vec<&'static str> {
+        let mut running = self.lock_runs().keys().copied().collect::<Vec<_>>();
+
+        running.sort_unstable();
+
+        running
+    }
+
+    // Waits until every run in progress finished, call `stop` first so new ticks don't keep it waiting.
+    pub async fn drain(&self) {
+        loop {
+            let idle = self.idle.notified();
+
+            if self.lock_runs().is_empty() {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+
+    // Aborts the runs in progress and returns the names of their jobs.
+    pub fn abort(&self) -> Vec<&'static str> {
+        let mut runs = self.lock_runs();
+
+        let mut aborted = runs
+            .drain()
+            .map(|(name, run)| {
+                run.abort();
+
+                self.stats[name].cancel();
+
+                name
+            })
+            .collect::<Vec<_>>();
+
+        self.idle.notify_waiters();
+
+        aborted.sort_unstable();
+
+        aborted
+    }
+
+    async fn run_job(
+        self,
+        job: RecurringJob,
+        stats: Arc<RecurringJobStats>,
+        provider: CqrsProvider,
+        clock: Arc<dyn ClockPort>,
+    ) {
+        let schedule = job.get_schedule();
+
+        let mut next = schedule.next_after(clock.now());
+
+        while let Some(due) = next {
+            stats.set_next_run(Some(due));
+
+            let jitter = Random::duration_up_to(job.get_jitter());
+
+            clock.sleep_until(due + jitter).await;
+
+            // Missed runs are measured from the un-jittered tick, so a jitter longer than
+            // the interval delays runs instead of skipping them.
+            let now = clock.now().checked_sub(jitter).unwrap_or(due).max(due);
+
+            let mut missed = 0;
+
+            next = schedule.next_after(due);
+
+            while let Some(tick) = next.filter(|tick| *tick <= now) {
+                missed += 1;
+
+                next = schedule.next_after(tick);
+            }
+
+            match job.get_missed_run_policy() {
+                MissedRunPolicy::Skip if missed > 0 => stats.add_missed(missed + 1),
+                _ => {
+                    stats.add_missed(missed);
+
+                    self.trigger(&job, &stats, &provider);
+                }
+            }
+        }
+
+        stats.set_next_run(None);
+    }
+
+    // A tick that comes while the previous run is still going is skipped.
+    fn trigger(&self, job: &RecurringJob, stats: &Arc<RecurringJobStats>, provider: &CqrsProvider) {
+        if !stats.try_start() {
+            return;
+        }
+
+        let name = job.get_name();
+
+        let command = job.get_command();
+
+        let stats = stats.clone();
+
+        let provider = provider.clone();
+
+        let jobs = self.clone();
+
+        // Registered under the lock so the run can't finish before it is tracked.
+        let mut runs = self.lock_runs();
+
+        let run = tokio::spawn(async move {
+            let result = command(provider.clone()).await;
+
+            if let Err(error) = result {
+                let failure = EventFailure::new(next_message_id(), error).with_subscriber(name);
+
+                EventFailureProvider::report(&provider.get_context(), failure).await;
+            }
+
+            // Untracked first, the next tick is skipped until `stats` is released.
+            jobs.finish(name);
+
+            stats.finish();
+        });
+
+        runs.insert(name, run.abort_handle());
+    }
+
+    fn finish(&self, name: &'static str) {
+        let mut runs = self.lock_runs();
+
+        runs.remove(name);
+
+        if runs.is_empty() {
+            self.idle.notify_waiters();
+        }
+    }
+
+    fn lock_runs(&self) -> MutexGuard<'_, HashMap<&'static str, AbortHandle>> {
+        self.runs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_tasks(&self) -> MutexGuard<'_, Vec<AbortHandle>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,53 @@
+use std::{any::TypeId, collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+use crate::models::retry_policy::RetryPolicy;
+
+#[derive(Clone, Default)]
+pub struct RetryProvider {
+    default: RetryPolicy,
+    overrides: HashMap<TypeId, RetryPolicy>,
+}
+
+#[async_trait]
+impl AdapterPort<RetryProvider> for RetryProvider {
+    fn token() -> &'static str {
+        "RETRY_PROVIDER"
+    }
+}
+
+impl RetryProvider {
+    pub fn new(default: RetryPolicy) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn with_policy<M: 'static>(mut self, policy: RetryPolicy) -> Self {
+        self.overrides.insert(TypeId::of::<M>(), policy);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_default(&self) -> &RetryPolicy {
+        &self.default
+    }
+
+    pub fn get_policy<M: ?Sized + 'static>(&self) -> &RetryPolicy {
+        self.overrides
+            .get(&TypeId::of::<M>())
+            .unwrap_or(&self.default)
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{
+    adapters::in_memory_saga_store_adapter::InMemorySagaStoreAdapter,
+    ports::saga_store_port::SagaStorePort,
+};
+
+type SagaLocks = Arc<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>>;
+
+pub struct SagaGuard {
+    key: String,
+    locks: SagaLocks,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for SagaGuard {
+    fn drop(&mut self) {
+        let mut locks = lock_sagas(&self.locks);
+
+        // Only the map and this guard hold the lock, nobody is waiting for it.
+        if locks
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) <= 2)
+        {
+            locks.remove(&self.key);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SagaProvider {
+    store: Arc<dyn SagaStorePort>,
+    locks: SagaLocks,
+}
+
+#[async_trait]
+impl AdapterPort<SagaProvider> for SagaProvider {
+    fn token() -> &'static str {
+        "SAGA_PROVIDER"
+    }
+}
+
+impl Default for SagaProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemorySagaStoreAdapter::new()))
+    }
+}
+
+impl SagaProvider {
+    pub fn new(store: Arc<dyn SagaStorePort>) -> Self {
+        Self {
+            store,
+            locks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_store(&self) -> Arc<dyn SagaStorePort> {
+        self.store.clone()
+    }
+
+    // Serializes events of one saga instance, its state is read and written as a whole.
+    pub async fn lock(&self, saga: &str, correlation_id: &str) -> SagaGuard {
+        let key = format!("{}:{}", saga, correlation_id);
+
+        let lock = lock_sagas(&self.locks)
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        SagaGuard {
+            key,
+            locks: self.locks.clone(),
+            _guard: lock.lock_owned().await,
+        }
+    }
+}
+
+fn lock_sagas(locks: &SagaLocks) -> MutexGuard<'_, HashMap<String, Arc<AsyncMutex<()>>>> {
+    locks.lock().unwrap_or_else(PoisonError::into_inner)
+}
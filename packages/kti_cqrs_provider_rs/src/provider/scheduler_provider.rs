@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+use tokio::{
+    sync::{Notify, OnceCell},
+    task::AbortHandle,
+};
+
+use crate::{
+    adapters::system_clock_adapter::SystemClockAdapter,
+    models::{event_failure::EventFailure, unit_of_work::DeferredEvent},
+    ports::{
+        clock_port::ClockPort, outbox_message_port::OutboxMessagePort,
+        persistent_command_port::PersistentCommandPort, schedule_store_port::ScheduleStorePort,
+    },
+};
+
+use super::{cqrs_provider::CqrsProvider, event_failure_provider::EventFailureProvider};
+
+type ScheduleDecoder = Arc<dyn Fn(&str) -> Result<DeferredEvent, Error> + Send + Sync>;
+
+struct ScheduledTask {
+    abort: AbortHandle,
+    running: bool,
+}
+
+#[derive(Clone)]
+pub struct SchedulerProvider {
+    clock: Arc<dyn ClockPort>,
+    store: Option<Arc<dyn ScheduleStorePort>>,
+    decoders: HashMap<&'static str, ScheduleDecoder>,
+    last_id: Arc<AtomicU64>,
+    seeded: Arc<OnceCell<()>>,
+    tasks: Arc<Mutex<HashMap<u64, ScheduledTask>>>,
+    idle: Arc<Notify>,
+}
+
+#[async_trait]
+impl AdapterPort<SchedulerProvider> for SchedulerProvider {
+    fn token() -> &'static str {
+        "SCHEDULER_PROVIDER"
+    }
+}
+
+impl Default for SchedulerProvider {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClockAdapter::new()))
+    }
+}
+
+impl SchedulerProvider {
+    pub fn new(clock: Arc<dyn ClockPort>) -> Self {
+        Self {
+            clock,
+            store: None,
+            decoders: HashMap::new(),
+            last_id: Arc::new(AtomicU64::new(0)),
+            seeded: Arc::new(OnceCell::new()),
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+            idle: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn ScheduleStorePort>) -> Self {
+        self.store = Some(store);
+
+        self
+    }
+
+    pub fn with_message<E: OutboxMessagePort>(mut self) -> Self {
+        let decoder: ScheduleDecoder = Arc::new(|payload| {
+            let event = E::decode(payload)?;
+
+            let job: DeferredEvent = Box::new(move |provider| {
+                Box::pin(async move { provider.publish_and_wait(event).await })
+            });
+
+            Ok(job)
+        });
+
+        self.decoders.insert(E::get_name(), decoder);
+
+        self
+    }
+
+    pub fn with_command<C: PersistentCommandPort>(mut self) -> Self {
+        let decoder: ScheduleDecoder = Arc::new(|payload| {
+            let command = C::decode(payload)?;
+
+            let job: DeferredEvent = Box::new(move |provider| {
+                Box::pin(async move { provider.dispatch_command(command).await.map(|_| ()) })
+            });
+
+            Ok(job)
+        });
+
+        self.decoders.insert(C::get_name(), decoder);
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_clock(&self) -> Arc<dyn ClockPort> {
+        self.clock.clone()
+    }
+
+    pub fn get_store(&self) -> Option<Arc<dyn ScheduleStorePort>> {
+        self.store.clone()
+    }
+
+    pub fn has_message(&self, name: &str) -> bool {
+        self.decoders.contains_key(name)
+    }
+
+    // Ids of schedules that did not finish yet, including running ones.
+    pub fn get_pending(&self) -> Vec<u64> {
+        let mut pending = self.lock_tasks().keys().copied().collect::<Vec<_>>();
+
+        pending.sort_unstable();
+
+        pending
+    }
+
+    // The first id continues after the largest one in the store, so new schedules don't
+    // overwrite the records of a previous run.
+    pub async fn next_id(&self) -> Result<u64, Error> {
+        self.seeded
+            .get_or_try_init(|| async {
+                if let Some(store) = &self.store {
+                    for record in store.list().await? {
+                        self.reserve_id(record.get_id());
+                    }
+                }
+
+                Ok::<_, Error>(())
+            })
+            .await?;
+
+        Ok(self.last_id.fetch_add(1, Ordering::AcqRel) + 1)
+    }
+
+    // Restored ids are kept, so new schedules continue after the largest one.
+    pub fn reserve_id(&self, id: u64) {
+        self.last_id.fetch_max(id, Ordering::AcqRel);
+    }
+
+    pub fn decode(&self, name: &str, payload: &str) -> Result<DeferredEvent, Error> {
+        let decoder = self
+            .decoders
+            .get(name)
+            .ok_or_else(|| format!("Scheduled message {} is not registered", name))?;
+
+        decoder(payload)
+    }
+
+    pub fn schedule(
+        &self,
+        id: u64,
+        due_at: SystemTime,
+        provider: CqrsProvider,
+        job: DeferredEvent,
+    ) {
+        let mut tasks = self.lock_tasks();
+
+        let scheduler = self.clone();
+
+        // The lock is held until the handle is stored, so the task cant finish first.
+        let handle = tokio::spawn(async move {
+            scheduler.clock.sleep_until(due_at).await;
+
+            match scheduler.lock_tasks().get_mut(&id) {
+                Some(task) => task.running = true,
+                None => return,
+            }
+
+            let result = job(provider.clone()).await;
+
+            // A failed persisted job keeps its record, `restore_schedules` runs it again.
+            let result = match (&scheduler.store, result) {
+                (Some(store), Ok(())) => store.remove(id).await.map(|_| ()),
+                (_, result) => result,
+            };
+
+            scheduler.finish(id);
+
+            if let Err(error) = result {
+                EventFailureProvider::report(&provider.get_context(), EventFailure::new(id, error))
+                    .await;
+            }
+        });
+
+        tasks.insert(
+            id,
+            ScheduledTask {
+                abort: handle.abort_handle(),
+                running: false,
+            },
+        );
+    }
+
+    pub fn is_running(&self, id: u64) -> bool {
+        self.lock_tasks().get(&id).is_some_and(|task| task.running)
+    }
+
+    // Running jobs can't be cancelled.
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut tasks = self.lock_tasks();
+
+        if tasks.get(&id).is_none_or(|task| task.running) {
+            return false;
+        }
+
+        if let Some(task) = tasks.remove(&id) {
+            task.abort.abort();
+        }
+
+        self.notify_if_idle(&tasks);
+
+        true
+    }
+
+    // Cancels the jobs that are not due yet, persisted ones stay in the store.
+    pub fn stop(&self) -> Vec<u64> {
+        let mut tasks = self.lock_tasks();
+
+        let mut stopped = tasks
+            .iter()
+            .filter(|(_, task)| !task.running)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in &stopped {
+            if let Some(task) = tasks.remove(id) {
+                task.abort.abort();
+            }
+        }
+
+        self.notify_if_idle(&tasks);
+
+        stopped.sort_unstable();
+
+        stopped
+    }
+
+    // Waits until every tracked job finished, call `stop` first so timers don't keep it waiting.
+    pub async fn drain(&self) {
+        loop {
+            let idle = self.idle.notified();
+
+            if self.lock_tasks().is_empty() {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+
+    // Aborts the running jobs, their persisted records stay in the store.
+    pub fn abort(&self) -> Vec<u64> {
+        let mut tasks = self.lock_tasks();
+
+        let mut aborted = tasks
+            .drain()
+            .map(|(id, task)| {
+                task.abort.abort();
+
+                id
+            })
+            .collect::<Vec<_>>();
+
+        self.idle.notify_waiters();
+
+        aborted.sort_unstable();
+
+        aborted
+    }
+
+    fn finish(&self, id: u64) {
+        let mut tasks = self.lock_tasks();
+
+        tasks.remove(&id);
+
+        self.notify_if_idle(&tasks);
+    }
+
+    fn notify_if_idle(&self, tasks: &HashMap<u64, ScheduledTask>) {
+        if tasks.is_empty() {
+            self.idle.notify_waiters();
+        }
+    }
+
+    fn lock_tasks(&self) -> MutexGuard<'_, HashMap<u64, ScheduledTask>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
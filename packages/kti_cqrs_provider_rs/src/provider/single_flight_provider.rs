@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+use tokio::sync::OnceCell;
+
+use crate::{errors::shared_error::SharedError, ports::query_cache_port::CachedValue};
+
+type Flight = Arc<OnceCell<Result<CachedValue, SharedError>>>;
+
+#[derive(Clone, Default)]
+pub struct SingleFlightProvider {
+    flights: Arc<Mutex<HashMap<String, Flight>>>,
+    executions: Arc<AtomicU64>,
+    joined: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl AdapterPort<SingleFlightProvider> for SingleFlightProvider {
+    fn token() -> &'static str {
+        "SINGLE_FLIGHT_PROVIDER"
+    }
+}
+
+impl SingleFlightProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_in_flight(&self) -> usize {
+        self.lock_flights().len()
+    }
+
+    pub fn get_executions(&self) -> u64 {
+        self.executions.load(Ordering::Acquire)
+    }
+
+    pub fn get_joined(&self) -> u64 {
+        self.joined.load(Ordering::Acquire)
+    }
+
+    // When the executing caller is dropped, a waiting caller runs its own future instead.
+    pub async fn run<O: Clone + Send + Sync + 'static>(
+        &self,
+        key: &str,
+        future: impl Future<Output = Result<O, Error>>,
+    ) -> Result<O, Error> {
+        let flight = {
+            let mut flights = self.lock_flights();
+
+            match flights.get(key) {
+                Some(flight) => {
+                    self.joined.fetch_add(1, Ordering::AcqRel);
+
+                    flight.clone()
+                }
+                None => {
+                    let flight = Flight::default();
+
+                    flights.insert(key.to_string(), flight.clone());
+
+                    flight
+                }
+            }
+        };
+
+        let result = flight
+            .get_or_init(|| async {
+                self.executions.fetch_add(1, Ordering::AcqRel);
+
+                match future.await {
+                    Ok(value) => Ok(Arc::new(value) as CachedValue),
+                    Err(error) => Err(SharedError::new(error)),
+                }
+            })
+            .await;
+
+        {
+            let mut flights = self.lock_flights();
+
+            if flights
+                .get(key)
+                .is_some_and(|current| Arc::ptr_eq(current, &flight))
+            {
+                flights.remove(key);
+            }
+        }
+
+        match result {
+            Ok(value) => value.downcast_ref::<O>().cloned().ok_or_else(|| {
+                format!("Single flight key {} was used by another query", key).into()
+            }),
+            Err(error) => Err(error.clone().into()),
+        }
+    }
+
+    fn lock_flights(&self) -> MutexGuard<'_, HashMap<String, Flight>> {
+        self.flights.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,80 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use ioc_container_rs::ports::{adapter_port::AdapterPort, context_port::ContextPort};
+use kti_cqrs_rs::errors::error::Error;
+
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimeoutProvider {
+    command: Option<Duration>,
+    query: Option<Duration>,
+    grace: Duration,
+}
+
+impl Default for TimeoutProvider {
+    fn default() -> Self {
+        Self {
+            command: None,
+            query: None,
+            grace: DEFAULT_GRACE_PERIOD,
+        }
+    }
+}
+
+#[async_trait]
+impl AdapterPort<TimeoutProvider> for TimeoutProvider {
+    fn token() -> &'static str {
+        "TIMEOUT_PROVIDER"
+    }
+}
+
+impl TimeoutProvider {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            command: Some(timeout),
+            query: Some(timeout),
+            grace: DEFAULT_GRACE_PERIOD,
+        }
+    }
+
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command = Some(timeout);
+
+        self
+    }
+
+    pub fn with_query_timeout(mut self, timeout: Duration) -> Self {
+        self.query = Some(timeout);
+
+        self
+    }
+
+    // How long a timed out handler may still run after its cancellation token fired.
+    pub fn with_grace_period(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+
+        self
+    }
+
+    pub async fn resolve(context: &Arc<dyn ContextPort>) -> Result<Self, Error> {
+        if !context.has_provider(Self::token()).await {
+            return Ok(Self::default());
+        }
+
+        Ok(*Self::get_adapter(context).await?)
+    }
+
+    pub fn get_command_timeout(&self) -> Option<Duration> {
+        self.command
+    }
+
+    pub fn get_query_timeout(&self) -> Option<Duration> {
+        self.query
+    }
+
+    pub fn get_grace_period(&self) -> Duration {
+        self.grace
+    }
+}